@@ -0,0 +1,7 @@
+ALTER TABLE users
+ADD COLUMN phone_number TEXT UNIQUE,
+ADD COLUMN phone_confirmed BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TYPE auth_code_type ADD VALUE IF NOT EXISTS 'phone_confirmation';
+ALTER TYPE auth_code_type ADD VALUE IF NOT EXISTS 'phone_password_reset';
+ALTER TYPE auth_code_type ADD VALUE IF NOT EXISTS 'phone_log_in';
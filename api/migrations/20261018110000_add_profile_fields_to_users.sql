@@ -0,0 +1,17 @@
+ALTER TABLE users
+ADD COLUMN display_name TEXT,
+ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC',
+ADD COLUMN preferences JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+CREATE OR REPLACE FUNCTION set_updated_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = clock_timestamp();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER users_set_updated_at
+BEFORE UPDATE ON users
+FOR EACH ROW
+EXECUTE FUNCTION set_updated_at();
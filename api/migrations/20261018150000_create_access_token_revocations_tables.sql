@@ -0,0 +1,54 @@
+CREATE TABLE revoked_access_tokens (
+    jti TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_revoked_access_tokens_expires_at ON revoked_access_tokens(expires_at);
+
+CREATE TABLE user_access_token_revocations (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    revoked_before TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_user_access_token_revocations_expires_at ON user_access_token_revocations(expires_at);
+
+-- Every API instance LISTENs on this channel to keep its in-memory denylist current.
+CREATE FUNCTION notify_revoked_access_token() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify(
+        'access_token_revocations',
+        json_build_object(
+            'kind', 'token',
+            'jti', NEW.jti,
+            'expires_at', EXTRACT(EPOCH FROM NEW.expires_at)::BIGINT
+        )::TEXT
+    );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER revoked_access_tokens_notify
+    AFTER INSERT ON revoked_access_tokens
+    FOR EACH ROW EXECUTE FUNCTION notify_revoked_access_token();
+
+CREATE FUNCTION notify_user_access_token_revocation() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify(
+        'access_token_revocations',
+        json_build_object(
+            'kind', 'user',
+            'user_id', NEW.user_id,
+            'revoked_before', EXTRACT(EPOCH FROM NEW.revoked_before)::BIGINT,
+            'expires_at', EXTRACT(EPOCH FROM NEW.expires_at)::BIGINT
+        )::TEXT
+    );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER user_access_token_revocations_notify
+    AFTER INSERT OR UPDATE ON user_access_token_revocations
+    FOR EACH ROW EXECUTE FUNCTION notify_user_access_token_revocation();
@@ -0,0 +1,15 @@
+-- Per-user cut-offs are compared with the millisecond `iat_ms` access token claim.
+CREATE OR REPLACE FUNCTION notify_user_access_token_revocation() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify(
+        'access_token_revocations',
+        json_build_object(
+            'kind', 'user',
+            'user_id', NEW.user_id,
+            'revoked_before', FLOOR(EXTRACT(EPOCH FROM NEW.revoked_before) * 1000)::BIGINT,
+            'expires_at', EXTRACT(EPOCH FROM NEW.expires_at)::BIGINT
+        )::TEXT
+    );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
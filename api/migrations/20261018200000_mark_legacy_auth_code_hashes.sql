@@ -0,0 +1,8 @@
+-- Unkeyed SHA-256 code hashes now need an explicit `v1$` prefix to verify. Only this
+-- migration writes it, and only for codes still usable, so those keep working until
+-- they expire and no unkeyed hash is accepted after that.
+UPDATE auth_codes
+SET code_hash = 'v1$' || code_hash
+WHERE code_hash NOT LIKE 'v2$%'
+  AND used = false
+  AND expires_at > NOW();
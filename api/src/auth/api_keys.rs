@@ -0,0 +1,89 @@
+//! API key generation, parsing, and verification.
+//!
+//! Keys have the form `ak_<prefix>_<secret>`:
+//!
+//! - `prefix` - 8 random lowercase alphanumeric characters, stored in clear
+//!   text to look the key up and identify it in listings
+//! - `secret` - 40 random alphanumeric characters, stored only as a SHA-256
+//!   hash via [`crate::auth::secrets`]
+
+use crate::auth::secrets::{hash_secret, random_alphanumeric, random_lowercase_alphanumeric};
+
+/// Marker that starts every API key.
+pub const API_KEY_MARKER: &str = "ak_";
+
+/// Length of the public lookup prefix.
+const PREFIX_LENGTH: usize = 8;
+
+/// Length of the secret part of the key.
+const SECRET_LENGTH: usize = 40;
+
+/// A newly generated API key.
+pub struct GeneratedApiKey {
+    /// Full key shown to the user exactly once.
+    pub key: String,
+    /// Public lookup prefix.
+    pub prefix: String,
+    /// Hex-encoded SHA-256 hash of the secret.
+    pub secret_hash: String,
+}
+
+/// Generates a new random API key.
+pub fn generate_api_key() -> GeneratedApiKey {
+    let prefix = random_lowercase_alphanumeric(PREFIX_LENGTH);
+    let secret = random_alphanumeric(SECRET_LENGTH);
+
+    GeneratedApiKey {
+        key: format!("{API_KEY_MARKER}{prefix}_{secret}"),
+        secret_hash: hash_secret(&secret),
+        prefix,
+    }
+}
+
+/// Splits an API key into its prefix and secret.
+///
+/// Returns `None` when the value is not shaped like an API key.
+///
+/// # Arguments
+///
+/// - `key` - Full API key presented by a client
+pub fn parse_api_key(key: &str) -> Option<(&str, &str)> {
+    let (prefix, secret) = key.strip_prefix(API_KEY_MARKER)?.split_once('_')?;
+
+    let is_valid = prefix.len() == PREFIX_LENGTH
+        && secret.len() == SECRET_LENGTH
+        && prefix.bytes().all(|b| b.is_ascii_alphanumeric())
+        && secret.bytes().all(|b| b.is_ascii_alphanumeric());
+
+    is_valid.then_some((prefix, secret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_api_key, parse_api_key};
+    use crate::auth::secrets::verify_secret;
+
+    #[test]
+    // Verifies generated keys parse back into a prefix and a secret matching the stored hash.
+    fn generated_key_parses_and_verifies() {
+        let generated = generate_api_key();
+
+        let (prefix, secret) = parse_api_key(&generated.key).expect("generated key parses");
+        assert_eq!(prefix, generated.prefix);
+        assert!(verify_secret(secret, &generated.secret_hash));
+        assert!(!verify_secret("wrong", &generated.secret_hash));
+    }
+
+    #[test]
+    // Verifies values that are not shaped like API keys are rejected.
+    fn parse_api_key_rejects_malformed_values() {
+        assert_eq!(parse_api_key(""), None);
+        assert_eq!(parse_api_key("eyJhbGciOiJIUzI1NiJ9.e30.sig"), None);
+        assert_eq!(parse_api_key("ak_short_secret"), None);
+        assert_eq!(
+            parse_api_key(&format!("ak_abcd1234_{}", "x".repeat(39))),
+            None
+        );
+        assert!(parse_api_key(&format!("ak_abcd1234_{}", "x".repeat(40))).is_some());
+    }
+}
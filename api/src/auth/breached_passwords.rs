@@ -0,0 +1,348 @@
+//! Offline breached-password screening against a local SHA-1 corpus.
+//!
+//! Known-compromised passwords are rejected without calling an external API.
+//! A Have I Been Pwned "ordered by hash" download (one `SHA1:COUNT` line per
+//! password, sorted by hash) is converted once into a compact index with
+//! [`build_index`] (see the `build_breached_password_index` binary), and the
+//! API opens it at start-up from `BREACHED_PASSWORDS_INDEX_PATH`.
+//!
+//! The index stays on disk. Like the k-anonymity range API, entries are
+//! grouped by hash prefix: the file starts with a table of where each 16-bit
+//! prefix bucket begins, followed by the next 64 bits of every hash, sorted.
+//! A lookup reads the table entry from memory and binary-searches one bucket
+//! on disk.
+//!
+//! ```text
+//! magic           8 bytes   b"PWNDIDX1"
+//! bucket offsets  65537 x u64 (little-endian record indexes)
+//! records         N x 8 bytes (SHA-1 bytes 2..10, sorted within each bucket)
+//! ```
+//!
+//! Truncating hashes to 80 bits keeps the full corpus at 8 bytes per password,
+//! with a false-positive rate far below one in a billion lookups.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use log::warn;
+use sha1::{Digest, Sha1};
+
+/// Identifies an index file and its format version.
+const MAGIC: &[u8; 8] = b"PWNDIDX1";
+
+/// Number of hash-prefix buckets (one per leading 16 bits).
+const BUCKET_COUNT: usize = 1 << 16;
+
+/// Size in bytes of each stored hash record.
+const RECORD_LENGTH: u64 = 8;
+
+/// Byte offset of the first record.
+const RECORDS_START: u64 = MAGIC.len() as u64 + (BUCKET_COUNT as u64 + 1) * 8;
+
+/// Truncated SHA-1 used as the index key: the bucket and the record stored in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct IndexKey {
+    bucket: u16,
+    record: u64,
+}
+
+impl IndexKey {
+    /// Builds a key from a full 20-byte SHA-1 digest.
+    fn from_digest(digest: &[u8]) -> Self {
+        let mut record = [0u8; 8];
+        record.copy_from_slice(&digest[2..10]);
+
+        Self {
+            bucket: u16::from_be_bytes([digest[0], digest[1]]),
+            record: u64::from_be_bytes(record),
+        }
+    }
+
+    /// Hashes a password into its key.
+    fn for_password(password: &str) -> Self {
+        Self::from_digest(&Sha1::digest(password.as_bytes()))
+    }
+}
+
+/// On-disk index of breached password hashes.
+pub struct BreachedPasswordIndex {
+    /// Open index file, shared by lookups.
+    file: Mutex<File>,
+    /// Record index at which each bucket starts, plus the total record count.
+    bucket_offsets: Vec<u64>,
+}
+
+impl fmt::Debug for BreachedPasswordIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BreachedPasswordIndex")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl BreachedPasswordIndex {
+    /// Opens an index written by [`build_index`].
+    ///
+    /// Only the bucket table (about 512 KiB) is read into memory.
+    ///
+    /// # Arguments
+    ///
+    /// - `path` - Path to the index file
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the file cannot be read, or
+    /// [`io::ErrorKind::InvalidData`] if it is not a valid index.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+
+        let mut magic = [0u8; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a breached password index"));
+        }
+
+        let mut table = vec![0u8; (BUCKET_COUNT + 1) * 8];
+        file.read_exact(&mut table)?;
+        let bucket_offsets: Vec<u64> = table
+            .chunks_exact(8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().expect("chunks are 8 bytes")))
+            .collect();
+
+        let record_count = bucket_offsets[BUCKET_COUNT];
+        if bucket_offsets.windows(2).any(|pair| pair[0] > pair[1])
+            || file.metadata()?.len() != RECORDS_START + record_count * RECORD_LENGTH
+        {
+            return Err(invalid_data("breached password index is corrupt"));
+        }
+
+        Ok(Self {
+            file: Mutex::new(file),
+            bucket_offsets,
+        })
+    }
+
+    /// Returns the number of hashes in the index.
+    pub fn len(&self) -> u64 {
+        self.bucket_offsets[BUCKET_COUNT]
+    }
+
+    /// Returns whether the index holds no hashes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether a password appears in the breach corpus.
+    ///
+    /// Read errors are logged and treated as "not breached" so a damaged
+    /// index never blocks sign-ups or log-ins.
+    ///
+    /// # Arguments
+    ///
+    /// - `password` - Password to look up
+    pub fn contains(&self, password: &str) -> bool {
+        match self.contains_key(IndexKey::for_password(password)) {
+            Ok(found) => found,
+            Err(error) => {
+                warn!("Breached password lookup failed: {}", error);
+                false
+            }
+        }
+    }
+
+    /// Binary-searches the key's bucket on disk.
+    fn contains_key(&self, key: IndexKey) -> io::Result<bool> {
+        let bucket = usize::from(key.bucket);
+        let (mut low, mut high) = (self.bucket_offsets[bucket], self.bucket_offsets[bucket + 1]);
+
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| io::Error::other("breached password index lock poisoned"))?;
+        let mut record = [0u8; 8];
+
+        while low < high {
+            let middle = low + (high - low) / 2;
+            file.seek(SeekFrom::Start(RECORDS_START + middle * RECORD_LENGTH))?;
+            file.read_exact(&mut record)?;
+
+            match u64::from_be_bytes(record).cmp(&key.record) {
+                std::cmp::Ordering::Equal => return Ok(true),
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+/// Converts a Have I Been Pwned "ordered by hash" file into an index.
+///
+/// Each input line is a 40-character hex SHA-1, optionally followed by
+/// `:COUNT`; blank lines are skipped. Lines must be sorted by hash, which the
+/// official downloads are. Returns the number of hashes written.
+///
+/// # Arguments
+///
+/// - `input` - Corpus in HIBP format
+/// - `output` - Destination for the index
+/// - `min_count` - Skip hashes with a prevalence count below this; lines
+///   without a count always pass
+///
+/// # Errors
+///
+/// Returns an I/O error if reading or writing fails, or
+/// [`io::ErrorKind::InvalidData`] for malformed or unsorted input lines.
+pub fn build_index<R, W>(input: R, output: W, min_count: u64) -> io::Result<u64>
+where
+    R: BufRead,
+    W: Write + Seek,
+{
+    let mut writer = BufWriter::new(output);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&vec![0u8; (BUCKET_COUNT + 1) * 8])?;
+
+    let mut bucket_counts = vec![0u64; BUCKET_COUNT];
+    let mut previous: Option<IndexKey> = None;
+    let mut last_written: Option<IndexKey> = None;
+
+    for (line_number, line) in input.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (hash, count) = match line.split_once(':') {
+            Some((hash, count)) => (hash, Some(count)),
+            None => (line, None),
+        };
+        let digest = hex::decode(hash)
+            .ok()
+            .filter(|digest| digest.len() == 20)
+            .ok_or_else(|| invalid_data(format!("line {}: invalid SHA-1 hash", line_number + 1)))?;
+        let count = match count {
+            Some(count) => count
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| invalid_data(format!("line {}: invalid count", line_number + 1)))?,
+            None => u64::MAX,
+        };
+
+        let key = IndexKey::from_digest(&digest);
+        if previous.is_some_and(|previous| key < previous) {
+            return Err(invalid_data(format!(
+                "line {}: input is not sorted by hash",
+                line_number + 1
+            )));
+        }
+        previous = Some(key);
+
+        // Hashes sharing their first 80 bits collapse into one record.
+        if count < min_count || last_written == Some(key) {
+            continue;
+        }
+
+        writer.write_all(&key.record.to_be_bytes())?;
+        bucket_counts[usize::from(key.bucket)] += 1;
+        last_written = Some(key);
+    }
+
+    let mut table = Vec::with_capacity((BUCKET_COUNT + 1) * 8);
+    let mut offset = 0u64;
+    table.extend_from_slice(&offset.to_le_bytes());
+    for count in &bucket_counts {
+        offset += count;
+        table.extend_from_slice(&offset.to_le_bytes());
+    }
+
+    let mut output = writer.into_inner().map_err(|error| error.into_error())?;
+    output.seek(SeekFrom::Start(MAGIC.len() as u64))?;
+    output.write_all(&table)?;
+    output.flush()?;
+
+    Ok(offset)
+}
+
+/// Builds an [`io::ErrorKind::InvalidData`] error.
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::fs::File;
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    use sha1::{Digest, Sha1};
+    use uuid::Uuid;
+
+    use super::{BreachedPasswordIndex, build_index};
+
+    /// Writes an index of the given passwords to a temporary file.
+    pub(crate) fn index_of(passwords: &[&str]) -> (PathBuf, BreachedPasswordIndex) {
+        let mut hashes: Vec<String> = passwords
+            .iter()
+            .map(|password| hex::encode_upper(Sha1::digest(password.as_bytes())))
+            .collect();
+        hashes.sort();
+        let corpus: String = hashes.iter().map(|hash| format!("{}:3\n", hash)).collect();
+
+        let path = std::env::temp_dir().join(format!("breached-{}.idx", Uuid::new_v4()));
+        let file = File::create(&path).expect("index file should be created");
+        build_index(Cursor::new(corpus), file, 1).expect("index should build");
+
+        let index = BreachedPasswordIndex::open(&path).expect("index should open");
+        (path, index)
+    }
+
+    #[test]
+    // Verifies built indexes find corpus passwords and nothing else.
+    fn finds_breached_passwords() {
+        let (path, index) = index_of(&["password123", "letmein", "Velvet-Harbor-42"]);
+
+        assert_eq!(index.len(), 3);
+        assert!(index.contains("password123"));
+        assert!(index.contains("Velvet-Harbor-42"));
+        assert!(!index.contains("Password123"));
+        assert!(!index.contains("Quiet-Lantern-7"));
+
+        std::fs::remove_file(path).expect("index file should be removed");
+    }
+
+    #[test]
+    // Verifies entries below the minimum count are skipped and duplicates collapse.
+    fn filters_by_count_and_deduplicates() {
+        let hash = |password: &str| hex::encode_upper(Sha1::digest(password.as_bytes()));
+        let mut lines = [
+            format!("{}:1", hash("rare-password")),
+            format!("{}:50", hash("common-password")),
+            format!("{}:50", hash("common-password")),
+        ];
+        lines.sort();
+
+        let mut output = Cursor::new(Vec::new());
+        let written =
+            build_index(Cursor::new(lines.join("\n")), &mut output, 2).expect("index should build");
+
+        assert_eq!(written, 1);
+    }
+
+    #[test]
+    // Verifies unsorted and malformed corpora are rejected with the offending line.
+    fn rejects_unsorted_or_malformed_input() {
+        let corpus = "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF:1\n0000000000000000000000000000000000000000:1\n";
+        let error = build_index(Cursor::new(corpus), Cursor::new(Vec::new()), 1)
+            .expect_err("unsorted input should be rejected");
+        assert_eq!(error.to_string(), "line 2: input is not sorted by hash");
+
+        let error = build_index(Cursor::new("not-a-hash:1\n"), Cursor::new(Vec::new()), 1)
+            .expect_err("malformed input should be rejected");
+        assert_eq!(error.to_string(), "line 1: invalid SHA-1 hash");
+    }
+}
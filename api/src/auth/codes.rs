@@ -1,68 +1,263 @@
 //! One-time authentication code utilities.
 //!
-//! This module supports short numeric code flows (for example email confirmation,
-//! password reset, and authenticated email-change verification) by generating
-//! six-digit codes, hashing codes for storage, and verifying user input against
-//! stored hashes.
+//! This module supports short code flows (for example email confirmation,
+//! password reset, authenticated email-change verification, and SMS phone
+//! verification) by generating codes, hashing codes for storage, and
+//! verifying user input against stored hashes.
+//!
+//! Codes are drawn from a configurable alphabet and length (six digits by
+//! default) and stored as HMAC-SHA256 under a server secret, so database
+//! read access alone is not enough to recover them. Stored hashes are
+//! versioned:
+//!
+//! - `v2$<hex>` - HMAC-SHA256 keyed with `AUTH_CODE_SECRET`
+//! - `v1$<hex>` - plain SHA-256, written before keyed hashing. Only the
+//!   migration that introduced the prefix writes it, for codes that had not
+//!   expired yet, so these stop verifying once those codes expire
+//!
+//! Unprefixed hashes are never accepted.
 
+use std::fmt;
+
+use hmac::{Hmac, Mac};
 use rand::Rng;
 use sha2::{Digest, Sha256};
 
-/// Generates a random six-digit authentication code as a string.
-pub fn generate_auth_code() -> String {
-    let mut rng = rand::thread_rng();
-    let code: u32 = rng.gen_range(100000..1000000);
-    code.to_string()
-}
+use crate::core::env::Env;
 
-/// Hashes an authentication code using SHA-256 and returns a hex-encoded digest.
-///
-/// # Arguments
-///
-/// - `code` - Plain-text authentication code to hash
-pub fn hash_code(code: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(code.as_bytes());
-    hex::encode(hasher.finalize())
-}
+/// Alphabet of the default six-digit numeric codes.
+pub const NUMERIC_ALPHABET: &str = "0123456789";
+
+/// Uppercase letters and digits without easily confused glyphs (`0`/`O`,
+/// `1`/`I`/`L`).
+pub const UNAMBIGUOUS_ALPHANUMERIC_ALPHABET: &str = "23456789ABCDEFGHJKMNPQRSTUVWXYZ";
+
+/// Shortest accepted code length.
+pub const MIN_CODE_LENGTH: usize = 4;
+
+/// Longest accepted code length.
+pub const MAX_CODE_LENGTH: usize = 32;
 
-/// Verifies a plain-text code against a previously hashed code.
+/// Prefix marking hashes made with the keyed (v2) scheme.
+const KEYED_HASH_PREFIX: &str = "v2$";
+
+/// Prefix the keyed-hash migration added to unkeyed (v1) hashes of unexpired codes.
+const LEGACY_HASH_PREFIX: &str = "v1$";
+
+/// Resolves an `AUTH_CODE_ALPHABET` setting into the characters codes use.
 ///
-/// Uses constant-time comparison to reduce timing side-channel leakage.
+/// Accepts the presets `numeric` and `alphanumeric` (see
+/// [`UNAMBIGUOUS_ALPHANUMERIC_ALPHABET`]) or a literal list of characters.
 ///
 /// # Arguments
 ///
-/// - `code` - User-provided plain-text code
-/// - `hash` - Stored hex-encoded SHA-256 hash
-pub fn verify_code(code: &str, hash: &str) -> bool {
-    let code_hash = hash_code(code);
-    constant_time_compare(&code_hash, hash)
-}
-
-/// Hashes an email-change confirmation code scoped to a target email address.
-///
-/// Scoping the hash to the normalized email ensures a valid code for one email
-/// cannot be reused to confirm a different email address.
+/// - `value` - Configured alphabet
 ///
-/// # Arguments
+/// # Errors
 ///
-/// - `code` - Plain-text confirmation code
-/// - `new_email` - Target email address being confirmed
-pub fn hash_email_change_code(code: &str, new_email: &str) -> String {
+/// Returns a message if a literal alphabet has fewer than two distinct
+/// characters or contains whitespace or `-`, which input normalization strips.
+pub fn resolve_code_alphabet(value: &str) -> Result<String, String> {
+    let alphabet = match value.trim() {
+        "numeric" => return Ok(NUMERIC_ALPHABET.to_string()),
+        "alphanumeric" => return Ok(UNAMBIGUOUS_ALPHANUMERIC_ALPHABET.to_string()),
+        alphabet => alphabet,
+    };
+
+    if alphabet.chars().any(|c| c.is_whitespace() || c == '-') {
+        return Err("Auth code alphabet cannot contain whitespace or '-'".to_string());
+    }
+
+    let mut characters: Vec<char> = Vec::new();
+    for c in alphabet.chars() {
+        if !characters.contains(&c) {
+            characters.push(c);
+        }
+    }
+    if characters.len() < 2 {
+        return Err("Auth code alphabet needs at least 2 distinct characters".to_string());
+    }
+
+    Ok(characters.into_iter().collect())
+}
+
+/// Code format and hashing key for one-time authentication codes.
+#[derive(Clone)]
+pub struct AuthCodeConfig {
+    /// HMAC key for stored hashes.
+    secret: Vec<u8>,
+    /// Number of characters in generated codes.
+    length: usize,
+    /// Characters generated codes are drawn from.
+    alphabet: Vec<char>,
+}
+
+impl fmt::Debug for AuthCodeConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthCodeConfig")
+            .field("length", &self.length)
+            .field("alphabet", &self.alphabet.iter().collect::<String>())
+            .finish()
+    }
+}
+
+impl AuthCodeConfig {
+    /// Creates a configuration.
+    ///
+    /// # Arguments
+    ///
+    /// - `secret` - HMAC key for stored hashes
+    /// - `length` - Number of characters in generated codes
+    /// - `alphabet` - Characters generated codes are drawn from, as returned
+    ///   by [`resolve_code_alphabet`]
+    pub fn new(secret: &str, length: usize, alphabet: &str) -> Self {
+        Self {
+            secret: secret.as_bytes().to_vec(),
+            length,
+            alphabet: alphabet.chars().collect(),
+        }
+    }
+
+    /// Builds the configuration from `AUTH_CODE_*` environment settings.
+    pub fn from_env(env: &Env) -> Self {
+        Self::new(
+            &env.auth_code_secret,
+            env.auth_code_length,
+            &env.auth_code_alphabet,
+        )
+    }
+
+    /// Generates a random authentication code.
+    pub fn generate(&self) -> String {
+        let mut rng = rand::thread_rng();
+        (0..self.length)
+            .map(|_| self.alphabet[rng.gen_range(0..self.alphabet.len())])
+            .collect()
+    }
+
+    /// Hashes an authentication code for storage.
+    ///
+    /// # Arguments
+    ///
+    /// - `code` - Plain-text authentication code to hash
+    pub fn hash(&self, code: &str) -> String {
+        self.keyed_hash(&self.normalize(code))
+    }
+
+    /// Verifies a plain-text code against a stored `v2$` or `v1$` hash.
+    ///
+    /// # Arguments
+    ///
+    /// - `code` - User-provided plain-text code
+    /// - `hash` - Stored hash
+    pub fn verify(&self, code: &str, hash: &str) -> bool {
+        self.verify_message(&self.normalize(code), hash)
+    }
+
+    /// Hashes an email-change confirmation code scoped to a target email address.
+    ///
+    /// Scoping the hash to the normalized email ensures a valid code for one email
+    /// cannot be reused to confirm a different email address.
+    ///
+    /// # Arguments
+    ///
+    /// - `code` - Plain-text confirmation code
+    /// - `new_email` - Target email address being confirmed
+    pub fn hash_email_change(&self, code: &str, new_email: &str) -> String {
+        self.keyed_hash(&email_change_message(&self.normalize(code), new_email))
+    }
+
+    /// Verifies an email-change code against a stored scoped hash.
+    ///
+    /// # Arguments
+    ///
+    /// - `code` - User-provided plain-text code
+    /// - `new_email` - Target email address being confirmed
+    /// - `hash` - Stored hash
+    pub fn verify_email_change(&self, code: &str, new_email: &str, hash: &str) -> bool {
+        self.verify_message(
+            &email_change_message(&self.normalize(code), new_email),
+            hash,
+        )
+    }
+
+    /// Hashes a phone confirmation code scoped to the phone number being verified.
+    ///
+    /// Like email-change codes, scoping prevents a code sent to one number from
+    /// confirming a different number.
+    ///
+    /// # Arguments
+    ///
+    /// - `code` - Plain-text confirmation code
+    /// - `phone_number` - Normalized E.164 phone number being confirmed
+    pub fn hash_phone_confirmation(&self, code: &str, phone_number: &str) -> String {
+        self.keyed_hash(&phone_confirmation_message(
+            &self.normalize(code),
+            phone_number,
+        ))
+    }
+
+    /// Verifies a phone confirmation code against a stored scoped hash.
+    ///
+    /// # Arguments
+    ///
+    /// - `code` - User-provided plain-text code
+    /// - `phone_number` - Normalized E.164 phone number being confirmed
+    /// - `hash` - Stored hash
+    pub fn verify_phone_confirmation(&self, code: &str, phone_number: &str, hash: &str) -> bool {
+        self.verify_message(
+            &phone_confirmation_message(&self.normalize(code), phone_number),
+            hash,
+        )
+    }
+
+    /// Normalizes user input: drops whitespace and dashes, and uppercases it
+    /// when the alphabet has no lowercase letters.
+    fn normalize(&self, code: &str) -> String {
+        let uppercase = !self.alphabet.iter().any(char::is_ascii_lowercase);
+        code.chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .map(|c| if uppercase { c.to_ascii_uppercase() } else { c })
+            .collect()
+    }
+
+    /// Returns the versioned keyed hash of a message.
+    fn keyed_hash(&self, message: &str) -> String {
+        format!("{}{}", KEYED_HASH_PREFIX, self.keyed_digest(message))
+    }
+
+    /// Returns the hex-encoded HMAC-SHA256 of a message.
+    fn keyed_digest(&self, message: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(message.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// Checks a message against a stored `v2$` or `v1$` hash.
+    fn verify_message(&self, message: &str, hash: &str) -> bool {
+        if let Some(digest) = hash.strip_prefix(KEYED_HASH_PREFIX) {
+            return constant_time_compare(&self.keyed_digest(message), digest);
+        }
+
+        match hash.strip_prefix(LEGACY_HASH_PREFIX) {
+            Some(digest) => {
+                constant_time_compare(&hex::encode(Sha256::digest(message.as_bytes())), digest)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Builds the hashed message for an email-change code.
+fn email_change_message(code: &str, new_email: &str) -> String {
     let normalized_email = new_email.trim().to_lowercase();
-    hash_code(&format!("{normalized_email}:{code}"))
+    format!("{normalized_email}:{code}")
 }
 
-/// Verifies an email-change code against a stored scoped hash.
-///
-/// # Arguments
-///
-/// - `code` - User-provided plain-text code
-/// - `new_email` - Target email address being confirmed
-/// - `hash` - Stored hex-encoded SHA-256 hash
-pub fn verify_email_change_code(code: &str, new_email: &str, hash: &str) -> bool {
-    let code_hash = hash_email_change_code(code, new_email);
-    constant_time_compare(&code_hash, hash)
+/// Builds the hashed message for a phone confirmation code.
+fn phone_confirmation_message(code: &str, phone_number: &str) -> String {
+    format!("phone:{phone_number}:{code}")
 }
 
 /// Compares two strings in constant time when lengths match.
@@ -71,7 +266,7 @@ pub fn verify_email_change_code(code: &str, new_email: &str, hash: &str) -> bool
 ///
 /// - `a` - First string
 /// - `b` - Second string
-fn constant_time_compare(a: &str, b: &str) -> bool {
+pub(crate) fn constant_time_compare(a: &str, b: &str) -> bool {
     if a.len() != b.len() {
         return false;
     }
@@ -85,72 +280,134 @@ fn constant_time_compare(a: &str, b: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use sha2::{Digest, Sha256};
+
     use super::{
-        generate_auth_code, hash_code, hash_email_change_code, verify_code,
-        verify_email_change_code,
+        AuthCodeConfig, NUMERIC_ALPHABET, UNAMBIGUOUS_ALPHANUMERIC_ALPHABET, resolve_code_alphabet,
     };
 
+    fn numeric_codes() -> AuthCodeConfig {
+        AuthCodeConfig::new("test-secret", 6, NUMERIC_ALPHABET)
+    }
+
     #[test]
-    // Verifies generated auth codes are always six numeric characters.
-    fn generate_auth_code_returns_six_digit_numeric_value() {
-        let code = generate_auth_code();
+    // Verifies generated auth codes are always six numeric characters by default.
+    fn generate_returns_six_digit_numeric_value() {
+        let code = numeric_codes().generate();
 
         assert_eq!(code.len(), 6);
         assert!(code.chars().all(|c| c.is_ascii_digit()));
     }
 
     #[test]
-    // Verifies hashing is deterministic for the same auth code input.
-    fn hash_code_is_deterministic_for_same_input() {
-        let first = hash_code("123456");
-        let second = hash_code("123456");
+    // Verifies codes follow a configured length and alphabet and accept sloppy input.
+    fn generate_uses_configured_format() {
+        let codes = AuthCodeConfig::new("test-secret", 8, UNAMBIGUOUS_ALPHANUMERIC_ALPHABET);
+        let code = codes.generate();
 
-        assert_eq!(first, second);
-        assert_eq!(first.len(), 64);
+        assert_eq!(code.len(), 8);
+        assert!(
+            code.chars()
+                .all(|c| UNAMBIGUOUS_ALPHANUMERIC_ALPHABET.contains(c))
+        );
+
+        let hash = codes.hash(&code);
+        let typed = format!(" {}-{} ", &code[..4], &code[4..]).to_lowercase();
+        assert!(codes.verify(&typed, &hash));
+    }
+
+    #[test]
+    // Verifies hashes are keyed and versioned, so another secret cannot verify them.
+    fn hash_is_keyed_and_versioned() {
+        let hash = numeric_codes().hash("123456");
+
+        assert_eq!(hash, numeric_codes().hash("123456"));
+        assert!(hash.starts_with("v2$"));
+        assert_eq!(hash.len(), 67);
+        assert_ne!(hash[3..], hex::encode(Sha256::digest(b"123456")));
+        assert!(!AuthCodeConfig::new("other-secret", 6, NUMERIC_ALPHABET).verify("123456", &hash));
     }
 
     #[test]
     // Verifies code verification succeeds for matches and fails for mismatches.
-    fn verify_code_accepts_matching_and_rejects_non_matching_codes() {
-        let hash = hash_code("654321");
+    fn verify_accepts_matching_and_rejects_non_matching_codes() {
+        let hash = numeric_codes().hash("654321");
+
+        assert!(numeric_codes().verify("654321", &hash));
+        assert!(!numeric_codes().verify("111111", &hash));
+    }
+
+    #[test]
+    // Verifies unkeyed SHA-256 hashes marked `v1$` by the migration still verify.
+    fn verify_accepts_legacy_unkeyed_hashes() {
+        let codes = numeric_codes();
+        let legacy_hash = format!("v1${}", hex::encode(Sha256::digest(b"654321")));
+        let legacy_email_hash = format!(
+            "v1${}",
+            hex::encode(Sha256::digest(b"next@example.com:999999"))
+        );
+
+        assert!(codes.verify("654321", &legacy_hash));
+        assert!(!codes.verify("111111", &legacy_hash));
+        assert!(codes.verify_email_change("999999", "next@example.com", &legacy_email_hash));
+    }
 
-        assert!(verify_code("654321", &hash));
-        assert!(!verify_code("111111", &hash));
+    #[test]
+    // Verifies unprefixed SHA-256 hashes, which the migration retired, never verify.
+    fn verify_rejects_unprefixed_hashes() {
+        let codes = numeric_codes();
+
+        assert!(!codes.verify("654321", &hex::encode(Sha256::digest(b"654321"))));
+        assert!(!codes.verify("654321", "654321"));
     }
 
     #[test]
     // Verifies email-change hashes normalize casing/whitespace for target emails.
-    fn hash_email_change_code_normalizes_target_email() {
-        let first = hash_email_change_code("123456", " New.Email@Example.com ");
-        let second = hash_email_change_code("123456", "new.email@example.com");
+    fn hash_email_change_normalizes_target_email() {
+        let first = numeric_codes().hash_email_change("123456", " New.Email@Example.com ");
+        let second = numeric_codes().hash_email_change("123456", "new.email@example.com");
 
         assert_eq!(first, second);
     }
 
     #[test]
     // Verifies email-change verification is bound to the intended target email.
-    fn verify_email_change_code_requires_matching_email_scope() {
-        let hash = hash_email_change_code("999999", "next@example.com");
-
-        assert!(verify_email_change_code(
-            "999999",
-            "next@example.com",
-            &hash
-        ));
-        assert!(verify_email_change_code(
-            "999999",
-            " NEXT@EXAMPLE.COM ",
-            &hash
-        ));
-        assert!(!verify_email_change_code(
-            "999999",
-            "other@example.com",
-            &hash
-        ));
-        assert!(!verify_email_change_code(
-            "111111",
-            "next@example.com",
-            &hash
-        ));
+    fn verify_email_change_requires_matching_email_scope() {
+        let codes = numeric_codes();
+        let hash = codes.hash_email_change("999999", "next@example.com");
+
+        assert!(codes.verify_email_change("999999", "next@example.com", &hash));
+        assert!(codes.verify_email_change("999999", " NEXT@EXAMPLE.COM ", &hash));
+        assert!(!codes.verify_email_change("999999", "other@example.com", &hash));
+        assert!(!codes.verify_email_change("111111", "next@example.com", &hash));
+    }
+
+    #[test]
+    // Verifies phone confirmation verification is bound to the intended phone number.
+    fn verify_phone_confirmation_requires_matching_phone_scope() {
+        let codes = numeric_codes();
+        let hash = codes.hash_phone_confirmation("424242", "+15550100000");
+
+        assert!(codes.verify_phone_confirmation("424242", "+15550100000", &hash));
+        assert!(!codes.verify_phone_confirmation("424242", "+15550100001", &hash));
+        assert!(!codes.verify("424242", &hash));
+    }
+
+    #[test]
+    // Verifies alphabet presets resolve and unusable literal alphabets are rejected.
+    fn resolves_code_alphabets() {
+        assert_eq!(
+            resolve_code_alphabet("numeric").as_deref(),
+            Ok(NUMERIC_ALPHABET)
+        );
+        assert_eq!(
+            resolve_code_alphabet("alphanumeric").as_deref(),
+            Ok(UNAMBIGUOUS_ALPHANUMERIC_ALPHABET)
+        );
+        assert_eq!(resolve_code_alphabet("ABBA").as_deref(), Ok("AB"));
+
+        for invalid in ["", "AAAA", "AB CD", "AB-CD"] {
+            assert!(resolve_code_alphabet(invalid).is_err(), "{invalid}");
+        }
     }
 }
@@ -1,7 +1,8 @@
 //! HTTP cookie helpers for authentication tokens.
 //!
 //! This module centralizes secure cookie configuration for access and refresh
-//! tokens so handlers can set and clear auth cookies consistently.
+//! tokens, and the CSRF token paired with them, so handlers and middleware can
+//! set and clear auth cookies consistently.
 
 use actix_web::cookie::{Cookie, SameSite, time::Duration};
 
@@ -102,11 +103,64 @@ pub fn clear_refresh_token_cookie(domain: Option<&str>) -> Cookie<'static> {
     cookie.finish()
 }
 
+/// Builds the `csrf_token` cookie for double-submit CSRF protection.
+///
+/// Unlike the token cookies, it is readable by JavaScript so the frontend can
+/// echo it in the `X-CSRF-Token` header.
+///
+/// # Arguments
+///
+/// - `token` - Random CSRF token
+/// - `domain` - Optional cookie domain (for example `localhost` or production domain)
+/// - `secure` - Whether to mark the cookie as `Secure`
+/// - `max_age_seconds` - Cookie lifetime in seconds; a session cookie when `None`
+pub fn create_csrf_token_cookie<'a>(
+    token: &'a str,
+    domain: Option<&'a str>,
+    secure: bool,
+    max_age_seconds: Option<u64>,
+) -> Cookie<'a> {
+    let mut cookie = Cookie::build("csrf_token", token)
+        .path("/")
+        .http_only(false)
+        .secure(secure)
+        .same_site(SameSite::Strict);
+
+    if let Some(max_age_seconds) = max_age_seconds {
+        cookie = cookie.max_age(Duration::seconds(max_age_seconds as i64));
+    }
+
+    if let Some(domain) = domain.filter(|domain| !domain.trim().is_empty()) {
+        cookie = cookie.domain(domain.to_string());
+    }
+
+    cookie.finish()
+}
+
+/// Builds an expired `csrf_token` cookie to clear the browser value.
+///
+/// # Arguments
+///
+/// - `domain` - Optional cookie domain used when the CSRF cookie was originally set
+pub fn clear_csrf_token_cookie(domain: Option<&str>) -> Cookie<'static> {
+    let mut cookie = Cookie::build("csrf_token", "")
+        .path("/")
+        .http_only(false)
+        .same_site(SameSite::Strict)
+        .max_age(Duration::ZERO);
+
+    if let Some(domain) = domain.filter(|domain| !domain.trim().is_empty()) {
+        cookie = cookie.domain(domain.to_string());
+    }
+
+    cookie.finish()
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        clear_access_token_cookie, clear_refresh_token_cookie, create_access_token_cookie,
-        create_refresh_token_cookie,
+        clear_access_token_cookie, clear_csrf_token_cookie, clear_refresh_token_cookie,
+        create_access_token_cookie, create_csrf_token_cookie, create_refresh_token_cookie,
     };
     use actix_web::cookie::{SameSite, time::Duration};
 
@@ -175,4 +229,26 @@ mod tests {
 
         assert_eq!(cookie.max_age(), None);
     }
+
+    #[test]
+    // Verifies CSRF cookies are script-readable, follow the refresh lifetime, and clear on the same scope.
+    fn csrf_token_cookie_is_readable_and_clearable() {
+        let cookie = create_csrf_token_cookie("token", Some("localhost"), true, Some(3600));
+
+        assert_eq!(cookie.name(), "csrf_token");
+        assert_eq!(cookie.value(), "token");
+        assert_eq!(cookie.path(), Some("/"));
+        assert_eq!(cookie.http_only(), Some(false));
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(cookie.same_site(), Some(SameSite::Strict));
+        assert_eq!(cookie.max_age(), Some(Duration::seconds(3600)));
+
+        let session_cookie = create_csrf_token_cookie("token", None, false, None);
+        assert_eq!(session_cookie.max_age(), None);
+
+        let cleared = clear_csrf_token_cookie(Some("localhost"));
+        assert_eq!(cleared.value(), "");
+        assert_eq!(cleared.path(), Some("/"));
+        assert_eq!(cleared.max_age(), Some(Duration::ZERO));
+    }
 }
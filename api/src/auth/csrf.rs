@@ -0,0 +1,317 @@
+//! Double-submit CSRF protection for cookie-authenticated requests.
+//!
+//! Browsers attach the `access_token` and `refresh_token` cookies to
+//! cross-site requests, so a state-changing request carrying them must also
+//! prove it came from the frontend. [`csrf_protection`] requires:
+//!
+//! - an `Origin` (or, failing that, `Referer`) matching `CORS_ALLOWED_ORIGIN`
+//! - an `X-CSRF-Token` header equal to the script-readable `csrf_token` cookie
+//!
+//! The `csrf_token` cookie is issued alongside the refresh token cookie and
+//! cleared with it. Requests authenticated with a bearer token or API key, and
+//! requests without auth cookies, are not affected.
+
+use actix_web::{
+    Error, ResponseError,
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::Method,
+    middleware::Next,
+    web,
+};
+use rand::{Rng, distributions::Alphanumeric};
+use url::Url;
+
+use crate::auth::{
+    codes::constant_time_compare,
+    cookies::{clear_csrf_token_cookie, create_csrf_token_cookie},
+    middleware::bearer_token,
+};
+use crate::core::{app_state::AppState, error::ApiError};
+
+/// Name of the script-readable cookie holding the CSRF token.
+pub const CSRF_COOKIE_NAME: &str = "csrf_token";
+
+/// Header the frontend echoes the CSRF token in.
+pub const CSRF_HEADER_NAME: &str = "X-CSRF-Token";
+
+/// Number of characters in a generated CSRF token.
+const CSRF_TOKEN_LENGTH: usize = 32;
+
+/// Cookies whose presence makes a request cookie-authenticated.
+const AUTH_COOKIE_NAMES: [&str; 2] = ["access_token", "refresh_token"];
+
+/// Path prefixes whose routes never rely on auth cookies.
+///
+/// OAuth endpoints authenticate clients with credentials in the request body
+/// or `Authorization` header.
+const EXEMPT_PATH_PREFIXES: [&str; 1] = ["/oauth/"];
+
+/// Exact paths whose routes never rely on auth cookies.
+///
+/// The forward-auth endpoint is called by reverse proxies rather than
+/// browsers. It is matched exactly so that other `/auth/verify*` routes, such
+/// as `/auth/verify-forgot-password`, stay protected.
+const EXEMPT_PATHS: [&str; 1] = ["/auth/verify"];
+
+/// Generates a random CSRF token.
+pub fn generate_csrf_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(CSRF_TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+/// Actix middleware that enforces double-submit CSRF tokens.
+///
+/// Unsafe methods (anything but `GET`, `HEAD` and `OPTIONS`) sent with auth
+/// cookies are rejected with [`ApiError::CsrfValidationFailed`] unless their
+/// origin and `X-CSRF-Token` header check out. Responses that set the refresh
+/// token cookie also set a `csrf_token` cookie with the same lifetime, and
+/// responses that clear it clear the CSRF cookie too.
+///
+/// # Errors
+///
+/// Returns downstream handler/middleware errors from `next.call(req)`.
+pub async fn csrf_protection<B>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<BoxBody>, Error>
+where
+    B: MessageBody + 'static,
+{
+    let Some(state) = req.app_data::<web::Data<AppState>>().cloned() else {
+        let error = ApiError::InternalError("Application state not configured".to_string());
+        return Ok(req.into_response(error.error_response()));
+    };
+
+    if requires_csrf_check(&req) && !passes_csrf_check(&req, &state.env.cors_allowed_origin) {
+        return Ok(req.into_response(ApiError::CsrfValidationFailed.error_response()));
+    }
+
+    let request_token = req
+        .cookie(CSRF_COOKIE_NAME)
+        .map(|cookie| cookie.value().to_string())
+        .filter(|token| !token.is_empty());
+
+    let mut response = next.call(req).await?.map_into_boxed_body();
+
+    let refresh_cookie = response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "refresh_token")
+        .map(|cookie| {
+            (
+                cookie.value().is_empty(),
+                cookie
+                    .max_age()
+                    .map(|max_age| max_age.whole_seconds().max(0) as u64),
+            )
+        });
+
+    let csrf_cookie = match refresh_cookie {
+        Some((true, _)) => Some(clear_csrf_token_cookie(state.env.cookie_domain.as_deref())),
+        Some((false, max_age_seconds)) => {
+            let token = request_token.unwrap_or_else(generate_csrf_token);
+            Some(
+                create_csrf_token_cookie(
+                    &token,
+                    state.env.cookie_domain.as_deref(),
+                    state.env.cookie_secure,
+                    max_age_seconds,
+                )
+                .into_owned(),
+            )
+        }
+        None => None,
+    };
+
+    if let Some(csrf_cookie) = csrf_cookie {
+        response.response_mut().add_cookie(&csrf_cookie)?;
+    }
+
+    Ok(response)
+}
+
+/// Returns whether a request must pass the CSRF check.
+fn requires_csrf_check(req: &ServiceRequest) -> bool {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return false;
+    }
+
+    if EXEMPT_PATHS.contains(&req.path())
+        || EXEMPT_PATH_PREFIXES
+            .iter()
+            .any(|prefix| req.path().starts_with(prefix))
+    {
+        return false;
+    }
+
+    if bearer_token(req.request()).is_some() || req.headers().contains_key("X-API-Key") {
+        return false;
+    }
+
+    AUTH_COOKIE_NAMES.iter().any(|name| {
+        req.cookie(name)
+            .is_some_and(|cookie| !cookie.value().is_empty())
+    })
+}
+
+/// Returns whether a cookie-authenticated request has a trusted origin and a
+/// CSRF header matching its cookie.
+///
+/// # Arguments
+///
+/// - `req` - Incoming request
+/// - `allowed_origin` - The frontend origin from `CORS_ALLOWED_ORIGIN`
+fn passes_csrf_check(req: &ServiceRequest, allowed_origin: &str) -> bool {
+    if !has_allowed_origin(req, allowed_origin) {
+        return false;
+    }
+
+    let Some(cookie) = req.cookie(CSRF_COOKIE_NAME) else {
+        return false;
+    };
+    let Some(header) = req
+        .headers()
+        .get(CSRF_HEADER_NAME)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+
+    !cookie.value().is_empty() && constant_time_compare(cookie.value(), header)
+}
+
+/// Returns whether the `Origin` header, or the `Referer` when `Origin` is
+/// absent, matches the allowed origin.
+///
+/// Requests carrying neither header are rejected.
+fn has_allowed_origin(req: &ServiceRequest, allowed_origin: &str) -> bool {
+    let Some(allowed_origin) = normalize_origin(allowed_origin) else {
+        return false;
+    };
+
+    let headers = req.headers();
+    let request_origin = match headers.get("Origin") {
+        Some(origin) => origin.to_str().ok().and_then(normalize_origin),
+        None => headers
+            .get("Referer")
+            .and_then(|referer| referer.to_str().ok())
+            .and_then(normalize_origin),
+    };
+
+    request_origin.is_some_and(|origin| origin == allowed_origin)
+}
+
+/// Parses a URL or origin into its serialized `scheme://host[:port]` form.
+///
+/// Returns `None` for opaque origins such as `null`.
+fn normalize_origin(value: &str) -> Option<String> {
+    let origin = Url::parse(value.trim()).ok()?.origin();
+
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{cookie::Cookie, test::TestRequest};
+
+    use super::{generate_csrf_token, normalize_origin, passes_csrf_check, requires_csrf_check};
+
+    #[test]
+    // Verifies generated tokens are random alphanumeric strings of the expected length.
+    fn generated_tokens_are_random_alphanumeric() {
+        let token = generate_csrf_token();
+
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(token, generate_csrf_token());
+    }
+
+    #[test]
+    // Verifies only unsafe, cookie-authenticated, non-exempt requests are checked.
+    fn only_unsafe_cookie_requests_require_checks() {
+        let session = Cookie::new("refresh_token", "refresh");
+
+        let req = TestRequest::post()
+            .uri("/auth/refresh")
+            .cookie(session.clone())
+            .to_srv_request();
+        assert!(requires_csrf_check(&req));
+
+        let req = TestRequest::get()
+            .uri("/auth/me")
+            .cookie(session.clone())
+            .to_srv_request();
+        assert!(!requires_csrf_check(&req));
+
+        let req = TestRequest::post().uri("/auth/log-in").to_srv_request();
+        assert!(!requires_csrf_check(&req));
+
+        let req = TestRequest::post()
+            .uri("/auth/log-out")
+            .cookie(session.clone())
+            .insert_header(("Authorization", "Bearer token"))
+            .to_srv_request();
+        assert!(!requires_csrf_check(&req));
+
+        let req = TestRequest::post()
+            .uri("/oauth/token")
+            .cookie(session.clone())
+            .to_srv_request();
+        assert!(!requires_csrf_check(&req));
+
+        let req = TestRequest::post()
+            .uri("/auth/verify")
+            .cookie(session.clone())
+            .to_srv_request();
+        assert!(!requires_csrf_check(&req));
+
+        for path in [
+            "/auth/verify-forgot-password",
+            "/auth/verify-forgot-password-by-phone",
+        ] {
+            let req = TestRequest::post()
+                .uri(path)
+                .cookie(session.clone())
+                .to_srv_request();
+            assert!(requires_csrf_check(&req), "{path} should require a token");
+        }
+    }
+
+    #[test]
+    // Verifies the check requires a trusted origin and a header matching the cookie.
+    fn check_requires_trusted_origin_and_matching_token() {
+        let allowed_origin = "http://localhost:5173";
+        let request = |origin: (&'static str, &'static str), token: &'static str| {
+            TestRequest::post()
+                .uri("/auth/refresh")
+                .cookie(Cookie::new("csrf_token", "token"))
+                .insert_header(origin)
+                .insert_header(("X-CSRF-Token", token))
+                .to_srv_request()
+        };
+
+        let req = request(("Origin", "http://localhost:5173"), "token");
+        assert!(passes_csrf_check(&req, allowed_origin));
+
+        let req = request(("Referer", "http://localhost:5173/settings?tab=1"), "token");
+        assert!(passes_csrf_check(&req, allowed_origin));
+
+        let req = request(("Origin", "http://localhost:5173"), "other");
+        assert!(!passes_csrf_check(&req, allowed_origin));
+
+        let req = request(("Origin", "https://evil.example"), "token");
+        assert!(!passes_csrf_check(&req, allowed_origin));
+
+        let req = request(("Origin", "null"), "token");
+        assert!(!passes_csrf_check(&req, allowed_origin));
+
+        assert_eq!(
+            normalize_origin("HTTP://LOCALHOST:5173/"),
+            Some("http://localhost:5173".to_string())
+        );
+    }
+}
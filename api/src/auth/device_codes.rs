@@ -0,0 +1,104 @@
+//! Device and user code helpers for the OAuth 2.0 device authorization grant.
+//!
+//! - Device codes are 40 random alphanumeric characters kept by the device and
+//!   stored only as a SHA-256 hash
+//! - User codes are 8 characters from a 20-letter consonant alphabet (as
+//!   suggested by RFC 8628 section 6.1), shown as `XXXX-XXXX` and typed by the
+//!   user into a browser session
+//!
+//! User codes are compared after normalization, so case, spaces, and the
+//! separator dash do not matter.
+
+use rand::{Rng, distributions::Alphanumeric};
+use sha2::{Digest, Sha256};
+
+/// Characters used in user codes: uppercase consonants without easily confused letters.
+const USER_CODE_ALPHABET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+
+/// Number of characters in a user code.
+const USER_CODE_LENGTH: usize = 8;
+
+/// Length of the device code.
+const DEVICE_CODE_LENGTH: usize = 40;
+
+/// Generates a random device code.
+pub fn generate_device_code() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .map(char::from)
+        .take(DEVICE_CODE_LENGTH)
+        .collect()
+}
+
+/// Hashes a device code for storage and lookup, returning a hex-encoded SHA-256 digest.
+///
+/// Device codes carry about 238 bits of entropy, so an unkeyed hash cannot be
+/// reversed and stays usable as a lookup key.
+///
+/// # Arguments
+///
+/// - `device_code` - Plain-text device code
+pub fn hash_device_code(device_code: &str) -> String {
+    hex::encode(Sha256::digest(device_code.as_bytes()))
+}
+
+/// Generates a random normalized user code.
+pub fn generate_user_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..USER_CODE_LENGTH)
+        .map(|_| char::from(USER_CODE_ALPHABET[rng.gen_range(0..USER_CODE_ALPHABET.len())]))
+        .collect()
+}
+
+/// Normalizes user input into the stored user code form.
+///
+/// Uppercases the input and drops every character outside the user code
+/// alphabet, such as dashes and spaces.
+///
+/// # Arguments
+///
+/// - `input` - User code as typed by the user
+pub fn normalize_user_code(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| c.to_ascii_uppercase())
+        .filter(|c| c.is_ascii() && USER_CODE_ALPHABET.contains(&(*c as u8)))
+        .collect()
+}
+
+/// Formats a normalized user code for display as `XXXX-XXXX`.
+///
+/// # Arguments
+///
+/// - `user_code` - Normalized user code
+pub fn format_user_code(user_code: &str) -> String {
+    let (first, second) = user_code.split_at(user_code.len() / 2);
+    format!("{first}-{second}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_user_code, generate_user_code, normalize_user_code};
+
+    #[test]
+    // Verifies user codes survive formatting and sloppy user input.
+    fn user_code_round_trips_through_display_and_input() {
+        let user_code = generate_user_code();
+        assert_eq!(user_code.len(), 8);
+
+        let displayed = format_user_code(&user_code);
+        assert_eq!(displayed.len(), 9);
+        assert_eq!(normalize_user_code(&displayed), user_code);
+        assert_eq!(
+            normalize_user_code(&format!(" {} ", displayed.to_lowercase())),
+            user_code
+        );
+    }
+
+    #[test]
+    // Verifies characters outside the alphabet are dropped during normalization.
+    fn normalize_user_code_drops_foreign_characters() {
+        assert_eq!(normalize_user_code("wdjb-mjht"), "WDJBMJHT");
+        assert_eq!(normalize_user_code("AEIOU-0123"), "");
+    }
+}
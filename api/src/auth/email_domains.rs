@@ -0,0 +1,388 @@
+//! Email domain policy for sign-up and email changes.
+//!
+//! Deployments can restrict which email domains may register or be switched
+//! to:
+//!
+//! - An allowlist of domain patterns. In allowlist-only mode, every other
+//!   domain is rejected; otherwise allowlisted domains are simply exempt from
+//!   the other checks.
+//! - A denylist of domain patterns.
+//! - A list of disposable (throwaway) inbox providers. A copy is bundled with
+//!   the binary and can be replaced at start-up by a newer list on disk.
+//!
+//! Patterns are either an exact domain (`example.com`) or a wildcard that
+//! matches any subdomain (`*.example.com`, which does not match
+//! `example.com` itself).
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock};
+
+use crate::core::env::Env;
+
+/// Disposable email domains bundled with the binary, one per line.
+const BUNDLED_DISPOSABLE_DOMAINS: &str = include_str!("../../data/disposable_email_domains.txt");
+
+/// Parsed copy of [`BUNDLED_DISPOSABLE_DOMAINS`].
+static BUNDLED: LazyLock<Arc<DisposableEmailDomains>> =
+    LazyLock::new(|| Arc::new(DisposableEmailDomains::parse(BUNDLED_DISPOSABLE_DOMAINS)));
+
+/// An exact or wildcard-subdomain email domain pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmailDomainPattern {
+    /// Matches exactly this domain.
+    Exact(String),
+    /// Matches any subdomain of this domain, but not the domain itself.
+    Subdomains(String),
+}
+
+impl EmailDomainPattern {
+    /// Returns whether an email domain matches the pattern.
+    ///
+    /// # Arguments
+    ///
+    /// - `domain` - Lowercase email domain
+    pub fn matches(&self, domain: &str) -> bool {
+        match self {
+            Self::Exact(pattern) => domain == pattern,
+            Self::Subdomains(parent) => domain
+                .strip_suffix(parent.as_str())
+                .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.')),
+        }
+    }
+}
+
+impl FromStr for EmailDomainPattern {
+    type Err = String;
+
+    /// Parses `example.com` or `*.example.com`, ignoring case and surrounding
+    /// whitespace.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_lowercase();
+        let (wildcard, domain) = match value.strip_prefix("*.") {
+            Some(domain) => (true, domain),
+            None => (false, value.as_str()),
+        };
+
+        let valid = domain.contains('.')
+            && domain.split('.').all(|label| {
+                !label.is_empty()
+                    && !label.starts_with('-')
+                    && !label.ends_with('-')
+                    && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            });
+        if !valid {
+            return Err(format!("Invalid email domain pattern: {}", value));
+        }
+
+        Ok(if wildcard {
+            Self::Subdomains(domain.to_string())
+        } else {
+            Self::Exact(domain.to_string())
+        })
+    }
+}
+
+/// Parses a comma-separated list of domain patterns.
+///
+/// # Arguments
+///
+/// - `value` - Patterns such as `example.com,*.example.com`
+///
+/// # Errors
+///
+/// Returns a message naming the first invalid pattern.
+pub fn parse_domain_patterns(value: &str) -> Result<Vec<EmailDomainPattern>, String> {
+    value
+        .split(',')
+        .filter(|pattern| !pattern.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Set of disposable email domains.
+///
+/// A domain is disposable when it, or any domain it is a subdomain of, is in
+/// the set.
+#[derive(Debug, Default)]
+pub struct DisposableEmailDomains {
+    domains: HashSet<String>,
+}
+
+impl DisposableEmailDomains {
+    /// Returns the list bundled with the binary.
+    pub fn bundled() -> Arc<Self> {
+        BUNDLED.clone()
+    }
+
+    /// Reads a list from disk, in the same format as the bundled list.
+    ///
+    /// # Arguments
+    ///
+    /// - `path` - Path to a text file with one domain per line
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the file cannot be read.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Parses one domain per line, skipping blank lines and `#` comments.
+    ///
+    /// # Arguments
+    ///
+    /// - `contents` - List contents
+    pub fn parse(contents: &str) -> Self {
+        let domains = contents
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter(|line| !line.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+
+        Self { domains }
+    }
+
+    /// Returns the number of domains in the list.
+    pub fn len(&self) -> usize {
+        self.domains.len()
+    }
+
+    /// Returns whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty()
+    }
+
+    /// Returns whether a domain or one of its parent domains is disposable.
+    ///
+    /// # Arguments
+    ///
+    /// - `domain` - Lowercase email domain
+    pub fn contains(&self, domain: &str) -> bool {
+        let mut candidate = domain;
+        loop {
+            if self.domains.contains(candidate) {
+                return true;
+            }
+            match candidate.split_once('.') {
+                Some((_, parent)) if parent.contains('.') => candidate = parent,
+                _ => return false,
+            }
+        }
+    }
+}
+
+/// Why an email domain was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailDomainViolation {
+    /// Allowlist-only mode is on and the domain is not allowlisted.
+    NotAllowed,
+    /// The domain matches the denylist.
+    Denied,
+    /// The domain belongs to a disposable inbox provider.
+    Disposable,
+}
+
+impl EmailDomainViolation {
+    /// Returns the validation error code for this violation.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::NotAllowed => "email_domain_not_allowed",
+            Self::Denied => "email_domain_denied",
+            Self::Disposable => "email_domain_disposable",
+        }
+    }
+
+    /// Returns the user-facing message for this violation.
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::NotAllowed => "Email addresses from this domain cannot be used here",
+            Self::Denied => "Email addresses from this domain are not accepted",
+            Self::Disposable => "Disposable email addresses are not accepted",
+        }
+    }
+}
+
+impl fmt::Display for EmailDomainViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+/// Configured email domain rules.
+#[derive(Debug, Clone, Default)]
+pub struct EmailDomainPolicy {
+    /// Domains exempt from the other checks, or the only ones accepted in
+    /// allowlist-only mode.
+    pub allowlist: Vec<EmailDomainPattern>,
+    /// Whether only allowlisted domains are accepted.
+    pub allowlist_only: bool,
+    /// Domains that are always rejected unless allowlisted.
+    pub denylist: Vec<EmailDomainPattern>,
+    /// Disposable inbox providers to reject, when blocking them is enabled.
+    pub disposable_domains: Option<Arc<DisposableEmailDomains>>,
+}
+
+impl EmailDomainPolicy {
+    /// Builds the policy from the `EMAIL_DOMAIN_*` environment settings.
+    ///
+    /// Disposable domains are blocked with the bundled list when
+    /// `BLOCK_DISPOSABLE_EMAIL_DOMAINS` is on; use
+    /// [`with_disposable_domains`](Self::with_disposable_domains) to use a
+    /// list loaded from disk instead.
+    pub fn from_env(env: &Env) -> Self {
+        Self {
+            allowlist: env.email_domain_allowlist.clone(),
+            allowlist_only: env.email_domain_allowlist_only,
+            denylist: env.email_domain_denylist.clone(),
+            disposable_domains: env
+                .block_disposable_email_domains
+                .then(DisposableEmailDomains::bundled),
+        }
+    }
+
+    /// Replaces the disposable domain list, if blocking is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// - `disposable_domains` - Loaded list, or `None` to keep the current one
+    pub fn with_disposable_domains(
+        mut self,
+        disposable_domains: Option<Arc<DisposableEmailDomains>>,
+    ) -> Self {
+        if self.disposable_domains.is_some() && disposable_domains.is_some() {
+            self.disposable_domains = disposable_domains;
+        }
+        self
+    }
+
+    /// Checks an email address's domain against the policy.
+    ///
+    /// # Arguments
+    ///
+    /// - `email` - Email address, already validated as well-formed
+    ///
+    /// # Errors
+    ///
+    /// Returns the first rule the domain breaks.
+    pub fn check(&self, email: &str) -> Result<(), EmailDomainViolation> {
+        let domain = email
+            .rsplit_once('@')
+            .map_or(email, |(_, domain)| domain)
+            .trim()
+            .trim_end_matches('.')
+            .to_lowercase();
+
+        if self
+            .allowlist
+            .iter()
+            .any(|pattern| pattern.matches(&domain))
+        {
+            return Ok(());
+        }
+        if self.allowlist_only {
+            return Err(EmailDomainViolation::NotAllowed);
+        }
+        if self.denylist.iter().any(|pattern| pattern.matches(&domain)) {
+            return Err(EmailDomainViolation::Denied);
+        }
+        if self
+            .disposable_domains
+            .as_ref()
+            .is_some_and(|domains| domains.contains(&domain))
+        {
+            return Err(EmailDomainViolation::Disposable);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{
+        DisposableEmailDomains, EmailDomainPattern, EmailDomainPolicy, EmailDomainViolation,
+        parse_domain_patterns,
+    };
+
+    #[test]
+    // Verifies exact and wildcard patterns parse and match the right domains.
+    fn parses_and_matches_domain_patterns() {
+        let patterns = parse_domain_patterns(" Example.com , *.corp.example.com,")
+            .expect("patterns should parse");
+        assert_eq!(
+            patterns,
+            vec![
+                EmailDomainPattern::Exact("example.com".to_string()),
+                EmailDomainPattern::Subdomains("corp.example.com".to_string()),
+            ]
+        );
+
+        assert!(patterns[0].matches("example.com"));
+        assert!(!patterns[0].matches("mail.example.com"));
+        assert!(patterns[1].matches("eu.corp.example.com"));
+        assert!(!patterns[1].matches("corp.example.com"));
+        assert!(!patterns[1].matches("evilcorp.example.com"));
+
+        assert!(parse_domain_patterns("*.").is_err());
+        assert!(parse_domain_patterns("localhost").is_err());
+        assert!(parse_domain_patterns("exa mple.com").is_err());
+    }
+
+    #[test]
+    // Verifies allowlist-only mode, denylist, allowlist exemptions and disposable blocking.
+    fn checks_email_domains_against_policy() {
+        let disposable = Arc::new(DisposableEmailDomains::parse(
+            "# comment\nmailinator.com\n\nTempMail.dev # inline\n",
+        ));
+        assert_eq!(disposable.len(), 2);
+        assert!(disposable.contains("eu.mailinator.com"));
+        assert!(!disposable.contains("com"));
+
+        let policy = EmailDomainPolicy {
+            allowlist: parse_domain_patterns("tempmail.dev").expect("should parse"),
+            allowlist_only: false,
+            denylist: parse_domain_patterns("*.spam.example").expect("should parse"),
+            disposable_domains: Some(disposable),
+        };
+        assert_eq!(policy.check("ada@example.com"), Ok(()));
+        assert_eq!(policy.check("ada@tempmail.dev"), Ok(()));
+        assert_eq!(
+            policy.check("ada@Eu.Mailinator.com"),
+            Err(EmailDomainViolation::Disposable)
+        );
+        assert_eq!(
+            policy.check("ada@x.spam.example"),
+            Err(EmailDomainViolation::Denied)
+        );
+
+        let allowlist_only = EmailDomainPolicy {
+            allowlist: parse_domain_patterns("example.com,*.example.com").expect("should parse"),
+            allowlist_only: true,
+            ..EmailDomainPolicy::default()
+        };
+        assert_eq!(allowlist_only.check("ada@example.com"), Ok(()));
+        assert_eq!(allowlist_only.check("ada@eng.example.com"), Ok(()));
+        assert_eq!(
+            allowlist_only.check("ada@example.org"),
+            Err(EmailDomainViolation::NotAllowed)
+        );
+    }
+
+    #[test]
+    // Verifies the bundled disposable list loads and covers well-known providers.
+    fn bundled_disposable_list_covers_known_providers() {
+        let bundled = DisposableEmailDomains::bundled();
+
+        assert!(bundled.len() > 100);
+        assert!(bundled.contains("mailinator.com"));
+        assert!(!bundled.contains("gmail.com"));
+    }
+}
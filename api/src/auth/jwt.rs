@@ -1,8 +1,13 @@
 //! JWT claim types and token helpers for authentication.
 //!
 //! This module creates and validates access/refresh tokens used by the API.
-//! Access tokens carry user identity and email, while refresh tokens include
-//! a unique token identifier (`jti`) for rotation and revocation workflows.
+//! Access tokens carry user identity and email, and both access and refresh
+//! tokens include a unique token identifier (`jti`) for rotation and
+//! revocation workflows. Access tokens issued by re-authentication also record
+//! when and how the user proved their identity (`auth_time` and `amr`).
+//! Service tokens are issued to service accounts by the OAuth2
+//! `client_credentials` grant; they carry the `service` token type and scopes
+//! instead of an email, so they are never accepted as human sessions.
 
 use chrono::{Duration, Utc};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
@@ -22,8 +27,50 @@ pub struct AccessTokenClaims {
     pub exp: usize,
     /// Issued-at timestamp (Unix epoch seconds).
     pub iat: usize,
+    /// Issued-at timestamp (Unix epoch milliseconds).
+    ///
+    /// Compared with user-wide revocation cut-offs, so a revocation also
+    /// covers tokens issued earlier in the same second. Zero when decoding
+    /// older tokens that predate the claim.
+    #[serde(default)]
+    pub iat_ms: i64,
     /// Token type marker. Expected value: `access`.
     pub token_type: String,
+    /// Unique token identifier used for revocation.
+    ///
+    /// Empty when decoding older tokens that predate the claim; those can
+    /// only be revoked together with the rest of the user's tokens.
+    #[serde(default)]
+    pub jti: String,
+    /// The user's token version when the token was issued.
+    ///
+    /// Tokens are rejected once the user's version moves past it. Defaults
+    /// to `0`, the initial version, when decoding older tokens.
+    #[serde(default)]
+    pub token_version: i32,
+    /// When the user last proved their identity (Unix epoch seconds).
+    ///
+    /// Only set on tokens issued by re-authentication.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_time: Option<usize>,
+    /// Authentication methods used at `auth_time`, such as `pwd` or `sms`
+    /// (RFC 8176 values).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub amr: Vec<String>,
+}
+
+impl AccessTokenClaims {
+    /// Returns when the token was issued, in Unix epoch milliseconds.
+    ///
+    /// Older tokens without `iat_ms` count as issued at the start of their
+    /// `iat` second.
+    pub fn issued_at_millis(&self) -> i64 {
+        if self.iat_ms > 0 {
+            self.iat_ms
+        } else {
+            self.iat as i64 * 1000
+        }
+    }
 }
 
 /// Claims stored in long-lived refresh tokens.
@@ -47,12 +94,30 @@ pub struct RefreshTokenClaims {
     pub remember_me: bool,
 }
 
+/// Claims stored in short-lived service account tokens.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServiceTokenClaims {
+    /// Service account ID as a UUID string.
+    pub sub: String,
+    /// OAuth2 client ID of the service account.
+    pub client_id: String,
+    /// Granted scopes, space-delimited as in OAuth2.
+    pub scope: String,
+    /// Expiration timestamp (Unix epoch seconds).
+    pub exp: usize,
+    /// Issued-at timestamp (Unix epoch seconds).
+    pub iat: usize,
+    /// Token type marker. Expected value: `service`.
+    pub token_type: String,
+}
+
 /// Creates and signs an access token for a user.
 ///
 /// # Arguments
 ///
 /// - `user_id` - Authenticated user's unique identifier
 /// - `email` - Authenticated user's email address
+/// - `token_version` - User's current token version
 /// - `secret` - JWT signing secret
 /// - `expiry_seconds` - Access token lifetime in seconds
 ///
@@ -62,6 +127,64 @@ pub struct RefreshTokenClaims {
 pub fn create_access_token(
     user_id: Uuid,
     email: &str,
+    token_version: i32,
+    secret: &str,
+    expiry_seconds: u64,
+) -> Result<String, ApiError> {
+    encode_access_token(
+        user_id,
+        email,
+        token_version,
+        Vec::new(),
+        secret,
+        expiry_seconds,
+    )
+}
+
+/// Creates and signs an access token for a user who has just re-authenticated.
+///
+/// The token's `auth_time` is its issue time, which
+/// [`RecentlyAuthenticated`](crate::auth::middleware::RecentlyAuthenticated)
+/// checks before sensitive actions.
+///
+/// # Arguments
+///
+/// - `user_id` - Authenticated user's unique identifier
+/// - `email` - Authenticated user's email address
+/// - `token_version` - User's current token version
+/// - `amr` - Authentication methods the user just used, such as `pwd`
+/// - `secret` - JWT signing secret
+/// - `expiry_seconds` - Access token lifetime in seconds
+///
+/// # Errors
+///
+/// Returns [`ApiError`] if token signing fails.
+pub fn create_reauthenticated_access_token(
+    user_id: Uuid,
+    email: &str,
+    token_version: i32,
+    amr: &[&str],
+    secret: &str,
+    expiry_seconds: u64,
+) -> Result<String, ApiError> {
+    encode_access_token(
+        user_id,
+        email,
+        token_version,
+        amr.iter().map(|method| method.to_string()).collect(),
+        secret,
+        expiry_seconds,
+    )
+}
+
+/// Builds and signs access token claims.
+///
+/// `auth_time` is set to the issue time when `amr` is not empty.
+fn encode_access_token(
+    user_id: Uuid,
+    email: &str,
+    token_version: i32,
+    amr: Vec<String>,
     secret: &str,
     expiry_seconds: u64,
 ) -> Result<String, ApiError> {
@@ -74,7 +197,12 @@ pub fn create_access_token(
         email: email.to_string(),
         exp,
         iat,
+        iat_ms: now.timestamp_millis(),
         token_type: "access".to_string(),
+        jti: Uuid::new_v4().to_string(),
+        token_version,
+        auth_time: (!amr.is_empty()).then_some(iat),
+        amr,
     };
 
     let token = encode(
@@ -128,9 +256,52 @@ pub fn create_refresh_token(
     Ok((token, jti))
 }
 
+/// Creates and signs a service token for a service account.
+///
+/// # Arguments
+///
+/// - `service_account_id` - Service account's unique identifier
+/// - `client_id` - Service account's OAuth2 client ID
+/// - `scopes` - Scope names granted to the token
+/// - `secret` - JWT signing secret
+/// - `expiry_seconds` - Service token lifetime in seconds
+///
+/// # Errors
+///
+/// Returns [`ApiError`] if token signing fails.
+pub fn create_service_token(
+    service_account_id: Uuid,
+    client_id: &str,
+    scopes: &[String],
+    secret: &str,
+    expiry_seconds: u64,
+) -> Result<String, ApiError> {
+    let now = Utc::now();
+    let exp = (now + Duration::seconds(expiry_seconds as i64)).timestamp() as usize;
+    let iat = now.timestamp() as usize;
+
+    let claims = ServiceTokenClaims {
+        sub: service_account_id.to_string(),
+        client_id: client_id.to_string(),
+        scope: scopes.join(" "),
+        exp,
+        iat,
+        token_type: "service".to_string(),
+    };
+
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )?;
+
+    Ok(token)
+}
+
 /// Decodes and validates an access token.
 ///
-/// Also verifies the custom `token_type` claim is `access`.
+/// Also verifies the custom `token_type` claim is `access`, so refresh and
+/// service tokens are never accepted as human sessions.
 ///
 /// # Arguments
 ///
@@ -154,6 +325,32 @@ pub fn decode_access_token(token: &str, secret: &str) -> Result<AccessTokenClaim
     Ok(token_data.claims)
 }
 
+/// Decodes and validates a service token.
+///
+/// Also verifies the custom `token_type` claim is `service`.
+///
+/// # Arguments
+///
+/// - `token` - JWT service token string
+/// - `secret` - JWT verification secret
+///
+/// # Errors
+///
+/// Returns [`ApiError::TokenInvalid`] for wrong token type or invalid token data.
+pub fn decode_service_token(token: &str, secret: &str) -> Result<ServiceTokenClaims, ApiError> {
+    let token_data = decode::<ServiceTokenClaims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &Validation::default(),
+    )?;
+
+    if token_data.claims.token_type != "service" {
+        return Err(ApiError::TokenInvalid);
+    }
+
+    Ok(token_data.claims)
+}
+
 /// Decodes and validates a refresh token.
 ///
 /// Also verifies the custom `token_type` claim is `refresh`.
@@ -185,7 +382,8 @@ mod tests {
     use uuid::Uuid;
 
     use super::{
-        create_access_token, create_refresh_token, decode_access_token, decode_refresh_token,
+        create_access_token, create_reauthenticated_access_token, create_refresh_token,
+        create_service_token, decode_access_token, decode_refresh_token, decode_service_token,
     };
     use crate::core::error::ApiError;
 
@@ -198,12 +396,36 @@ mod tests {
         let email = "user@example.com";
 
         let token =
-            create_access_token(user_id, email, TEST_SECRET, 900).expect("access token created");
+            create_access_token(user_id, email, 2, TEST_SECRET, 900).expect("access token created");
         let claims = decode_access_token(&token, TEST_SECRET).expect("token should decode");
 
         assert_eq!(claims.sub, user_id.to_string());
         assert_eq!(claims.email, email);
         assert_eq!(claims.token_type, "access");
+        assert!(Uuid::parse_str(&claims.jti).is_ok());
+        assert_eq!(claims.token_version, 2);
+        assert_eq!(claims.auth_time, None);
+        assert!(claims.amr.is_empty());
+    }
+
+    #[test]
+    // Verifies re-authenticated access tokens record their issue time and methods.
+    fn reauthenticated_access_token_records_auth_time_and_methods() {
+        let user_id = Uuid::new_v4();
+
+        let token = create_reauthenticated_access_token(
+            user_id,
+            "user@example.com",
+            0,
+            &["pwd"],
+            TEST_SECRET,
+            900,
+        )
+        .expect("access token created");
+        let claims = decode_access_token(&token, TEST_SECRET).expect("token should decode");
+
+        assert_eq!(claims.auth_time, Some(claims.iat));
+        assert_eq!(claims.amr, vec!["pwd".to_string()]);
     }
 
     #[test]
@@ -237,11 +459,49 @@ mod tests {
     // Verifies the refresh decoder rejects access-token payloads.
     fn decode_refresh_token_rejects_access_token_type() {
         let user_id = Uuid::new_v4();
-        let access_token = create_access_token(user_id, "user@example.com", TEST_SECRET, 900)
+        let access_token = create_access_token(user_id, "user@example.com", 0, TEST_SECRET, 900)
             .expect("access token created");
 
         let result = decode_refresh_token(&access_token, TEST_SECRET);
 
         assert!(matches!(result, Err(ApiError::TokenInvalid)));
     }
+
+    #[test]
+    // Verifies service tokens round-trip and are rejected by the access and refresh decoders.
+    fn service_token_is_never_a_session() {
+        let service_account_id = Uuid::new_v4();
+        let scopes = vec!["profile:read".to_string(), "profile:write".to_string()];
+
+        let token =
+            create_service_token(service_account_id, "sa_client", &scopes, TEST_SECRET, 300)
+                .expect("service token created");
+        let claims = decode_service_token(&token, TEST_SECRET).expect("token should decode");
+
+        assert_eq!(claims.sub, service_account_id.to_string());
+        assert_eq!(claims.client_id, "sa_client");
+        assert_eq!(claims.scope, "profile:read profile:write");
+        assert_eq!(claims.token_type, "service");
+
+        assert!(matches!(
+            decode_access_token(&token, TEST_SECRET),
+            Err(ApiError::TokenInvalid)
+        ));
+        assert!(matches!(
+            decode_refresh_token(&token, TEST_SECRET),
+            Err(ApiError::TokenInvalid)
+        ));
+    }
+
+    #[test]
+    // Verifies the service decoder rejects access-token payloads.
+    fn decode_service_token_rejects_access_token_type() {
+        let access_token =
+            create_access_token(Uuid::new_v4(), "user@example.com", 0, TEST_SECRET, 900)
+                .expect("access token created");
+
+        let result = decode_service_token(&access_token, TEST_SECRET);
+
+        assert!(matches!(result, Err(ApiError::TokenInvalid)));
+    }
 }
@@ -1,16 +1,21 @@
 //! Request authentication extractor.
 //!
 //! This module provides [`AuthenticatedUser`], an `actix-web` request extractor
-//! that reads the `access_token` cookie, validates the JWT, and exposes the
-//! authenticated user's identity to handlers.
+//! that reads the access token from an `Authorization: Bearer` header (used by
+//! non-browser clients) or the `access_token` cookie (used by browsers),
+//! validates the JWT, rejects revoked or outdated tokens, and exposes the
+//! authenticated user's identity to handlers. [`RecentlyAuthenticated`]
+//! additionally requires a recent re-authentication for sensitive actions.
 
-use actix_web::{FromRequest, HttpRequest, dev::Payload};
-use futures::future::{Ready, err, ok};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::{FromRequest, HttpRequest, dev::Payload, web};
+use chrono::Utc;
+use futures::future::LocalBoxFuture;
 use uuid::Uuid;
 
-use crate::auth::jwt::decode_access_token;
+use crate::auth::jwt::{AccessTokenClaims, decode_access_token};
 use crate::core::app_state::AppState;
-use crate::core::error::ApiError;
+use crate::core::error::{ApiError, ApiResult};
 
 /// Authenticated user context extracted from a request.
 pub struct AuthenticatedUser {
@@ -22,43 +27,230 @@ pub struct AuthenticatedUser {
 
 impl FromRequest for AuthenticatedUser {
     type Error = ApiError;
-    type Future = Ready<Result<Self, Self::Error>>;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
 
-    /// Extracts and validates the authenticated user from request cookies.
+    /// Extracts and validates the authenticated user from the request.
+    ///
+    /// A bearer token in the `Authorization` header takes precedence over the
+    /// `access_token` cookie.
     ///
     /// # Errors
     ///
     /// Returns:
-    /// - [`ApiError::Unauthorized`] when no access token cookie is present
+    /// - [`ApiError::Unauthorized`] when no access token is present
+    /// - [`ApiError::Unauthorized`] when the token has been revoked or is outdated
     /// - [`ApiError::TokenInvalid`] when token claims are invalid
+    /// - [`ApiError::DatabaseError`] when the token version cannot be looked up
     /// - [`ApiError::InternalError`] when environment config is missing
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-        // Extract access token from cookie
-        let token = match req.cookie("access_token") {
+        let claims = request_access_token_claims(req);
+
+        Box::pin(async move {
+            let claims = claims.await?;
+            let user_id = Uuid::parse_str(&claims.sub).map_err(|_| ApiError::TokenInvalid)?;
+
+            Ok(AuthenticatedUser {
+                user_id,
+                email: claims.email,
+            })
+        })
+    }
+}
+
+/// How long a re-authentication unlocks sensitive actions, in seconds.
+pub const REAUTHENTICATION_WINDOW_SECONDS: u64 = 300;
+
+/// Authenticated user who re-authenticated within the last `MAX_AGE_SECONDS`.
+///
+/// Guards sensitive actions ("sudo mode"). The access token must come from
+/// `POST /auth/reauthenticate`, which records when the user last proved their
+/// identity.
+pub struct RecentlyAuthenticated<const MAX_AGE_SECONDS: u64> {
+    /// Unique identifier of the authenticated user.
+    pub user_id: Uuid,
+    /// Email address from the validated access token.
+    pub email: String,
+    /// When the user re-authenticated (Unix epoch seconds).
+    pub auth_time: usize,
+    /// Authentication methods used to re-authenticate, such as `pwd` or `sms`.
+    pub amr: Vec<String>,
+}
+
+impl<const MAX_AGE_SECONDS: u64> FromRequest for RecentlyAuthenticated<MAX_AGE_SECONDS> {
+    type Error = ApiError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    /// Extracts the authenticated user and checks how recently they re-authenticated.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`AuthenticatedUser`], and
+    /// [`ApiError::ReauthenticationRequired`] when the token carries no
+    /// `auth_time` or it is older than `MAX_AGE_SECONDS`.
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let claims = request_access_token_claims(req);
+
+        Box::pin(async move {
+            let claims = claims.await?;
+            let user_id = Uuid::parse_str(&claims.sub).map_err(|_| ApiError::TokenInvalid)?;
+
+            let auth_time = claims
+                .auth_time
+                .filter(|auth_time| is_recent(*auth_time, MAX_AGE_SECONDS))
+                .ok_or(ApiError::ReauthenticationRequired)?;
+
+            Ok(RecentlyAuthenticated {
+                user_id,
+                email: claims.email,
+                auth_time,
+                amr: claims.amr,
+            })
+        })
+    }
+}
+
+/// Reads the access token from a request and authenticates it.
+///
+/// A bearer token in the `Authorization` header takes precedence over the
+/// `access_token` cookie.
+fn request_access_token_claims(
+    req: &HttpRequest,
+) -> LocalBoxFuture<'static, ApiResult<AccessTokenClaims>> {
+    // Extract access token from the Authorization header, then the cookie
+    let token = match bearer_token(req) {
+        Some(token) => token,
+        None => match req.cookie("access_token") {
             Some(cookie) => cookie.value().to_string(),
-            None => return err(ApiError::Unauthorized),
-        };
-
-        // Get JWT secret from app data
-        let app_state = match req.app_data::<actix_web::web::Data<AppState>>() {
-            Some(app_state) => app_state,
-            None => {
-                return err(ApiError::InternalError(
+            None => return Box::pin(async { Err(ApiError::Unauthorized) }),
+        },
+    };
+
+    // Get JWT secret from app data
+    let app_state = match req.app_data::<web::Data<AppState>>() {
+        Some(app_state) => app_state.clone(),
+        None => {
+            return Box::pin(async {
+                Err(ApiError::InternalError(
                     "Application state not configured".to_string(),
-                ));
-            }
-        };
-
-        // Decode and validate token
-        match decode_access_token(&token, &app_state.env.jwt_secret) {
-            Ok(claims) => match Uuid::parse_str(&claims.sub) {
-                Ok(user_id) => ok(AuthenticatedUser {
-                    user_id,
-                    email: claims.email,
-                }),
-                Err(_) => err(ApiError::TokenInvalid),
-            },
-            Err(e) => err(e),
+                ))
+            });
         }
+    };
+
+    // Decode and validate token
+    Box::pin(async move { authenticate_access_token(&app_state, &token).await })
+}
+
+/// Returns whether an `auth_time` lies within the last `max_age_seconds`.
+///
+/// # Arguments
+///
+/// - `auth_time` - Re-authentication time (Unix epoch seconds)
+/// - `max_age_seconds` - Maximum accepted age in seconds
+fn is_recent(auth_time: usize, max_age_seconds: u64) -> bool {
+    let age = Utc::now().timestamp() - auth_time as i64;
+
+    age <= max_age_seconds as i64
+}
+
+/// Decodes an access token and checks that it is still valid for its user.
+///
+/// The token must not be on the revocation denylist, and its token version
+/// must match the user's current one.
+///
+/// # Arguments
+///
+/// - `state` - Application state with the JWT secret, denylist, and token versions
+/// - `token` - JWT access token string
+///
+/// # Errors
+///
+/// - [`ApiError::Unauthorized`] when the token has been revoked, its version is
+///   outdated, or its user no longer exists
+/// - [`ApiError::TokenInvalid`] or [`ApiError::TokenExpired`] when decoding fails
+/// - [`ApiError::DatabaseError`] when the token version cannot be looked up
+pub async fn authenticate_access_token(
+    state: &AppState,
+    token: &str,
+) -> ApiResult<AccessTokenClaims> {
+    let claims = decode_access_token(token, &state.env.jwt_secret)?;
+
+    if state.access_token_revocations.is_revoked(&claims) {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| ApiError::TokenInvalid)?;
+    let current_version = state.token_versions.current(&state.pool, user_id).await?;
+    if current_version != Some(claims.token_version) {
+        return Err(ApiError::Unauthorized);
+    }
+
+    Ok(claims)
+}
+
+/// Returns the token from an `Authorization: Bearer <token>` header, if present.
+///
+/// The scheme is matched case-insensitively. Other schemes are ignored so the
+/// cookie can still be used.
+///
+/// # Arguments
+///
+/// - `req` - Incoming HTTP request
+pub fn bearer_token(req: &HttpRequest) -> Option<String> {
+    let value = req.headers().get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    let token = token.trim();
+
+    if !scheme.eq_ignore_ascii_case("bearer") || token.is_empty() {
+        return None;
+    }
+
+    Some(token.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+    use chrono::Utc;
+
+    use super::{bearer_token, is_recent};
+
+    #[test]
+    // Verifies bearer tokens are read case-insensitively and other schemes are ignored.
+    fn bearer_token_reads_only_bearer_scheme() {
+        let req = TestRequest::default()
+            .insert_header(("Authorization", "Bearer abc.def.ghi"))
+            .to_http_request();
+        assert_eq!(bearer_token(&req).as_deref(), Some("abc.def.ghi"));
+
+        let req = TestRequest::default()
+            .insert_header(("Authorization", "bearer abc"))
+            .to_http_request();
+        assert_eq!(bearer_token(&req).as_deref(), Some("abc"));
+
+        let req = TestRequest::default()
+            .insert_header(("Authorization", "Basic dXNlcjpwYXNz"))
+            .to_http_request();
+        assert_eq!(bearer_token(&req), None);
+
+        let req = TestRequest::default()
+            .insert_header(("Authorization", "Bearer "))
+            .to_http_request();
+        assert_eq!(bearer_token(&req), None);
+
+        assert_eq!(
+            bearer_token(&TestRequest::default().to_http_request()),
+            None
+        );
+    }
+
+    #[test]
+    // Verifies re-authentication counts as recent only within the allowed age.
+    fn auth_time_is_recent_only_within_max_age() {
+        let now = Utc::now().timestamp() as usize;
+
+        assert!(is_recent(now, 300));
+        assert!(is_recent(now - 299, 300));
+        assert!(!is_recent(now - 301, 300));
     }
 }
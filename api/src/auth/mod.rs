@@ -2,14 +2,44 @@
 //!
 //! This module provides reusable utilities for:
 //!
+//! - [`api_keys`] - API key generation, parsing, and verification
+//! - [`breached_passwords`] - On-disk index of breached password hashes for offline screening
 //! - [`codes`] - Numeric authentication code generation and verification helpers
 //! - [`cookies`] - Secure auth cookie construction and clearing
+//! - [`csrf`] - Double-submit CSRF token middleware for cookie-authenticated requests
+//! - [`device_codes`] - Device and user code helpers for the device authorization grant
+//! - [`email_domains`] - Email domain allowlists, denylists and disposable-domain blocking
 //! - [`jwt`] - JWT claim types and token encode/decode helpers
 //! - [`middleware`] - Request extractor for authenticated users
 //! - [`password`] - Password hashing and verification
+//! - [`password_policy`] - Configurable password rules and zxcvbn-style strength scoring
+//! - [`principal`] - Request extractors for API keys, service accounts, and for sessions or API keys with scopes
+//! - [`request_rates`] - Sliding-window request counters for adaptive abuse protection
+//! - [`revocation`] - In-memory access token denylist kept in sync across instances
+//! - [`scopes`] - Permission scopes granted to API keys and service accounts
+//! - [`secrets`] - Random secret generation and SHA-256 storage hashes for API keys and service accounts
+//! - [`service_accounts`] - Service account client credential generation and verification
+//! - [`trusted_proxies`] - Client address resolution that only trusts forwarded headers from configured proxies
+//! - [`token_versions`] - Short-lived cache of users' token versions for bulk invalidation
+//! - [`user_import`] - Bulk import of users with password hashes from another system
 
+pub mod api_keys;
+pub mod breached_passwords;
 pub mod codes;
 pub mod cookies;
+pub mod csrf;
+pub mod device_codes;
+pub mod email_domains;
 pub mod jwt;
 pub mod middleware;
 pub mod password;
+pub mod password_policy;
+pub mod principal;
+pub mod request_rates;
+pub mod revocation;
+pub mod scopes;
+pub mod secrets;
+pub mod service_accounts;
+pub mod token_versions;
+pub mod trusted_proxies;
+pub mod user_import;
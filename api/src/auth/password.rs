@@ -1,16 +1,195 @@
 //! Password hashing and verification helpers.
 //!
 //! This module wraps Argon2 password hashing and verification with API-level
-//! error mapping so authentication handlers can fail consistently.
+//! error mapping so authentication handlers can fail consistently. Hashing
+//! cost comes from [`PasswordHashParams`]; verification reads the parameters
+//! embedded in each stored hash, so hashes made with older settings keep
+//! working and can be upgraded with [`needs_rehash`].
+//!
+//! When [`PasswordPeppers`] are configured, the password is first keyed with
+//! HMAC-SHA256 under a secret kept outside the database, so a leaked
+//! database alone is not enough to crack hashes. The pepper version is stored
+//! in the Argon2 `keyid` parameter, letting old versions keep verifying while
+//! log-in re-peppers hashes with the current one.
+//!
+//! Users imported from other systems may carry bcrypt (`$2a$`, `$2b$`, `$2x$`,
+//! `$2y$`), PBKDF2-SHA256 (`$pbkdf2-sha256$`) or scrypt (`$scrypt$`) hashes.
+//! These verify like Argon2 hashes and always need a rehash.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{LazyLock, Mutex};
 
 use argon2::{
-    Argon2,
+    Algorithm, Argon2, KeyId, Params, ParamsBuilder, Version,
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
 };
+use hmac::{Hmac, Mac};
+use pbkdf2::Pbkdf2;
+use scrypt::Scrypt;
+use sha2::Sha256;
 
+use crate::core::env::Env;
 use crate::core::error::ApiError;
 
-/// Hashes a plain-text password using Argon2 and a random salt.
+/// Versioned server-side secrets mixed into passwords before hashing.
+///
+/// Parsed from `PASSWORD_PEPPERS` as comma-separated `version:secret` pairs,
+/// for example `1:old-secret,2:new-secret`. New hashes use the highest
+/// version; the others only verify existing hashes until they are re-peppered.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct PasswordPeppers {
+    /// Secret for each pepper version.
+    secrets: BTreeMap<u32, Vec<u8>>,
+}
+
+impl fmt::Debug for PasswordPeppers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PasswordPeppers")
+            .field("versions", &self.secrets.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl FromStr for PasswordPeppers {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut secrets = BTreeMap::new();
+        for entry in value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (version, secret) = entry
+                .split_once(':')
+                .ok_or_else(|| "Password peppers must be version:secret pairs".to_string())?;
+            let version = version
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid password pepper version: {}", version))?;
+            if secret.is_empty() {
+                return Err(format!("Password pepper {} has an empty secret", version));
+            }
+            if secrets
+                .insert(version, secret.as_bytes().to_vec())
+                .is_some()
+            {
+                return Err(format!("Password pepper {} is defined twice", version));
+            }
+        }
+
+        Ok(Self { secrets })
+    }
+}
+
+impl PasswordPeppers {
+    /// Returns the version new hashes are peppered with, or `None` when no
+    /// pepper is configured.
+    pub fn current_version(&self) -> Option<u32> {
+        self.secrets.keys().next_back().copied()
+    }
+
+    /// Keys a password with the given pepper version.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::InternalError`] if the version is not configured.
+    fn apply(&self, version: u32, password: &str) -> Result<Vec<u8>, ApiError> {
+        let secret = self.secrets.get(&version).ok_or_else(|| {
+            ApiError::InternalError(format!("Password pepper {} is not configured", version))
+        })?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+            .map_err(|_| ApiError::InternalError("Invalid password pepper".to_string()))?;
+        mac.update(password.as_bytes());
+
+        Ok(mac.finalize().into_bytes().to_vec())
+    }
+}
+
+/// Argon2id cost parameters and pepper used for new password hashes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PasswordHashParams {
+    /// Memory cost in KiB.
+    pub memory_kib: u32,
+    /// Number of passes over memory.
+    pub iterations: u32,
+    /// Degree of parallelism (lanes).
+    pub parallelism: u32,
+    /// Pepper secrets; empty when passwords are not peppered.
+    pub peppers: PasswordPeppers,
+}
+
+impl Default for PasswordHashParams {
+    /// Returns the `argon2` crate defaults (19 MiB, 2 passes, 1 lane) without a pepper.
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+            peppers: PasswordPeppers::default(),
+        }
+    }
+}
+
+impl PasswordHashParams {
+    /// Builds the parameters from `ARGON2_*` and `PASSWORD_PEPPERS` environment settings.
+    pub fn from_env(env: &Env) -> Self {
+        Self {
+            memory_kib: env.argon2_memory_kib,
+            iterations: env.argon2_iterations,
+            parallelism: env.argon2_parallelism,
+            peppers: env.password_peppers.clone(),
+        }
+    }
+
+    /// Builds an Argon2id hasher with these parameters, recording the current
+    /// pepper version as the key ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::InternalError`] if the parameters are out of range.
+    fn hasher(&self) -> Result<Argon2<'static>, ApiError> {
+        let invalid_params = || ApiError::InternalError("Invalid Argon2 parameters".to_string());
+
+        let mut builder = ParamsBuilder::new();
+        builder
+            .m_cost(self.memory_kib)
+            .t_cost(self.iterations)
+            .p_cost(self.parallelism);
+        if let Some(version) = self.peppers.current_version() {
+            builder
+                .keyid(KeyId::new(version.to_string().as_bytes()).map_err(|_| invalid_params())?);
+        }
+        let params = builder.build().map_err(|_| invalid_params())?;
+
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
+
+/// Returns the pepper version recorded in an Argon2 hash's key ID, or `None`
+/// for unpeppered hashes.
+///
+/// # Errors
+///
+/// Returns [`ApiError::InternalError`] if the key ID is not a version number.
+fn pepper_version(params: &Params) -> Result<Option<u32>, ApiError> {
+    if params.keyid().is_empty() {
+        return Ok(None);
+    }
+
+    std::str::from_utf8(params.keyid())
+        .ok()
+        .and_then(|keyid| keyid.parse::<u32>().ok())
+        .map(Some)
+        .ok_or_else(|| ApiError::InternalError("Invalid password pepper version".to_string()))
+}
+
+/// Hashes a plain-text password using default Argon2 parameters and a random salt.
+///
+/// Handlers hash with the configured parameters and pepper through
+/// [`hash_password_with_params`] instead.
 ///
 /// # Arguments
 ///
@@ -20,39 +199,240 @@ use crate::core::error::ApiError;
 ///
 /// Returns [`ApiError::InternalError`] if hashing fails.
 pub fn hash_password(password: &str) -> Result<String, ApiError> {
+    hash_password_with_params(password, &PasswordHashParams::default())
+}
+
+/// Hashes a plain-text password using the given Argon2 parameters, the
+/// current pepper if any, and a random salt.
+///
+/// # Arguments
+///
+/// - `password` - Plain-text password provided by a user
+/// - `params` - Argon2 cost parameters and pepper
+///
+/// # Errors
+///
+/// Returns [`ApiError::InternalError`] if the parameters are invalid or
+/// hashing fails.
+pub fn hash_password_with_params(
+    password: &str,
+    params: &PasswordHashParams,
+) -> Result<String, ApiError> {
     let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
+    let argon2 = params.hasher()?;
+    let input = match params.peppers.current_version() {
+        Some(version) => params.peppers.apply(version, password)?,
+        None => password.as_bytes().to_vec(),
+    };
 
     let password_hash = argon2
-        .hash_password(password.as_bytes(), &salt)
+        .hash_password(&input, &salt)
         .map_err(|_| ApiError::InternalError("Failed to hash password".to_string()))?
         .to_string();
 
     Ok(password_hash)
 }
 
-/// Verifies a plain-text password against a stored Argon2 hash.
+/// Password hashing schemes a stored hash can use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HashScheme {
+    /// Argon2 (`$argon2id$`, `$argon2i$`, `$argon2d$`).
+    Argon2,
+    /// Modular-crypt bcrypt (`$2a$`, `$2b$`, `$2x$`, `$2y$`).
+    Bcrypt,
+    /// PHC-format PBKDF2-SHA256 (`$pbkdf2-sha256$`).
+    Pbkdf2Sha256,
+    /// PHC-format scrypt (`$scrypt$`).
+    Scrypt,
+}
+
+/// Identifies the scheme of a stored hash, or `None` if it is not a
+/// well-formed hash of a supported scheme.
+fn hash_scheme(password_hash: &str) -> Option<HashScheme> {
+    if ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .any(|prefix| password_hash.starts_with(prefix))
+    {
+        return password_hash
+            .parse::<bcrypt::HashParts>()
+            .ok()
+            .map(|_| HashScheme::Bcrypt);
+    }
+
+    let parsed_hash = PasswordHash::new(password_hash).ok()?;
+    match parsed_hash.algorithm.as_str() {
+        "argon2id" | "argon2i" | "argon2d" => Some(HashScheme::Argon2),
+        "pbkdf2-sha256" => Some(HashScheme::Pbkdf2Sha256),
+        "scrypt" => Some(HashScheme::Scrypt),
+        _ => None,
+    }
+}
+
+/// Returns whether [`verify_password`] can check passwords against a stored hash.
+///
+/// # Arguments
+///
+/// - `password_hash` - Stored or imported password hash
+pub fn is_supported_hash(password_hash: &str) -> bool {
+    hash_scheme(password_hash).is_some()
+}
+
+/// Returns whether a stored hash was made with anything other than Argon2id
+/// at the given parameters and current pepper version.
+///
+/// Legacy bcrypt, PBKDF2 and scrypt hashes always need a rehash.
+/// Unparseable hashes are reported as not needing one, since they can never
+/// be verified in the first place.
+///
+/// # Arguments
+///
+/// - `password_hash` - Stored password hash
+/// - `params` - Currently configured Argon2 cost parameters and pepper
+pub fn needs_rehash(password_hash: &str, params: &PasswordHashParams) -> bool {
+    match hash_scheme(password_hash) {
+        Some(HashScheme::Argon2) => {}
+        Some(_) => return true,
+        None => return false,
+    }
+
+    let Ok(parsed_hash) = PasswordHash::new(password_hash) else {
+        return false;
+    };
+    if parsed_hash.algorithm != Algorithm::Argon2id.ident()
+        || parsed_hash.version != Some(Version::V0x13.into())
+    {
+        return true;
+    }
+
+    match Params::try_from(&parsed_hash) {
+        Ok(current) => {
+            current.m_cost() != params.memory_kib
+                || current.t_cost() != params.iterations
+                || current.p_cost() != params.parallelism
+                || pepper_version(&current).ok() != Some(params.peppers.current_version())
+        }
+        Err(_) => true,
+    }
+}
+
+/// Verifies a plain-text password against a stored unpeppered Argon2 or legacy hash.
+///
+/// Handlers verify with the configured pepper through
+/// [`verify_password_with_params`] instead.
 ///
 /// # Arguments
 ///
 /// - `password` - Plain-text password provided by a user
-/// - `password_hash` - Stored Argon2 password hash
+/// - `password_hash` - Stored password hash
 ///
 /// # Errors
 ///
-/// Returns [`ApiError::InternalError`] if the stored hash format is invalid.
+/// Returns [`ApiError::InternalError`] if the stored hash format is invalid,
+/// uses an unsupported scheme, or is peppered.
 pub fn verify_password(password: &str, password_hash: &str) -> Result<bool, ApiError> {
-    let parsed_hash = PasswordHash::new(password_hash)
-        .map_err(|_| ApiError::InternalError("Invalid password hash format".to_string()))?;
+    verify_password_with_params(password, password_hash, &PasswordHashParams::default())
+}
+
+/// Verifies a plain-text password against a stored Argon2 or legacy hash,
+/// applying the pepper version recorded in the hash.
+///
+/// # Arguments
+///
+/// - `password` - Plain-text password provided by a user
+/// - `password_hash` - Stored password hash
+/// - `params` - Configured parameters holding the pepper secrets
+///
+/// # Errors
+///
+/// Returns [`ApiError::InternalError`] if the stored hash format is invalid,
+/// uses an unsupported scheme, or was peppered with a version that is no
+/// longer configured.
+pub fn verify_password_with_params(
+    password: &str,
+    password_hash: &str,
+    params: &PasswordHashParams,
+) -> Result<bool, ApiError> {
+    let invalid_format = || ApiError::InternalError("Invalid password hash format".to_string());
+    let scheme = hash_scheme(password_hash).ok_or_else(invalid_format)?;
+
+    if scheme == HashScheme::Bcrypt {
+        return bcrypt::verify(password, password_hash).map_err(|_| invalid_format());
+    }
+
+    let parsed_hash = PasswordHash::new(password_hash).map_err(|_| invalid_format())?;
+    let argon2 = Argon2::default();
+    let (verifier, input): (&dyn PasswordVerifier, Vec<u8>) = match scheme {
+        HashScheme::Argon2 => {
+            let hash_params = Params::try_from(&parsed_hash).map_err(|_| invalid_format())?;
+            let input = match pepper_version(&hash_params)? {
+                Some(version) => params.peppers.apply(version, password)?,
+                None => password.as_bytes().to_vec(),
+            };
+            (&argon2, input)
+        }
+        HashScheme::Pbkdf2Sha256 => (&Pbkdf2, password.as_bytes().to_vec()),
+        HashScheme::Scrypt => (&Scrypt, password.as_bytes().to_vec()),
+        HashScheme::Bcrypt => unreachable!("bcrypt hashes are verified above"),
+    };
 
-    Ok(Argon2::default()
-        .verify_password(password.as_bytes(), &parsed_hash)
-        .is_ok())
+    Ok(verifier.verify_password(&input, &parsed_hash).is_ok())
+}
+
+/// Placeholder password behind the hashes used by [`verify_dummy_password`].
+const DUMMY_PASSWORD: &str = "dummy-password-for-unknown-accounts";
+
+/// Dummy hashes made so far, one per set of hashing parameters.
+static DUMMY_HASHES: LazyLock<Mutex<HashMap<PasswordHashParams, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Runs a full password verification against a throwaway hash made with the
+/// configured parameters.
+///
+/// Log-in calls this when no account matches the submitted email, so the
+/// response takes as long as a wrong password for a real account and its
+/// timing does not reveal which emails are registered. The hash is made once
+/// per parameter set and reused.
+///
+/// # Arguments
+///
+/// - `password` - Plain-text password provided by a user
+/// - `params` - Configured parameters new hashes are made with
+///
+/// # Errors
+///
+/// Returns [`ApiError::InternalError`] if the dummy hash cannot be made.
+pub fn verify_dummy_password(password: &str, params: &PasswordHashParams) -> Result<(), ApiError> {
+    let cached = DUMMY_HASHES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(params)
+        .cloned();
+    let dummy_hash = match cached {
+        Some(hash) => hash,
+        None => {
+            let hash = hash_password_with_params(DUMMY_PASSWORD, params)?;
+            DUMMY_HASHES
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .insert(params.clone(), hash.clone());
+            hash
+        }
+    };
+
+    verify_password_with_params(password, &dummy_hash, params).map(|_| ())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{hash_password, verify_password};
+    use argon2::password_hash::{PasswordHasher, SaltString, rand_core::OsRng};
+    use pbkdf2::Pbkdf2;
+    use scrypt::Scrypt;
+
+    use super::{
+        PasswordHashParams, PasswordPeppers, hash_password, hash_password_with_params,
+        is_supported_hash, needs_rehash, verify_dummy_password, verify_password,
+        verify_password_with_params,
+    };
     use crate::core::error::ApiError;
 
     #[test]
@@ -83,4 +463,162 @@ mod tests {
 
         assert!(matches!(result, Err(ApiError::InternalError(_))));
     }
+
+    #[test]
+    // Verifies hashes embed the configured parameters and still verify.
+    fn hash_password_with_params_uses_given_parameters() {
+        let params = PasswordHashParams {
+            memory_kib: 8192,
+            iterations: 3,
+            parallelism: 2,
+            ..PasswordHashParams::default()
+        };
+        let hash = hash_password_with_params("my-very-strong-password", &params)
+            .expect("password should hash");
+
+        assert!(hash.starts_with("$argon2id$v=19$m=8192,t=3,p=2$"));
+        assert!(
+            verify_password("my-very-strong-password", &hash).expect("verification should run")
+        );
+    }
+
+    #[test]
+    // Verifies only hashes with outdated parameters are flagged for rehashing.
+    fn needs_rehash_detects_outdated_parameters() {
+        let current = PasswordHashParams::default();
+        let stronger = PasswordHashParams {
+            memory_kib: current.memory_kib * 2,
+            ..current.clone()
+        };
+        let hash = hash_password("correct-password").expect("password should hash");
+
+        assert!(!needs_rehash(&hash, &current));
+        assert!(needs_rehash(&hash, &stronger));
+        assert!(needs_rehash(
+            "$argon2i$v=19$m=19456,t=2,p=1$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG",
+            &current
+        ));
+        assert!(!needs_rehash("not-a-valid-hash", &current));
+    }
+
+    #[test]
+    // Verifies imported bcrypt, PBKDF2-SHA256 and scrypt hashes verify and are flagged for rehashing.
+    fn verifies_legacy_hashes() {
+        let password = "my-very-strong-password";
+        let salt = SaltString::generate(&mut OsRng);
+        let legacy_hashes = [
+            bcrypt::hash(password, 4).expect("bcrypt should hash"),
+            Pbkdf2
+                .hash_password_customized(
+                    password.as_bytes(),
+                    Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+                    None,
+                    pbkdf2::Params {
+                        rounds: 1_000,
+                        output_length: 32,
+                    },
+                    &salt,
+                )
+                .expect("PBKDF2 should hash")
+                .to_string(),
+            Scrypt
+                .hash_password_customized(
+                    password.as_bytes(),
+                    None,
+                    None,
+                    scrypt::Params::new(4, 8, 1, 32).expect("scrypt params should be valid"),
+                    &salt,
+                )
+                .expect("scrypt should hash")
+                .to_string(),
+        ];
+
+        for hash in &legacy_hashes {
+            assert!(is_supported_hash(hash), "{hash}");
+            assert!(verify_password(password, hash).expect("verification should run"));
+            assert!(!verify_password("wrong-password", hash).expect("verification should run"));
+            assert!(needs_rehash(hash, &PasswordHashParams::default()));
+        }
+    }
+
+    #[test]
+    // Verifies hashes of unsupported schemes are rejected as invalid.
+    fn rejects_unsupported_hash_schemes() {
+        let md5_crypt = "$1$saltsalt$qjXMvbEw8oaL.CzflDugX/";
+        let pbkdf2_sha1 = "$pbkdf2$i=1000,l=20$c29tZXNhbHQ$Ks8m8Cp8WrKjKwG3j5g7uSeQYsI";
+
+        for hash in [md5_crypt, pbkdf2_sha1] {
+            assert!(!is_supported_hash(hash));
+            assert!(matches!(
+                verify_password("password", hash),
+                Err(ApiError::InternalError(_))
+            ));
+        }
+    }
+
+    #[test]
+    // Verifies pepper lists parse into versions and malformed lists are rejected.
+    fn parses_password_peppers() {
+        let peppers: PasswordPeppers = "2:new-secret, 1:old:secret".parse().expect("should parse");
+        assert_eq!(peppers.current_version(), Some(2));
+        assert_eq!(
+            "".parse::<PasswordPeppers>()
+                .map(|peppers| peppers.current_version()),
+            Ok(None)
+        );
+
+        for invalid in ["secret", "one:secret", "1:", "1:a,1:b"] {
+            assert!(invalid.parse::<PasswordPeppers>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    // Verifies peppered hashes record their version, need the pepper to verify, and are re-peppered after rotation.
+    fn peppered_hashes_rotate_versions() {
+        let password = "my-very-strong-password";
+        let v1 = PasswordHashParams {
+            peppers: "1:old-secret".parse().expect("should parse"),
+            ..PasswordHashParams::default()
+        };
+        let v2 = PasswordHashParams {
+            peppers: "1:old-secret,2:new-secret".parse().expect("should parse"),
+            ..PasswordHashParams::default()
+        };
+
+        let hash = hash_password_with_params(password, &v1).expect("password should hash");
+        assert!(hash.contains(",keyid=MQ$"));
+        assert!(verify_password_with_params(password, &hash, &v1).expect("should verify"));
+        assert!(!verify_password_with_params("wrong-password", &hash, &v1).expect("should run"));
+        assert!(matches!(
+            verify_password(password, &hash),
+            Err(ApiError::InternalError(_))
+        ));
+        assert!(!needs_rehash(&hash, &v1));
+
+        assert!(verify_password_with_params(password, &hash, &v2).expect("should verify"));
+        assert!(needs_rehash(&hash, &v2));
+
+        let rehashed = hash_password_with_params(password, &v2).expect("password should hash");
+        assert!(rehashed.contains(",keyid=Mg$"));
+        assert!(verify_password_with_params(password, &rehashed, &v2).expect("should verify"));
+        assert!(!needs_rehash(&rehashed, &v2));
+
+        let unpeppered = hash_password(password).expect("password should hash");
+        assert!(needs_rehash(&unpeppered, &v2));
+        assert!(needs_rehash(&rehashed, &PasswordHashParams::default()));
+    }
+
+    #[test]
+    // Verifies dummy verification succeeds with default and peppered parameters.
+    fn verifies_dummy_passwords() {
+        let peppered = PasswordHashParams {
+            peppers: "1:secret".parse().expect("should parse"),
+            ..PasswordHashParams::default()
+        };
+
+        for params in [PasswordHashParams::default(), peppered] {
+            verify_dummy_password("any-password", &params).expect("dummy verify should run");
+            verify_dummy_password("another-password", &params).expect("cached hash should run");
+        }
+    }
 }
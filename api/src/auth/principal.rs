@@ -0,0 +1,292 @@
+//! Request extractors for API keys, service accounts, and any authenticated user.
+//!
+//! - [`ApiKeyAuth`] - Accepts only API keys and exposes the key's scopes
+//! - [`Principal`] - Accepts either a session access token or an API key, so a
+//!   route can serve browsers, bearer clients, and scripts alike
+//! - [`ServiceAccountAuth`] - Accepts only service tokens issued by the
+//!   `client_credentials` grant
+//!
+//! API keys are read from the `X-API-Key` header or from
+//! `Authorization: Bearer ak_...`. Service tokens are never accepted by
+//! [`Principal`] or [`AuthenticatedUser`], because they do not represent a user.
+
+use actix_web::{FromRequest, HttpRequest, dev::Payload, web};
+use chrono::Utc;
+use futures::future::LocalBoxFuture;
+use uuid::Uuid;
+
+use crate::auth::api_keys::{API_KEY_MARKER, parse_api_key};
+use crate::auth::jwt::decode_service_token;
+use crate::auth::middleware::{AuthenticatedUser, bearer_token};
+use crate::auth::scopes::Scope;
+use crate::auth::secrets::verify_secret;
+use crate::core::app_state::AppState;
+use crate::core::error::{ApiError, ApiResult};
+use crate::repository::api_keys::ApiKeyRepo;
+use crate::repository::service_accounts::ServiceAccountRepo;
+
+/// Header carrying an API key as an alternative to `Authorization`.
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// Request authenticated with an API key.
+#[derive(Debug, Clone)]
+pub struct ApiKeyAuth {
+    /// Identifier of the API key used.
+    pub api_key_id: Uuid,
+    /// User the key acts on behalf of.
+    pub user_id: Uuid,
+    /// Scopes granted to the key.
+    pub scopes: Vec<Scope>,
+}
+
+impl FromRequest for ApiKeyAuth {
+    type Error = ApiError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    /// Extracts and verifies the API key presented with the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns:
+    /// - [`ApiError::Unauthorized`] when no API key is present
+    /// - [`ApiError::TokenInvalid`] when the key is malformed, unknown, or revoked
+    /// - [`ApiError::TokenExpired`] when the key has expired
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let key = presented_api_key(req);
+        let state = req.app_data::<web::Data<AppState>>().cloned();
+
+        Box::pin(async move {
+            let key = key.ok_or(ApiError::Unauthorized)?;
+            let state = state.ok_or_else(|| {
+                ApiError::InternalError("Application state not configured".to_string())
+            })?;
+
+            authenticate_api_key(&state, &key).await
+        })
+    }
+}
+
+/// How a [`Principal`] authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrincipalKind {
+    /// Session access token from a cookie or bearer header.
+    Session,
+    /// API key.
+    ApiKey {
+        /// Identifier of the API key used.
+        api_key_id: Uuid,
+    },
+}
+
+/// Any authenticated caller together with the scopes it was granted.
+///
+/// Sessions are granted every scope; API keys are limited to the scopes chosen
+/// when the key was created.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    /// User the request acts on behalf of.
+    pub user_id: Uuid,
+    /// How the request authenticated.
+    pub kind: PrincipalKind,
+    /// Scopes granted to the request.
+    pub scopes: Vec<Scope>,
+}
+
+impl Principal {
+    /// Returns `true` when the principal was granted `scope`.
+    ///
+    /// # Arguments
+    ///
+    /// - `scope` - Scope to check
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// Fails unless the principal was granted `scope`.
+    ///
+    /// # Arguments
+    ///
+    /// - `scope` - Scope required by the route
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::InsufficientScope`] when the scope was not granted.
+    pub fn require_scope(&self, scope: Scope) -> ApiResult<()> {
+        if self.has_scope(scope) {
+            return Ok(());
+        }
+
+        Err(ApiError::InsufficientScope(scope.to_string()))
+    }
+}
+
+impl From<AuthenticatedUser> for Principal {
+    fn from(user: AuthenticatedUser) -> Self {
+        Principal {
+            user_id: user.user_id,
+            kind: PrincipalKind::Session,
+            scopes: Scope::ALL.to_vec(),
+        }
+    }
+}
+
+impl From<ApiKeyAuth> for Principal {
+    fn from(api_key: ApiKeyAuth) -> Self {
+        Principal {
+            user_id: api_key.user_id,
+            kind: PrincipalKind::ApiKey {
+                api_key_id: api_key.api_key_id,
+            },
+            scopes: api_key.scopes,
+        }
+    }
+}
+
+impl FromRequest for Principal {
+    type Error = ApiError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    /// Authenticates with an API key when one is presented, otherwise with a session.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`ApiKeyAuth`] or [`AuthenticatedUser`],
+    /// depending on the credential presented.
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        if presented_api_key(req).is_some() {
+            let api_key = ApiKeyAuth::from_request(req, payload);
+            return Box::pin(async move { api_key.await.map(Principal::from) });
+        }
+
+        let session = AuthenticatedUser::from_request(req, payload);
+        Box::pin(async move { session.await.map(Principal::from) })
+    }
+}
+
+/// Request authenticated with a service token.
+#[derive(Debug, Clone)]
+pub struct ServiceAccountAuth {
+    /// Identifier of the service account.
+    pub service_account_id: Uuid,
+    /// OAuth2 client ID of the service account.
+    pub client_id: String,
+    /// Scopes granted to the token.
+    pub scopes: Vec<Scope>,
+}
+
+impl ServiceAccountAuth {
+    /// Fails unless the token was granted `scope`.
+    ///
+    /// # Arguments
+    ///
+    /// - `scope` - Scope required by the route
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::InsufficientScope`] when the scope was not granted.
+    pub fn require_scope(&self, scope: Scope) -> ApiResult<()> {
+        if self.scopes.contains(&scope) {
+            return Ok(());
+        }
+
+        Err(ApiError::InsufficientScope(scope.to_string()))
+    }
+}
+
+impl FromRequest for ServiceAccountAuth {
+    type Error = ApiError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    /// Extracts and verifies the service token from the `Authorization` header.
+    ///
+    /// # Errors
+    ///
+    /// Returns:
+    /// - [`ApiError::Unauthorized`] when no bearer token is present
+    /// - [`ApiError::TokenInvalid`] when the token is not a valid service token
+    ///   or the service account was revoked
+    /// - [`ApiError::TokenExpired`] when the token has expired
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let token = bearer_token(req);
+        let state = req.app_data::<web::Data<AppState>>().cloned();
+
+        Box::pin(async move {
+            let token = token.ok_or(ApiError::Unauthorized)?;
+            let state = state.ok_or_else(|| {
+                ApiError::InternalError("Application state not configured".to_string())
+            })?;
+
+            let claims = decode_service_token(&token, &state.env.jwt_secret)?;
+            let service_account_id =
+                Uuid::parse_str(&claims.sub).map_err(|_| ApiError::TokenInvalid)?;
+
+            if !ServiceAccountRepo::is_service_account_active(&state.pool, service_account_id)
+                .await?
+            {
+                return Err(ApiError::TokenInvalid);
+            }
+
+            let scopes: Vec<String> = claims.scope.split_whitespace().map(String::from).collect();
+
+            Ok(ServiceAccountAuth {
+                service_account_id,
+                client_id: claims.client_id,
+                scopes: Scope::parse_all(&scopes),
+            })
+        })
+    }
+}
+
+/// Returns the API key presented in `X-API-Key` or as a bearer token, if any.
+///
+/// Bearer tokens that do not start with the API key marker are left for
+/// session authentication.
+///
+/// # Arguments
+///
+/// - `req` - Incoming HTTP request
+fn presented_api_key(req: &HttpRequest) -> Option<String> {
+    if let Some(value) = req.headers().get(API_KEY_HEADER) {
+        return value.to_str().ok().map(|key| key.trim().to_string());
+    }
+
+    bearer_token(req).filter(|token| token.starts_with(API_KEY_MARKER))
+}
+
+/// Verifies an API key and records its use.
+///
+/// # Arguments
+///
+/// - `state` - Application state with the database pool
+/// - `key` - Full API key presented by the client
+///
+/// # Errors
+///
+/// - `TokenInvalid` - If the key is malformed, unknown, revoked, or its secret doesn't match
+/// - `TokenExpired` - If the key has expired
+async fn authenticate_api_key(state: &AppState, key: &str) -> ApiResult<ApiKeyAuth> {
+    let (prefix, secret) = parse_api_key(key).ok_or(ApiError::TokenInvalid)?;
+
+    let api_key = ApiKeyRepo::find_active_api_key_by_prefix(&state.pool, prefix)
+        .await?
+        .ok_or(ApiError::TokenInvalid)?;
+
+    if !verify_secret(secret, &api_key.secret_hash) {
+        return Err(ApiError::TokenInvalid);
+    }
+
+    if api_key
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(ApiError::TokenExpired);
+    }
+
+    ApiKeyRepo::touch_api_key(&state.pool, api_key.id).await?;
+
+    Ok(ApiKeyAuth {
+        api_key_id: api_key.id,
+        user_id: api_key.user_id,
+        scopes: Scope::parse_all(&api_key.scopes),
+    })
+}
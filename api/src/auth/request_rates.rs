@@ -0,0 +1,126 @@
+//! In-memory request counters for adaptive abuse protection.
+//!
+//! [`RequestRateTracker`] counts recent requests per key (such as a client
+//! address or a target email) over a sliding window. Handlers compare the
+//! count with a threshold to decide when to ask for
+//! [human verification](crate::services::human_verification) instead of
+//! rejecting clients outright. Counts are kept per instance, so behind a load
+//! balancer each instance applies the threshold separately.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of tracked keys at which idle keys are first pruned.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Process-wide sliding-window request counts by key.
+///
+/// Each key keeps at most `limit` timestamps, so memory per key is bounded by
+/// the threshold callers compare against rather than by the request rate.
+/// Idle keys are pruned once the number of keys doubles since the last
+/// prune, which keeps the cost of pruning constant per recorded request.
+#[derive(Debug)]
+pub struct RequestRateTracker {
+    window: Duration,
+    requests: Mutex<TrackedRequests>,
+}
+
+/// Request times by key and the pruning schedule, guarded together.
+#[derive(Debug)]
+struct TrackedRequests {
+    times_by_key: HashMap<String, VecDeque<Instant>>,
+    /// Key count at which idle keys are next pruned.
+    prune_at: usize,
+}
+
+impl RequestRateTracker {
+    /// Creates an empty tracker.
+    ///
+    /// # Arguments
+    ///
+    /// - `window` - How far back requests are counted
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            requests: Mutex::new(TrackedRequests {
+                times_by_key: HashMap::new(),
+                prune_at: PRUNE_THRESHOLD,
+            }),
+        }
+    }
+
+    /// Records a request and returns how many requests the key made within
+    /// the window, including this one, capped at `limit`.
+    ///
+    /// # Arguments
+    ///
+    /// - `key` - What the request is counted against, such as `"sign-up:ip:203.0.113.7"`
+    /// - `limit` - Largest count callers need to tell apart, such as a threshold plus one
+    pub fn record(&self, key: &str, limit: usize) -> usize {
+        let now = Instant::now();
+        let window = self.window;
+        let mut requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+
+        if requests.times_by_key.len() >= requests.prune_at {
+            requests.times_by_key.retain(|_, times| {
+                times
+                    .back()
+                    .is_some_and(|last| now.duration_since(*last) < window)
+            });
+            requests.prune_at = (requests.times_by_key.len() * 2).max(PRUNE_THRESHOLD);
+        }
+
+        let times = requests.times_by_key.entry(key.to_string()).or_default();
+        while times
+            .front()
+            .is_some_and(|first| now.duration_since(*first) >= window)
+        {
+            times.pop_front();
+        }
+        times.push_back(now);
+        while times.len() > limit.max(1) {
+            times.pop_front();
+        }
+
+        times.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use super::RequestRateTracker;
+
+    #[test]
+    // Verifies requests are counted per key and drop out once the window passes.
+    fn counts_requests_per_key_within_window() {
+        let tracker = RequestRateTracker::new(Duration::from_millis(50));
+
+        assert_eq!(tracker.record("a", 10), 1);
+        assert_eq!(tracker.record("a", 10), 2);
+        assert_eq!(tracker.record("b", 10), 1);
+
+        sleep(Duration::from_millis(60));
+        assert_eq!(tracker.record("a", 10), 1);
+    }
+
+    #[test]
+    // Verifies counts, and the times kept per key, stop growing at the limit.
+    fn caps_counts_at_limit() {
+        let tracker = RequestRateTracker::new(Duration::from_secs(60));
+
+        for _ in 0..100 {
+            tracker.record("a", 3);
+        }
+
+        assert_eq!(tracker.record("a", 3), 3);
+        let requests = tracker
+            .requests
+            .lock()
+            .expect("lock should not be poisoned");
+        assert_eq!(requests.times_by_key["a"].len(), 3);
+    }
+}
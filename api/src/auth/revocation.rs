@@ -0,0 +1,370 @@
+//! In-memory access token denylist shared by all requests.
+//!
+//! Access tokens are stateless JWTs, so logging out or changing a password
+//! would otherwise leave them usable until they expire. [`AccessTokenRevocations`]
+//! keeps the revoked token identifiers (`jti`) and per-user cut-offs in memory
+//! so [`AuthenticatedUser`](crate::auth::middleware::AuthenticatedUser) can
+//! check them without a database round trip.
+//!
+//! Postgres is the source of truth. Each instance loads the denylist at start-up
+//! and then applies the `NOTIFY` messages that database triggers send on
+//! [`REVOCATION_CHANNEL`], so a revocation on one instance takes effect on all.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use actix_web::rt::task::JoinHandle;
+use chrono::{DateTime, Utc};
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::auth::jwt::AccessTokenClaims;
+use crate::models::access_token_revocation::UserAccessTokenRevocation;
+use crate::repository::access_token_revocations::AccessTokenRevocationRepo;
+
+/// Postgres channel carrying [`Revocation`] messages as JSON.
+pub const REVOCATION_CHANNEL: &str = "access_token_revocations";
+
+/// Delay before reconnecting after the notification listener fails.
+const LISTENER_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// A revocation as broadcast on [`REVOCATION_CHANNEL`].
+///
+/// Expiries are Unix epoch seconds, matching JWT claims. User-wide cut-offs
+/// are Unix epoch milliseconds, so they also cover tokens issued earlier in
+/// the same second.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Revocation {
+    /// A single token identified by its `jti`.
+    Token {
+        /// Revoked token identifier.
+        jti: String,
+        /// Token expiry, after which the entry can be dropped.
+        expires_at: i64,
+    },
+    /// Every token issued to a user before `revoked_before`.
+    User {
+        /// The user whose tokens are revoked.
+        user_id: Uuid,
+        /// Tokens issued before this millisecond are rejected.
+        revoked_before: i64,
+        /// Expiry of the newest affected token, after which the entry can be dropped.
+        expires_at: i64,
+    },
+}
+
+impl From<UserAccessTokenRevocation> for Revocation {
+    fn from(revocation: UserAccessTokenRevocation) -> Self {
+        Revocation::User {
+            user_id: revocation.user_id,
+            revoked_before: revocation.revoked_before.timestamp_millis(),
+            expires_at: revocation.expires_at.timestamp(),
+        }
+    }
+}
+
+/// Revoked tokens and per-user cut-offs, keyed for constant-time lookups.
+#[derive(Debug, Default)]
+struct Denylist {
+    /// Expiry of each revoked `jti`.
+    tokens: HashMap<String, i64>,
+    /// Cut-off and expiry of each user-wide revocation.
+    users: HashMap<Uuid, (i64, i64)>,
+}
+
+/// Process-wide access token denylist.
+#[derive(Debug, Default)]
+pub struct AccessTokenRevocations {
+    denylist: RwLock<Denylist>,
+}
+
+impl AccessTokenRevocations {
+    /// Creates an empty denylist.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether an access token has been revoked.
+    ///
+    /// # Arguments
+    ///
+    /// - `claims` - Claims of a token that already passed signature and expiry checks
+    pub fn is_revoked(&self, claims: &AccessTokenClaims) -> bool {
+        let denylist = self.denylist.read().unwrap_or_else(|e| e.into_inner());
+
+        if !claims.jti.is_empty() && denylist.tokens.contains_key(&claims.jti) {
+            return true;
+        }
+
+        let Ok(user_id) = Uuid::parse_str(&claims.sub) else {
+            return false;
+        };
+        denylist
+            .users
+            .get(&user_id)
+            .is_some_and(|(revoked_before, _)| claims.issued_at_millis() < *revoked_before)
+    }
+
+    /// Adds a revocation to this instance's denylist and drops expired entries.
+    ///
+    /// # Arguments
+    ///
+    /// - `revocation` - Revocation to apply
+    pub fn apply(&self, revocation: Revocation) {
+        let now = Utc::now().timestamp();
+        let mut denylist = self.denylist.write().unwrap_or_else(|e| e.into_inner());
+
+        match revocation {
+            Revocation::Token { jti, expires_at } => {
+                denylist.tokens.insert(jti, expires_at);
+            }
+            Revocation::User {
+                user_id,
+                revoked_before,
+                expires_at,
+            } => {
+                let entry = denylist.users.entry(user_id).or_insert((0, 0));
+                *entry = (entry.0.max(revoked_before), entry.1.max(expires_at));
+            }
+        }
+
+        denylist.tokens.retain(|_, expires_at| *expires_at > now);
+        denylist
+            .users
+            .retain(|_, (_, expires_at)| *expires_at > now);
+    }
+
+    /// Replaces the denylist with the unexpired revocations stored in Postgres.
+    ///
+    /// Expired rows are deleted first.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if a query fails.
+    pub async fn load(&self, pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
+        AccessTokenRevocationRepo::delete_expired_revocations(pool).await?;
+        let tokens = AccessTokenRevocationRepo::list_active_revoked_access_tokens(pool).await?;
+        let users =
+            AccessTokenRevocationRepo::list_active_user_access_token_revocations(pool).await?;
+
+        let mut denylist = Denylist::default();
+        for token in tokens {
+            denylist
+                .tokens
+                .insert(token.jti, token.expires_at.timestamp());
+        }
+        for user in users {
+            denylist.users.insert(
+                user.user_id,
+                (
+                    user.revoked_before.timestamp_millis(),
+                    user.expires_at.timestamp(),
+                ),
+            );
+        }
+
+        *self.denylist.write().unwrap_or_else(|e| e.into_inner()) = denylist;
+
+        Ok(())
+    }
+
+    /// Revokes a single access token on every instance.
+    ///
+    /// The token is denied on this instance immediately, without waiting for
+    /// its own notification.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `user_id` - User the token was issued to
+    /// - `claims` - Claims of the token to revoke
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the revocation cannot be stored.
+    pub async fn revoke_token(
+        &self,
+        pool: &Pool<Postgres>,
+        user_id: Uuid,
+        claims: &AccessTokenClaims,
+    ) -> Result<(), sqlx::Error> {
+        if claims.jti.is_empty() {
+            return Ok(());
+        }
+
+        let expires_at = DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_else(Utc::now);
+        AccessTokenRevocationRepo::revoke_access_token(pool, &claims.jti, user_id, expires_at)
+            .await?;
+        self.apply(Revocation::Token {
+            jti: claims.jti.clone(),
+            expires_at: claims.exp as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Spawns a task that applies revocations broadcast by other instances.
+    ///
+    /// The denylist is reloaded whenever the listener (re)connects, so
+    /// notifications missed while disconnected are not lost. The task runs
+    /// until the returned handle is aborted or the runtime shuts down.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    pub fn listen(self: Arc<Self>, pool: Pool<Postgres>) -> JoinHandle<()> {
+        actix_web::rt::spawn(async move {
+            loop {
+                if let Err(error) = self.receive_notifications(&pool).await {
+                    error!("Access token revocation listener failed: {}", error);
+                }
+                actix_web::rt::time::sleep(LISTENER_RETRY_DELAY).await;
+            }
+        })
+    }
+
+    /// Listens on [`REVOCATION_CHANNEL`] until the connection fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if listening or reloading fails.
+    async fn receive_notifications(&self, pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(REVOCATION_CHANNEL).await?;
+        self.load(pool).await?;
+
+        loop {
+            match listener.try_recv().await? {
+                Some(notification) => match serde_json::from_str(notification.payload()) {
+                    Ok(revocation) => self.apply(revocation),
+                    Err(error) => warn!("Ignoring malformed revocation notification: {}", error),
+                },
+                // The connection dropped and will be re-established on the next call.
+                None => self.load(pool).await?,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::{AccessTokenRevocations, Revocation};
+    use crate::auth::jwt::AccessTokenClaims;
+
+    fn claims(user_id: Uuid, jti: &str, iat: i64) -> AccessTokenClaims {
+        AccessTokenClaims {
+            sub: user_id.to_string(),
+            email: "user@example.com".to_string(),
+            exp: (Utc::now().timestamp() + 900) as usize,
+            iat: iat as usize,
+            iat_ms: iat * 1000,
+            token_type: "access".to_string(),
+            jti: jti.to_string(),
+            token_version: 0,
+            auth_time: None,
+            amr: Vec::new(),
+        }
+    }
+
+    #[test]
+    // Verifies single-token revocations match only the revoked `jti`.
+    fn token_revocation_denies_only_that_jti() {
+        let revocations = AccessTokenRevocations::new();
+        let user_id = Uuid::new_v4();
+        let now = Utc::now().timestamp();
+
+        revocations.apply(Revocation::Token {
+            jti: "revoked".to_string(),
+            expires_at: now + 900,
+        });
+
+        assert!(revocations.is_revoked(&claims(user_id, "revoked", now)));
+        assert!(!revocations.is_revoked(&claims(user_id, "other", now)));
+    }
+
+    #[test]
+    // Verifies user-wide revocations deny tokens issued before the cut-off but not after it.
+    fn user_revocation_denies_tokens_issued_before_cut_off() {
+        let revocations = AccessTokenRevocations::new();
+        let user_id = Uuid::new_v4();
+        let now = Utc::now().timestamp();
+
+        revocations.apply(Revocation::User {
+            user_id,
+            revoked_before: now * 1000,
+            expires_at: now + 900,
+        });
+
+        assert!(revocations.is_revoked(&claims(user_id, "old", now - 1)));
+        assert!(!revocations.is_revoked(&claims(user_id, "new", now)));
+        assert!(!revocations.is_revoked(&claims(Uuid::new_v4(), "other-user", now - 1)));
+    }
+
+    #[test]
+    // Verifies a token issued earlier in the cut-off's second is revoked and a later one is not.
+    fn user_revocation_covers_tokens_issued_in_the_same_second() {
+        let revocations = AccessTokenRevocations::new();
+        let user_id = Uuid::new_v4();
+        let now = Utc::now().timestamp();
+
+        revocations.apply(Revocation::User {
+            user_id,
+            revoked_before: now * 1000 + 500,
+            expires_at: now + 900,
+        });
+
+        let mut earlier = claims(user_id, "earlier", now);
+        earlier.iat_ms = now * 1000 + 100;
+        assert!(revocations.is_revoked(&earlier));
+
+        let mut later = claims(user_id, "later", now);
+        later.iat_ms = now * 1000 + 900;
+        assert!(!revocations.is_revoked(&later));
+
+        // Tokens without `iat_ms` count from the start of their second.
+        let mut legacy = claims(user_id, "legacy", now);
+        legacy.iat_ms = 0;
+        assert!(revocations.is_revoked(&legacy));
+    }
+
+    #[test]
+    // Verifies expired entries are dropped and notifications parse from the trigger's JSON.
+    fn expired_entries_are_pruned_and_notifications_parse() {
+        let revocations = AccessTokenRevocations::new();
+        let user_id = Uuid::new_v4();
+        let now = Utc::now().timestamp();
+
+        revocations.apply(Revocation::Token {
+            jti: "expired".to_string(),
+            expires_at: now - 1,
+        });
+        assert!(!revocations.is_revoked(&claims(user_id, "expired", now - 60)));
+
+        let payload = format!(
+            r#"{{"kind": "user", "user_id": "{user_id}", "revoked_before": {}, "expires_at": {}}}"#,
+            now * 1000,
+            now + 900
+        );
+        let revocation: Revocation = serde_json::from_str(&payload).expect("payload parses");
+        assert_eq!(
+            revocation,
+            Revocation::User {
+                user_id,
+                revoked_before: now * 1000,
+                expires_at: now + 900,
+            }
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Short-lived cache of users' current token versions.
+//!
+//! Every access token carries the `token_version` its user had when it was
+//! issued. Bumping the version in `users` (on a password or email change, or
+//! by an administrator) rejects all older tokens at once, without tracking
+//! each token. [`TokenVersionCache`] keeps recently read versions in memory
+//! so [`AuthenticatedUser`](crate::auth::middleware::AuthenticatedUser) does
+//! not query the database on every request.
+//!
+//! Bumps on this instance update the cache directly. Other instances pick
+//! them up once their cached entry expires, so the TTL bounds how long an
+//! outdated token keeps working elsewhere.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::repository::auth::AuthRepo;
+
+/// Number of cached entries at which expired entries are first pruned.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// A token version and when it was read.
+#[derive(Debug, Clone, Copy)]
+struct CachedTokenVersion {
+    version: i32,
+    cached_at: Instant,
+}
+
+/// Process-wide cache of token versions by user.
+///
+/// Expired entries are pruned once the number of entries doubles since the
+/// last prune, which keeps the cost of pruning constant per stored version.
+#[derive(Debug)]
+pub struct TokenVersionCache {
+    ttl: Duration,
+    entries: RwLock<CachedTokenVersions>,
+}
+
+/// Cached versions and the pruning schedule, guarded together.
+#[derive(Debug)]
+struct CachedTokenVersions {
+    /// Cached version of each user.
+    versions_by_user: HashMap<Uuid, CachedTokenVersion>,
+    /// Entry count at which expired entries are next pruned.
+    prune_at: usize,
+}
+
+impl TokenVersionCache {
+    /// Creates an empty cache.
+    ///
+    /// # Arguments
+    ///
+    /// - `ttl` - How long a version is served from memory before being re-read
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(CachedTokenVersions {
+                versions_by_user: HashMap::new(),
+                prune_at: PRUNE_THRESHOLD,
+            }),
+        }
+    }
+
+    /// Returns a user's current token version.
+    ///
+    /// Returns `None` when the user no longer exists.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool, used on a cache miss
+    /// - `user_id` - User identifier to look up
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the database lookup fails.
+    pub async fn current(
+        &self,
+        pool: &Pool<Postgres>,
+        user_id: Uuid,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        if let Some(version) = self.cached(user_id) {
+            return Ok(Some(version));
+        }
+
+        let version = AuthRepo::find_token_version(pool, user_id).await?;
+        if let Some(version) = version {
+            self.store(user_id, version);
+        }
+
+        Ok(version)
+    }
+
+    /// Records a user's token version, such as right after bumping it.
+    ///
+    /// # Arguments
+    ///
+    /// - `user_id` - User identifier
+    /// - `version` - The user's current token version
+    pub fn store(&self, user_id: Uuid, version: i32) {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+
+        if entries.versions_by_user.len() >= entries.prune_at {
+            let ttl = self.ttl;
+            entries
+                .versions_by_user
+                .retain(|_, entry| entry.cached_at.elapsed() < ttl);
+            entries.prune_at = (entries.versions_by_user.len() * 2).max(PRUNE_THRESHOLD);
+        }
+
+        entries.versions_by_user.insert(
+            user_id,
+            CachedTokenVersion {
+                version,
+                cached_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Drops a user's cached version, such as after deleting the user.
+    ///
+    /// # Arguments
+    ///
+    /// - `user_id` - User identifier
+    pub fn forget(&self, user_id: Uuid) {
+        self.entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .versions_by_user
+            .remove(&user_id);
+    }
+
+    /// Returns the cached version for a user if it has not expired.
+    fn cached(&self, user_id: Uuid) -> Option<i32> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+
+        entries
+            .versions_by_user
+            .get(&user_id)
+            .filter(|entry| entry.cached_at.elapsed() < self.ttl)
+            .map(|entry| entry.version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use uuid::Uuid;
+
+    use super::{PRUNE_THRESHOLD, TokenVersionCache};
+
+    #[test]
+    // Verifies stored versions are served until the TTL elapses.
+    fn cached_versions_expire_after_ttl() {
+        let user_id = Uuid::new_v4();
+
+        let cache = TokenVersionCache::new(Duration::from_secs(60));
+        cache.store(user_id, 3);
+        assert_eq!(cache.cached(user_id), Some(3));
+        cache.store(user_id, 4);
+        assert_eq!(cache.cached(user_id), Some(4));
+        assert_eq!(cache.cached(Uuid::new_v4()), None);
+        cache.forget(user_id);
+        assert_eq!(cache.cached(user_id), None);
+
+        let expired = TokenVersionCache::new(Duration::ZERO);
+        expired.store(user_id, 3);
+        assert_eq!(expired.cached(user_id), None);
+    }
+
+    #[test]
+    // Verifies a full cache of live entries is scanned once per doubling, not on every store.
+    fn pruning_is_amortised() {
+        let cache = TokenVersionCache::new(Duration::from_secs(60));
+        for _ in 0..=PRUNE_THRESHOLD {
+            cache.store(Uuid::new_v4(), 0);
+        }
+
+        let entries = cache.entries.read().expect("lock should not be poisoned");
+        assert_eq!(entries.versions_by_user.len(), PRUNE_THRESHOLD + 1);
+        assert_eq!(entries.prune_at, PRUNE_THRESHOLD * 2);
+    }
+}
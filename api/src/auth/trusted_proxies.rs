@@ -0,0 +1,286 @@
+//! Client address resolution behind trusted reverse proxies.
+//!
+//! `Forwarded` and `X-Forwarded-For` are ordinary request headers, so any
+//! client can send them. They are only believed when the TCP peer is one of
+//! the configured proxies; otherwise the peer address is the client address.
+//!
+//! Only one header is read, the one the proxies are configured to append to
+//! (`X-Forwarded-For` unless set otherwise). The other header is passed through
+//! from the client unchanged, so it is never consulted.
+//!
+//! Behind trusted proxies, the forwarded chain is read from right to left and
+//! the first address that is not itself a trusted proxy is the client. Entries
+//! further left were written by the client and are ignored.
+
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+
+use actix_web::HttpRequest;
+use actix_web::http::header::{self, HeaderMap};
+use ipnet::IpNet;
+
+/// Header trusted proxies record the client address in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ForwardedHeader {
+    /// RFC 7239 `Forwarded`, reading the `for` parameter of each element.
+    Forwarded,
+    /// `X-Forwarded-For`, as appended by nginx and most load balancers.
+    #[default]
+    XForwardedFor,
+}
+
+impl FromStr for ForwardedHeader {
+    type Err = String;
+
+    /// Parses `forwarded` or `x-forwarded-for`, ignoring case.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "forwarded" => Ok(Self::Forwarded),
+            "x-forwarded-for" => Ok(Self::XForwardedFor),
+            _ => Err(format!("Invalid trusted proxy header: {}", value)),
+        }
+    }
+}
+
+/// Reverse proxies whose forwarded headers are trusted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+    header: ForwardedHeader,
+}
+
+impl TrustedProxies {
+    /// Sets the header the proxies record the client address in.
+    ///
+    /// # Arguments
+    ///
+    /// - `header` - Header appended to by the trusted proxies
+    pub fn with_header(mut self, header: ForwardedHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Returns whether an address belongs to a trusted proxy.
+    ///
+    /// # Arguments
+    ///
+    /// - `address` - Address to check
+    pub fn contains(&self, address: IpAddr) -> bool {
+        self.networks
+            .iter()
+            .any(|network| network.contains(&address))
+    }
+
+    /// Returns the client's IP address for a request.
+    ///
+    /// Returns `None` when the request has no peer address, as in tests that
+    /// do not set one.
+    ///
+    /// # Arguments
+    ///
+    /// - `req` - Incoming request
+    pub fn client_address(&self, req: &HttpRequest) -> Option<IpAddr> {
+        let peer = req.peer_addr()?.ip();
+        if !self.contains(peer) {
+            return Some(peer);
+        }
+
+        let mut client = peer;
+        for address in forwarded_addresses(req.headers(), self.header).iter().rev() {
+            let Some(address) = address else {
+                break;
+            };
+            client = *address;
+            if !self.contains(client) {
+                break;
+            }
+        }
+
+        Some(client)
+    }
+}
+
+impl FromStr for TrustedProxies {
+    type Err = String;
+
+    /// Parses a comma-separated list of IP addresses and CIDR ranges, such as
+    /// `10.0.0.0/8,127.0.0.1`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let networks = value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                entry
+                    .parse::<IpNet>()
+                    .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| format!("Invalid trusted proxy: {}", entry))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            networks,
+            header: ForwardedHeader::default(),
+        })
+    }
+}
+
+/// Returns the forwarded-for chain of a request from one header, nearest hop
+/// last.
+///
+/// Entries that are not IP addresses, such as `unknown` or obfuscated
+/// identifiers, are returned as `None`.
+///
+/// # Arguments
+///
+/// - `headers` - Request headers
+/// - `header` - Header to read the chain from
+fn forwarded_addresses(headers: &HeaderMap, header: ForwardedHeader) -> Vec<Option<IpAddr>> {
+    match header {
+        ForwardedHeader::Forwarded => headers
+            .get_all(header::FORWARDED)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (name, value) = pair.trim().split_once('=')?;
+                    name.eq_ignore_ascii_case("for").then_some(value)
+                })
+            })
+            .map(parse_address)
+            .collect(),
+        ForwardedHeader::XForwardedFor => headers
+            .get_all(header::X_FORWARDED_FOR)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(parse_address)
+            .collect(),
+    }
+}
+
+/// Parses a forwarded address, with or without quotes, brackets, or a port.
+///
+/// # Arguments
+///
+/// - `value` - Address such as `203.0.113.7`, `"[2001:db8::1]:4711"`
+fn parse_address(value: &str) -> Option<IpAddr> {
+    let value = value.trim().trim_matches('"');
+
+    value
+        .parse::<IpAddr>()
+        .or_else(|_| value.parse::<SocketAddr>().map(|socket| socket.ip()))
+        .or_else(|_| {
+            value
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+        })
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use actix_web::test::TestRequest;
+
+    use super::{ForwardedHeader, TrustedProxies};
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().expect("test address should parse")
+    }
+
+    #[test]
+    // Verifies forwarded headers are ignored unless the peer is a trusted proxy.
+    fn forwarded_headers_require_a_trusted_peer() {
+        let proxies: TrustedProxies = "10.0.0.0/8, 127.0.0.1".parse().expect("proxies parse");
+
+        let direct = TestRequest::default()
+            .peer_addr("198.51.100.9:50000".parse().expect("peer parses"))
+            .insert_header(("X-Forwarded-For", "203.0.113.7"))
+            .to_http_request();
+        assert_eq!(proxies.client_address(&direct), Some(ip("198.51.100.9")));
+        assert_eq!(
+            TrustedProxies::default().client_address(&direct),
+            Some(ip("198.51.100.9"))
+        );
+
+        let proxied = TestRequest::default()
+            .peer_addr("10.1.2.3:50000".parse().expect("peer parses"))
+            .insert_header(("X-Forwarded-For", "192.0.2.1, 203.0.113.7, 10.0.0.5"))
+            .to_http_request();
+        assert_eq!(proxies.client_address(&proxied), Some(ip("203.0.113.7")));
+
+        let no_header = TestRequest::default()
+            .peer_addr("127.0.0.1:50000".parse().expect("peer parses"))
+            .to_http_request();
+        assert_eq!(proxies.client_address(&no_header), Some(ip("127.0.0.1")));
+
+        assert_eq!(
+            proxies.client_address(&TestRequest::default().to_http_request()),
+            None
+        );
+    }
+
+    #[test]
+    // Verifies a client-supplied `Forwarded` is ignored when proxies append `X-Forwarded-For`.
+    fn only_the_configured_header_is_read() {
+        let proxies: TrustedProxies = "10.0.0.0/8".parse().expect("proxies parse");
+
+        let request = TestRequest::default()
+            .peer_addr("10.1.2.3:50000".parse().expect("peer parses"))
+            .insert_header(("Forwarded", "for=192.0.2.99"))
+            .insert_header(("X-Forwarded-For", "192.0.2.1, 203.0.113.7"))
+            .to_http_request();
+        assert_eq!(proxies.client_address(&request), Some(ip("203.0.113.7")));
+        assert_eq!(
+            proxies
+                .with_header(ForwardedHeader::Forwarded)
+                .client_address(&request),
+            Some(ip("192.0.2.99"))
+        );
+    }
+
+    #[test]
+    // Verifies RFC 7239 `Forwarded` elements are parsed with quotes and ports.
+    fn forwarded_header_is_parsed() {
+        let proxies = "127.0.0.1"
+            .parse::<TrustedProxies>()
+            .expect("proxies parse")
+            .with_header(ForwardedHeader::Forwarded);
+
+        let request = TestRequest::default()
+            .peer_addr("127.0.0.1:50000".parse().expect("peer parses"))
+            .insert_header((
+                "Forwarded",
+                "for=192.0.2.60;proto=http, for=\"[2001:db8:cafe::17]:4711\"",
+            ))
+            .insert_header(("X-Forwarded-For", "198.51.100.9"))
+            .to_http_request();
+        assert_eq!(
+            proxies.client_address(&request),
+            Some(ip("2001:db8:cafe::17"))
+        );
+
+        let unknown = TestRequest::default()
+            .peer_addr("127.0.0.1:50000".parse().expect("peer parses"))
+            .insert_header(("Forwarded", "for=192.0.2.60, for=unknown"))
+            .to_http_request();
+        assert_eq!(proxies.client_address(&unknown), Some(ip("127.0.0.1")));
+    }
+
+    #[test]
+    // Verifies invalid proxy entries and header names are rejected.
+    fn invalid_proxies_are_rejected() {
+        assert!("10.0.0.0/8,not-an-ip".parse::<TrustedProxies>().is_err());
+        assert!("x-real-ip".parse::<ForwardedHeader>().is_err());
+        assert_eq!(
+            "X-Forwarded-For".parse::<ForwardedHeader>(),
+            Ok(ForwardedHeader::XForwardedFor)
+        );
+        assert_eq!(
+            "".parse::<TrustedProxies>().expect("empty list parses"),
+            TrustedProxies::default()
+        );
+    }
+}
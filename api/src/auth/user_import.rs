@@ -0,0 +1,324 @@
+//! Bulk import of users migrated from another system.
+//!
+//! Users are read from CSV (with a header row) or JSON Lines, one user per
+//! row, with the fields `email`, `first_name`, `last_name`, `hashed_password`
+//! and an optional `locale`. Imported users get a confirmed email and keep
+//! their existing hash, which may be Argon2, bcrypt, PBKDF2-SHA256 or scrypt
+//! (see [`password`](crate::auth::password)); non-Argon2id hashes are
+//! upgraded at the user's first log-in. The `import_users` binary drives this
+//! module from the command line.
+
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::auth::password::is_supported_hash;
+use crate::repository::auth::AuthRepo;
+use crate::services::email_templates::DEFAULT_LOCALE;
+use crate::validators::locale::validate_locale;
+
+/// Input file formats accepted by [`read_users`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// Comma-separated values with a header row naming the fields.
+    Csv,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+impl ImportFormat {
+    /// Picks the format from a file extension (`.csv`, `.jsonl` or `.ndjson`).
+    ///
+    /// # Arguments
+    ///
+    /// - `path` - Input file path
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "jsonl" | "ndjson" => Some(Self::JsonLines),
+            _ => None,
+        }
+    }
+}
+
+/// A user record read from an import file.
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ImportedUser {
+    /// User's email address.
+    #[validate(email(message = "Email is invalid"))]
+    pub email: String,
+
+    /// User's first name.
+    #[validate(length(min = 1, message = "First name is required"))]
+    pub first_name: String,
+
+    /// User's last name.
+    #[validate(length(min = 1, message = "Last name is required"))]
+    pub last_name: String,
+
+    /// Password hash from the old system.
+    #[validate(custom(function = "validate_imported_hash"))]
+    pub hashed_password: String,
+
+    /// Preferred locale for emails; defaults to `en`.
+    #[serde(default)]
+    #[validate(custom(function = "validate_locale"))]
+    pub locale: Option<String>,
+}
+
+impl ImportedUser {
+    /// Trims every field, lowercases the email and normalizes the locale the
+    /// same way sign-up does.
+    fn normalized(self) -> Self {
+        Self {
+            email: self.email.trim().to_lowercase(),
+            first_name: self.first_name.trim().to_string(),
+            last_name: self.last_name.trim().to_string(),
+            hashed_password: self.hashed_password.trim().to_string(),
+            locale: self
+                .locale
+                .map(|locale| locale.trim().replace('_', "-"))
+                .filter(|locale| !locale.is_empty()),
+        }
+    }
+}
+
+/// Validates that an imported hash uses a scheme log-in can verify.
+///
+/// # Errors
+///
+/// Returns a `ValidationError` with code `unsupported_password_hash` otherwise.
+fn validate_imported_hash(hashed_password: &str) -> Result<(), ValidationError> {
+    if is_supported_hash(hashed_password) {
+        return Ok(());
+    }
+
+    let mut error = ValidationError::new("unsupported_password_hash");
+    error.message = Some("Password hash format is not supported".into());
+    Err(error)
+}
+
+/// One row of an import file, parsed or not.
+#[derive(Debug)]
+pub struct ImportRow {
+    /// Line number in the input file (1-based).
+    pub line: u64,
+    /// The parsed user, or why the row could not be parsed.
+    pub user: Result<ImportedUser, String>,
+}
+
+/// A row that was not imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedRow {
+    /// Line number in the input file (1-based).
+    pub line: u64,
+    /// Why the row was skipped.
+    pub reason: String,
+}
+
+/// Outcome of [`import_users`].
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    /// Number of users created.
+    pub imported: u64,
+    /// Rows that were invalid or whose email already exists.
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// Reads every row of an import file.
+///
+/// Rows that fail to parse are returned with their error so one bad row does
+/// not stop the import.
+///
+/// # Arguments
+///
+/// - `input` - Import file contents
+/// - `format` - Format of `input`
+///
+/// # Errors
+///
+/// Returns an I/O error if reading fails, including a CSV file without a
+/// readable header row.
+pub fn read_users<R: Read>(input: R, format: ImportFormat) -> io::Result<Vec<ImportRow>> {
+    match format {
+        ImportFormat::Csv => read_csv(input),
+        ImportFormat::JsonLines => read_json_lines(input),
+    }
+}
+
+/// Reads CSV rows, matching columns to fields by the header row.
+fn read_csv<R: Read>(input: R) -> io::Result<Vec<ImportRow>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input);
+    let headers = reader.headers().map_err(io::Error::other)?.clone();
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let row = match record {
+            Ok(record) => ImportRow {
+                line: record.position().map_or(0, |position| position.line()),
+                user: record
+                    .deserialize::<ImportedUser>(Some(&headers))
+                    .map_err(|error| error.to_string()),
+            },
+            Err(error) if error.is_io_error() => return Err(io::Error::other(error)),
+            Err(error) => ImportRow {
+                line: error.position().map_or(0, |position| position.line()),
+                user: Err(error.to_string()),
+            },
+        };
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// Reads one JSON object per non-blank line.
+fn read_json_lines<R: Read>(input: R) -> io::Result<Vec<ImportRow>> {
+    let mut rows = Vec::new();
+    for (index, line) in BufReader::new(input).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        rows.push(ImportRow {
+            line: index as u64 + 1,
+            user: serde_json::from_str(&line).map_err(|error| error.to_string()),
+        });
+    }
+
+    Ok(rows)
+}
+
+/// Creates users from parsed import rows in a single transaction.
+///
+/// Invalid rows and rows whose email already exists (in the database or
+/// earlier in the file) are skipped and reported in the summary.
+///
+/// # Arguments
+///
+/// - `pool` - Database connection pool
+/// - `rows` - Rows returned by [`read_users`]
+/// - `dry_run` - Validate and count without committing anything
+///
+/// # Errors
+///
+/// Returns `sqlx::Error` if a database operation fails; nothing is imported
+/// in that case.
+pub async fn import_users(
+    pool: &Pool<Postgres>,
+    rows: Vec<ImportRow>,
+    dry_run: bool,
+) -> Result<ImportSummary, sqlx::Error> {
+    let mut summary = ImportSummary::default();
+    let mut tx = pool.begin().await?;
+
+    for row in rows {
+        let user = match row.user.map(ImportedUser::normalized).and_then(|user| {
+            user.validate()
+                .map(|_| user)
+                .map_err(|errors| validation_message(&errors))
+        }) {
+            Ok(user) => user,
+            Err(reason) => {
+                summary.skipped.push(SkippedRow {
+                    line: row.line,
+                    reason,
+                });
+                continue;
+            }
+        };
+
+        let created = AuthRepo::import_user(
+            &mut tx,
+            &user.first_name,
+            &user.last_name,
+            &user.email,
+            &user.hashed_password,
+            user.locale.as_deref().unwrap_or(DEFAULT_LOCALE),
+        )
+        .await?;
+
+        match created {
+            Some(_) => summary.imported += 1,
+            None => summary.skipped.push(SkippedRow {
+                line: row.line,
+                reason: format!("A user with email {} already exists", user.email),
+            }),
+        }
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    Ok(summary)
+}
+
+/// Joins validation messages into one line, ordered by field name.
+fn validation_message(errors: &ValidationErrors) -> String {
+    let field_errors = errors.field_errors();
+    let mut fields: Vec<_> = field_errors.keys().collect();
+    fields.sort();
+
+    fields
+        .into_iter()
+        .flat_map(|field| {
+            field_errors[field].iter().map(move |error| {
+                error
+                    .message
+                    .as_ref()
+                    .map(|message| message.to_string())
+                    .unwrap_or_else(|| format!("{} is invalid", field))
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{ImportFormat, read_users};
+
+    #[test]
+    // Verifies CSV and JSON Lines rows are parsed with their line numbers and per-row errors.
+    fn reads_csv_and_json_lines_rows() {
+        let csv = "email,first_name,last_name,hashed_password,locale\n\
+                   ada@example.com,Ada,Lovelace,$2b$04$abc,es\n\
+                   grace@example.com,Grace\n";
+        let rows = read_users(Cursor::new(csv), ImportFormat::Csv).expect("CSV should read");
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].line, 2);
+        let user = rows[0].user.as_ref().expect("first row should parse");
+        assert_eq!(user.email, "ada@example.com");
+        assert_eq!(user.locale.as_deref(), Some("es"));
+        assert_eq!(rows[1].line, 3);
+        assert!(rows[1].user.is_err());
+
+        let jsonl = "{\"email\":\"ada@example.com\",\"first_name\":\"Ada\",\"last_name\":\"Lovelace\",\"hashed_password\":\"$2b$04$abc\"}\n\
+                     \n\
+                     {\"email\":\"grace@example.com\"}\n";
+        let rows =
+            read_users(Cursor::new(jsonl), ImportFormat::JsonLines).expect("JSONL should read");
+
+        assert_eq!(rows.len(), 2);
+        assert!(
+            rows[0]
+                .user
+                .as_ref()
+                .is_ok_and(|user| user.locale.is_none())
+        );
+        assert_eq!(rows[1].line, 3);
+        assert!(rows[1].user.is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Recommends `ARGON2_*` settings that hash a password within a target time on this host.
+//!
+//! Starting from the `argon2` defaults, memory is doubled while a hash stays
+//! under the target, then passes are added until the next one would exceed
+//! it. Run it on hardware matching production, in release mode:
+//!
+//! ```text
+//! cargo run --release --bin argon2_benchmark -- \
+//!     [--target-ms N] [--max-memory-mib N] [--parallelism N]
+//! ```
+
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use api::auth::password::{PasswordHashParams, hash_password_with_params};
+
+const USAGE: &str =
+    "usage: argon2_benchmark [--target-ms N] [--max-memory-mib N] [--parallelism N]";
+
+/// Number of hashes timed per candidate; the fastest run counts.
+const SAMPLES: usize = 3;
+
+/// Password hashed during the benchmark.
+const SAMPLE_PASSWORD: &str = "Velvet-Harbor-42";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let mut target_ms: u64 = 250;
+    let mut max_memory_mib: u32 = 256;
+    let mut parallelism: u32 = 1;
+
+    for pair in args.chunks(2) {
+        let parsed = match pair {
+            [flag, value] if flag == "--target-ms" => value.parse().map(|v| target_ms = v),
+            [flag, value] if flag == "--max-memory-mib" => {
+                value.parse().map(|v| max_memory_mib = v)
+            }
+            [flag, value] if flag == "--parallelism" => value.parse().map(|v| parallelism = v),
+            _ => {
+                eprintln!("{}", USAGE);
+                return ExitCode::FAILURE;
+            }
+        };
+        if parsed.is_err() {
+            eprintln!("{} must be a positive integer", pair[0]);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let target = Duration::from_millis(target_ms);
+    let max_memory_kib = max_memory_mib.saturating_mul(1024);
+
+    let mut best = PasswordHashParams {
+        parallelism,
+        ..PasswordHashParams::default()
+    };
+    let mut best_time = match time_hash(&best) {
+        Ok(elapsed) => elapsed,
+        Err(error) => {
+            eprintln!("Failed to hash with {:?}: {}", best, error);
+            return ExitCode::FAILURE;
+        }
+    };
+    report(&best, best_time);
+
+    if best_time > target {
+        println!();
+        println!(
+            "The argon2 defaults already take longer than {} ms; keeping them.",
+            target_ms
+        );
+        print_recommendation(&best);
+        return ExitCode::SUCCESS;
+    }
+
+    // Memory first: it is the cost attackers' hardware finds most expensive.
+    while best.memory_kib.saturating_mul(2) <= max_memory_kib {
+        let candidate = PasswordHashParams {
+            memory_kib: best.memory_kib * 2,
+            ..best.clone()
+        };
+        let Ok(elapsed) = time_hash(&candidate) else {
+            break;
+        };
+        report(&candidate, elapsed);
+        if elapsed > target {
+            break;
+        }
+        (best, best_time) = (candidate, elapsed);
+    }
+
+    // Then spend the remaining budget on extra passes.
+    loop {
+        let candidate = PasswordHashParams {
+            iterations: best.iterations + 1,
+            ..best.clone()
+        };
+        let Ok(elapsed) = time_hash(&candidate) else {
+            break;
+        };
+        report(&candidate, elapsed);
+        if elapsed > target {
+            break;
+        }
+        (best, best_time) = (candidate, elapsed);
+    }
+
+    println!();
+    println!(
+        "Recommended for a {} ms target ({} ms measured):",
+        target_ms,
+        best_time.as_millis()
+    );
+    print_recommendation(&best);
+
+    ExitCode::SUCCESS
+}
+
+/// Returns the fastest of [`SAMPLES`] hashes with the given parameters.
+fn time_hash(params: &PasswordHashParams) -> Result<Duration, String> {
+    let mut fastest = Duration::MAX;
+    for _ in 0..SAMPLES {
+        let started = Instant::now();
+        hash_password_with_params(SAMPLE_PASSWORD, params).map_err(|error| error.to_string())?;
+        fastest = fastest.min(started.elapsed());
+    }
+
+    Ok(fastest)
+}
+
+/// Prints one measured candidate.
+fn report(params: &PasswordHashParams, elapsed: Duration) {
+    println!(
+        "m={} KiB t={} p={}: {} ms",
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        elapsed.as_millis()
+    );
+}
+
+/// Prints the settings in `.env` form.
+fn print_recommendation(params: &PasswordHashParams) {
+    println!("ARGON2_MEMORY_KIB={}", params.memory_kib);
+    println!("ARGON2_ITERATIONS={}", params.iterations);
+    println!("ARGON2_PARALLELISM={}", params.parallelism);
+}
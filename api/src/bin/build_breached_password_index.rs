@@ -0,0 +1,52 @@
+//! Builds the breached password index read by `BREACHED_PASSWORDS_INDEX_PATH`.
+//!
+//! Converts a Have I Been Pwned "ordered by hash" SHA-1 download into the
+//! compact index format of [`api::auth::breached_passwords`].
+//!
+//! ```text
+//! cargo run --release --bin build_breached_password_index -- \
+//!     pwnedpasswords.txt breached-passwords.idx [--min-count N]
+//! ```
+
+use std::fs::File;
+use std::io::BufReader;
+use std::process::ExitCode;
+
+use api::auth::breached_passwords::build_index;
+
+const USAGE: &str = "usage: build_breached_password_index <input> <output> [--min-count N]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let (input_path, output_path, min_count) = match args.as_slice() {
+        [input, output] => (input, output, 1),
+        [input, output, flag, count] if flag == "--min-count" => match count.parse::<u64>() {
+            Ok(count) => (input, output, count),
+            Err(_) => {
+                eprintln!("--min-count must be a non-negative integer");
+                return ExitCode::FAILURE;
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = File::open(input_path).and_then(|input| {
+        let output = File::create(output_path)?;
+        build_index(BufReader::new(input), output, min_count)
+    });
+
+    match result {
+        Ok(written) => {
+            println!("Wrote {} hashes to {}", written, output_path);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("Failed to build index: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
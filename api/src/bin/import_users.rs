@@ -0,0 +1,104 @@
+//! Imports users from another system with their existing password hashes.
+//!
+//! Reads a CSV or JSON Lines file in the format described in
+//! [`api::auth::user_import`] and creates confirmed users in the database at
+//! `DATABASE_URL` (read from the environment or `.env`). Skipped rows are
+//! listed with their line numbers; `--dry-run` validates without writing.
+//!
+//! ```text
+//! cargo run --release --bin import_users -- users.csv [--format csv|jsonl] [--dry-run]
+//! ```
+
+use std::fs::File;
+use std::path::Path;
+use std::process::ExitCode;
+
+use sqlx::postgres::PgPoolOptions;
+
+use api::auth::user_import::{ImportFormat, import_users, read_users};
+
+const USAGE: &str = "usage: import_users <input> [--format csv|jsonl] [--dry-run]";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let mut input_path = None;
+    let mut format = None;
+    let mut dry_run = false;
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--format" => {
+                format = match args_iter.next().map(String::as_str) {
+                    Some("csv") => Some(ImportFormat::Csv),
+                    Some("jsonl") => Some(ImportFormat::JsonLines),
+                    _ => {
+                        eprintln!("--format must be csv or jsonl");
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            path if input_path.is_none() && !path.starts_with("--") => input_path = Some(path),
+            _ => {
+                eprintln!("{}", USAGE);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let Some(input_path) = input_path else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+    let Some(format) = format.or_else(|| ImportFormat::from_path(Path::new(input_path))) else {
+        eprintln!("Cannot tell the format from the file name; pass --format csv|jsonl");
+        return ExitCode::FAILURE;
+    };
+
+    let rows = match File::open(input_path).and_then(|input| read_users(input, format)) {
+        Ok(rows) => rows,
+        Err(error) => {
+            eprintln!("Failed to read {}: {}", input_path, error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    dotenvy::dotenv().ok();
+    let Ok(database_url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set");
+        return ExitCode::FAILURE;
+    };
+    let pool = match PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+    {
+        Ok(pool) => pool,
+        Err(error) => {
+            eprintln!("Failed to connect to the database: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let summary = match import_users(&pool, rows, dry_run).await {
+        Ok(summary) => summary,
+        Err(error) => {
+            eprintln!("Import failed, nothing was imported: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    for skipped in &summary.skipped {
+        eprintln!("line {}: {}", skipped.line, skipped.reason);
+    }
+    println!(
+        "{} {} users, skipped {} rows",
+        if dry_run { "Would import" } else { "Imported" },
+        summary.imported,
+        summary.skipped.len()
+    );
+
+    ExitCode::SUCCESS
+}
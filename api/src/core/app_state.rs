@@ -3,16 +3,37 @@
 //! This module groups runtime dependencies needed by request handlers so they
 //! can receive a single injected state value in both production and tests.
 
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use sqlx::{Pool, Postgres};
 
+use crate::auth::breached_passwords::BreachedPasswordIndex;
+use crate::auth::codes::AuthCodeConfig;
+use crate::auth::email_domains::{DisposableEmailDomains, EmailDomainPolicy};
+use crate::auth::password::PasswordHashParams;
+use crate::auth::password_policy::PasswordPolicy;
+use crate::auth::request_rates::RequestRateTracker;
+use crate::auth::revocation::AccessTokenRevocations;
+use crate::auth::token_versions::TokenVersionCache;
 use crate::core::env::Env;
+use crate::core::error::{ApiError, ApiResult};
+use crate::services::dev_mailbox::DevMailbox;
 use crate::services::email::{EmailSender, EmailService};
+use crate::services::email_templates::EmailTemplates;
+use crate::services::human_verification::{HttpHumanVerifier, HumanVerifier, ProofOfWorkVerifier};
+use crate::services::sms::{HttpSmsGateway, LogSmsSender, SmsSender};
 
 /// Shared email sender trait object used by handlers.
 pub type DynEmailSender = Arc<dyn EmailSender + Send + Sync>;
 
+/// Shared SMS sender trait object used by handlers.
+pub type DynSmsSender = Arc<dyn SmsSender + Send + Sync>;
+
+/// Shared human verifier trait object used by handlers.
+pub type DynHumanVerifier = Arc<dyn HumanVerifier + Send + Sync>;
+
 /// Runtime application dependencies shared across requests.
 #[derive(Clone)]
 pub struct AppState {
@@ -22,25 +43,92 @@ pub struct AppState {
     pub env: Env,
     /// Email sender used by authentication flows.
     pub email_sender: DynEmailSender,
+    /// In-memory mailbox backing [`email_sender`](Self::email_sender) in development.
+    ///
+    /// Set only when no Resend API key is configured in development.
+    pub dev_mailbox: Option<Arc<DevMailbox>>,
+    /// SMS sender used by phone-number flows, when SMS delivery is available.
+    pub sms_sender: Option<DynSmsSender>,
+    /// Revoked access tokens, checked on every authenticated request.
+    pub access_token_revocations: Arc<AccessTokenRevocations>,
+    /// Users' current token versions, checked on every authenticated request.
+    pub token_versions: Arc<TokenVersionCache>,
+    /// Breached password corpus, when `BREACHED_PASSWORDS_INDEX_PATH` is configured.
+    pub breached_passwords: Option<Arc<BreachedPasswordIndex>>,
+    /// Disposable domain list from `DISPOSABLE_EMAIL_DOMAINS_PATH`, replacing the bundled one.
+    pub disposable_email_domains: Option<Arc<DisposableEmailDomains>>,
+    /// Challenge backend for clients that cross the human verification threshold.
+    pub human_verifier: DynHumanVerifier,
+    /// Recent sign-up and password reset requests by client address and email.
+    pub request_rates: Arc<RequestRateTracker>,
 }
 
 impl AppState {
-    /// Creates app state with the default Resend-backed email sender.
+    /// Creates app state with the default email sender.
+    ///
+    /// Uses the Resend-backed sender when an API key is configured. In
+    /// development without an API key, emails are captured by a [`DevMailbox`]
+    /// instead.
+    ///
+    /// SMS messages are sent through the configured HTTP gateway. Without a
+    /// gateway, they are logged in development and disabled elsewhere.
     ///
     /// # Arguments
     ///
     /// - `pool` - Database connection pool.
     /// - `env` - Runtime environment configuration.
     pub fn new(pool: Pool<Postgres>, env: Env) -> Self {
-        let email_sender = Arc::new(EmailService::new(
-            &env.resend_api_key,
-            &env.resend_from_email,
+        let templates = Arc::new(EmailTemplates::new(
+            env.email_templates_dir.as_deref().map(Path::new),
+            env.auth_code_expiry_seconds,
         ));
 
+        let (email_sender, dev_mailbox): (DynEmailSender, Option<Arc<DevMailbox>>) =
+            match env.resend_api_key.as_deref() {
+                Some(api_key) => (
+                    Arc::new(EmailService::new(
+                        api_key,
+                        &env.resend_from_email,
+                        templates,
+                    )),
+                    None,
+                ),
+                None => {
+                    let mailbox = Arc::new(DevMailbox::new(&env.resend_from_email, templates));
+                    (mailbox.clone(), Some(mailbox))
+                }
+            };
+
+        let sms_sender: Option<DynSmsSender> = match env.sms_gateway_url.as_deref() {
+            Some(url) => Some(Arc::new(HttpSmsGateway::new(
+                url,
+                env.sms_gateway_api_key.as_deref(),
+                env.sms_from.as_deref(),
+                env.auth_code_expiry_seconds,
+            ))),
+            None if env.is_development() => {
+                Some(Arc::new(LogSmsSender::new(env.auth_code_expiry_seconds)))
+            }
+            None => None,
+        };
+
+        let token_versions = Arc::new(TokenVersionCache::new(Duration::from_secs(
+            env.token_version_cache_ttl_seconds,
+        )));
+        let (human_verifier, request_rates) = human_verification_from_env(&env);
+
         Self {
             pool,
             env,
             email_sender,
+            dev_mailbox,
+            sms_sender,
+            access_token_revocations: Arc::new(AccessTokenRevocations::new()),
+            token_versions,
+            breached_passwords: None,
+            disposable_email_domains: None,
+            human_verifier,
+            request_rates,
         }
     }
 
@@ -54,10 +142,153 @@ impl AppState {
     /// - `env` - Runtime environment configuration.
     /// - `email_sender` - Email sender implementation.
     pub fn with_email_sender(pool: Pool<Postgres>, env: Env, email_sender: DynEmailSender) -> Self {
+        let token_versions = Arc::new(TokenVersionCache::new(Duration::from_secs(
+            env.token_version_cache_ttl_seconds,
+        )));
+        let (human_verifier, request_rates) = human_verification_from_env(&env);
+
         Self {
             pool,
             env,
             email_sender,
+            dev_mailbox: None,
+            sms_sender: None,
+            access_token_revocations: Arc::new(AccessTokenRevocations::new()),
+            token_versions,
+            breached_passwords: None,
+            disposable_email_domains: None,
+            human_verifier,
+            request_rates,
         }
     }
+
+    /// Replaces the SMS sender.
+    ///
+    /// This is primarily used by tests to inject a mock sender.
+    ///
+    /// # Arguments
+    ///
+    /// - `sms_sender` - SMS sender implementation.
+    pub fn with_sms_sender(mut self, sms_sender: DynSmsSender) -> Self {
+        self.sms_sender = Some(sms_sender);
+        self
+    }
+
+    /// Replaces the human verifier.
+    ///
+    /// This is primarily used by tests to inject a stub verifier.
+    ///
+    /// # Arguments
+    ///
+    /// - `human_verifier` - Human verifier implementation.
+    pub fn with_human_verifier(mut self, human_verifier: DynHumanVerifier) -> Self {
+        self.human_verifier = human_verifier;
+        self
+    }
+
+    /// Replaces the access token denylist.
+    ///
+    /// Used at start-up to share a denylist that was already loaded and is
+    /// kept current by a notification listener.
+    ///
+    /// # Arguments
+    ///
+    /// - `access_token_revocations` - Access token denylist.
+    pub fn with_access_token_revocations(
+        mut self,
+        access_token_revocations: Arc<AccessTokenRevocations>,
+    ) -> Self {
+        self.access_token_revocations = access_token_revocations;
+        self
+    }
+
+    /// Sets the breached password corpus.
+    ///
+    /// Used at start-up once the index has been opened, and by tests.
+    ///
+    /// # Arguments
+    ///
+    /// - `breached_passwords` - Opened breached password index.
+    pub fn with_breached_passwords(
+        mut self,
+        breached_passwords: Option<Arc<BreachedPasswordIndex>>,
+    ) -> Self {
+        self.breached_passwords = breached_passwords;
+        self
+    }
+
+    /// Sets the disposable email domain list that replaces the bundled one.
+    ///
+    /// Used at start-up once the list has been read, and by tests.
+    ///
+    /// # Arguments
+    ///
+    /// - `disposable_email_domains` - Loaded disposable domain list.
+    pub fn with_disposable_email_domains(
+        mut self,
+        disposable_email_domains: Option<Arc<DisposableEmailDomains>>,
+    ) -> Self {
+        self.disposable_email_domains = disposable_email_domains;
+        self
+    }
+
+    /// Returns the password policy configured for this instance.
+    pub fn password_policy(&self) -> PasswordPolicy {
+        PasswordPolicy::from_env(&self.env).with_breached_passwords(self.breached_passwords.clone())
+    }
+
+    /// Returns the email domain policy configured for this instance.
+    pub fn email_domain_policy(&self) -> EmailDomainPolicy {
+        EmailDomainPolicy::from_env(&self.env)
+            .with_disposable_domains(self.disposable_email_domains.clone())
+    }
+
+    /// Returns the format and hashing key for one-time authentication codes.
+    pub fn auth_codes(&self) -> AuthCodeConfig {
+        AuthCodeConfig::from_env(&self.env)
+    }
+
+    /// Returns the Argon2 parameters new password hashes are made with.
+    pub fn password_hash_params(&self) -> PasswordHashParams {
+        PasswordHashParams::from_env(&self.env)
+    }
+
+    /// Returns the SMS sender used by phone-number flows.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::SmsServiceError`] when SMS delivery is not configured.
+    pub fn sms_sender(&self) -> ApiResult<&DynSmsSender> {
+        self.sms_sender
+            .as_ref()
+            .ok_or_else(|| ApiError::SmsServiceError("SMS delivery is not configured".to_string()))
+    }
+}
+
+/// Builds the configured human verifier and an empty request rate tracker.
+///
+/// Uses the HTTP verification service when `HUMAN_VERIFIER_URL` is set and the
+/// built-in proof of work otherwise, keyed with a key derived from the JWT
+/// secret rather than the JWT signing key itself.
+///
+/// # Arguments
+///
+/// - `env` - Runtime environment configuration.
+fn human_verification_from_env(env: &Env) -> (DynHumanVerifier, Arc<RequestRateTracker>) {
+    let human_verifier: DynHumanVerifier = match env.human_verifier_url.as_deref() {
+        Some(url) => Arc::new(HttpHumanVerifier::new(
+            url,
+            env.human_verifier_secret.as_deref().unwrap_or_default(),
+            env.human_verifier_site_key.as_deref(),
+        )),
+        None => Arc::new(ProofOfWorkVerifier::with_derived_key(
+            env.jwt_secret.as_bytes(),
+            env.proof_of_work_difficulty,
+        )),
+    };
+    let request_rates = Arc::new(RequestRateTracker::new(Duration::from_secs(
+        env.human_verification_window_seconds,
+    )));
+
+    (human_verifier, request_rates)
 }
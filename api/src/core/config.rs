@@ -4,11 +4,24 @@
 
 use actix_web::web::ServiceConfig;
 
+use crate::routes::api_keys::{create_api_key, list_api_keys, revoke_api_key};
 use crate::routes::auth::{
-    change_password, confirm_email, confirm_email_change, current_user, forgot_password, log_in,
-    log_out, refresh_session, request_email_change, set_password, sign_up, verify_forgot_password,
+    change_password, confirm_email, confirm_email_change, confirm_phone, current_user,
+    delete_current_user, forgot_password, forgot_password_by_phone, log_in, log_in_for_token,
+    log_in_with_phone_code, log_out, log_out_everywhere, reauthenticate, refresh_session,
+    refresh_session_for_token, request_email_change, request_phone_confirmation,
+    request_phone_log_in_code, set_password, sign_up, update_current_user, verify_forgot_password,
+    verify_forgot_password_by_phone, verify_session,
 };
+use crate::routes::dev::{list_mailbox_messages, show_mailbox_message, show_mailbox_message_html};
 use crate::routes::health::health_check;
+use crate::routes::oauth::{
+    approve_device, deny_device, introspect_token, issue_token, request_device_authorization,
+    revoke_token,
+};
+use crate::routes::service_accounts::{
+    create_service_account, current_service_account, list_service_accounts, revoke_service_account,
+};
 
 /// Registers all API routes with the Actix service configuration.
 ///
@@ -23,13 +36,46 @@ pub fn configure_routes(config: &mut ServiceConfig) {
         .service(sign_up)
         .service(confirm_email)
         .service(log_in)
+        .service(log_in_for_token)
         .service(log_out)
+        .service(log_out_everywhere)
+        .service(reauthenticate)
         .service(refresh_session)
+        .service(refresh_session_for_token)
+        .service(verify_session)
         .service(current_user)
+        .service(update_current_user)
+        .service(delete_current_user)
         .service(request_email_change)
         .service(confirm_email_change)
         .service(forgot_password)
         .service(verify_forgot_password)
         .service(set_password)
-        .service(change_password);
+        .service(change_password)
+        .service(request_phone_confirmation)
+        .service(confirm_phone)
+        .service(forgot_password_by_phone)
+        .service(verify_forgot_password_by_phone)
+        .service(request_phone_log_in_code)
+        .service(log_in_with_phone_code)
+        // API key routes
+        .service(create_api_key)
+        .service(list_api_keys)
+        .service(revoke_api_key)
+        // Service account routes
+        .service(create_service_account)
+        .service(list_service_accounts)
+        .service(current_service_account)
+        .service(revoke_service_account)
+        // OAuth2 routes
+        .service(issue_token)
+        .service(request_device_authorization)
+        .service(introspect_token)
+        .service(revoke_token)
+        .service(approve_device)
+        .service(deny_device)
+        // Development routes
+        .service(list_mailbox_messages)
+        .service(show_mailbox_message)
+        .service(show_mailbox_message_html);
 }
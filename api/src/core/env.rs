@@ -8,7 +8,14 @@ use std::env;
 use anyhow::Error;
 use dotenvy::dotenv;
 
+use crate::auth::codes::{
+    MAX_CODE_LENGTH, MIN_CODE_LENGTH, NUMERIC_ALPHABET, resolve_code_alphabet,
+};
+use crate::auth::email_domains::{EmailDomainPattern, parse_domain_patterns};
+use crate::auth::password::PasswordPeppers;
+use crate::auth::trusted_proxies::{ForwardedHeader, TrustedProxies};
 use crate::core::app::AppResult;
+use crate::services::human_verification::MAX_PROOF_OF_WORK_DIFFICULTY;
 
 /// Runtime configuration loaded from environment variables.
 #[derive(Debug, Clone)]
@@ -31,12 +38,112 @@ pub struct Env {
     pub jwt_access_token_expiry_seconds: u64,
     /// Refresh token lifetime in seconds.
     pub jwt_refresh_token_expiry_seconds: u64,
+    /// Lifetime in seconds of tokens issued to service accounts.
+    pub jwt_service_token_expiry_seconds: u64,
+    /// How long in seconds a user's token version is cached before being re-read.
+    ///
+    /// Bounds how long other instances keep accepting access tokens after a
+    /// password or email change.
+    pub token_version_cache_ttl_seconds: u64,
     /// Resend API key for transactional emails.
-    pub resend_api_key: String,
+    ///
+    /// Only optional in development, where captured emails are stored in the
+    /// in-memory dev mailbox instead of being delivered.
+    pub resend_api_key: Option<String>,
     /// Sender email used by the Resend integration.
     pub resend_from_email: String,
+    /// Optional directory whose email templates override the built-in templates.
+    pub email_templates_dir: Option<String>,
     /// Authentication code lifetime in seconds.
     pub auth_code_expiry_seconds: u64,
+    /// HMAC key for stored authentication code hashes; required and distinct
+    /// from the JWT secret outside development, where it defaults to it.
+    pub auth_code_secret: String,
+    /// Number of characters in authentication codes.
+    pub auth_code_length: usize,
+    /// Characters authentication codes are drawn from.
+    pub auth_code_alphabet: String,
+    /// Minimum duration of unauthenticated code-verification and code-request
+    /// responses, in milliseconds.
+    pub auth_code_min_response_ms: u64,
+    /// Minimum number of characters in a password.
+    pub password_min_length: usize,
+    /// Maximum number of characters in a password.
+    pub password_max_length: usize,
+    /// Minimum number of character classes (lowercase, uppercase, digits,
+    /// symbols) a password must mix.
+    pub password_min_character_classes: usize,
+    /// Minimum strength score (0-4) a password must reach.
+    pub password_min_strength_score: u8,
+    /// Longest run of one repeated character a password may contain.
+    pub password_max_repeated_characters: usize,
+    /// Number of recent passwords a user may not reuse; `0` disables the check.
+    pub password_history_size: usize,
+    /// Optional breached password index built by `build_breached_password_index`.
+    ///
+    /// When set, passwords found in it are rejected wherever a password is chosen.
+    pub breached_passwords_index_path: Option<String>,
+    /// Whether log-ins with a breached password flag the account for a forced reset.
+    pub breached_passwords_check_on_log_in: bool,
+    /// Email domains exempt from the other domain checks, or the only ones
+    /// accepted in allowlist-only mode.
+    pub email_domain_allowlist: Vec<EmailDomainPattern>,
+    /// Whether only allowlisted email domains may sign up or be switched to.
+    pub email_domain_allowlist_only: bool,
+    /// Email domains rejected at sign-up and email change.
+    pub email_domain_denylist: Vec<EmailDomainPattern>,
+    /// Whether disposable email domains are rejected at sign-up and email change.
+    pub block_disposable_email_domains: bool,
+    /// Optional disposable domain list replacing the bundled one.
+    pub disposable_email_domains_path: Option<String>,
+    /// Argon2 memory cost in KiB used when hashing passwords.
+    pub argon2_memory_kib: u32,
+    /// Argon2 number of passes over memory used when hashing passwords.
+    pub argon2_iterations: u32,
+    /// Argon2 degree of parallelism (lanes) used when hashing passwords.
+    pub argon2_parallelism: u32,
+    /// Versioned secrets mixed into passwords before hashing; empty when unset.
+    pub password_peppers: PasswordPeppers,
+    /// Service account client IDs trusted to introspect any token and revoke
+    /// tokens of any user.
+    ///
+    /// Other service accounts may only revoke their owner's tokens.
+    pub oauth_trusted_client_ids: Vec<String>,
+    /// Browser page where users enter device authorization user codes.
+    pub device_verification_uri: String,
+    /// Device authorization (device and user code) lifetime in seconds.
+    pub device_code_expiry_seconds: u64,
+    /// Minimum number of seconds a device must wait between token polls.
+    pub device_code_poll_interval_seconds: u64,
+    /// Log-in page that `/auth/verify?redirect=true` sends unauthenticated browsers to.
+    pub forward_auth_login_url: String,
+    /// HTTP endpoint of the SMS gateway used for phone codes.
+    ///
+    /// When unset, SMS codes are logged in development and phone flows are
+    /// unavailable elsewhere.
+    pub sms_gateway_url: Option<String>,
+    /// Optional bearer token sent to the SMS gateway.
+    pub sms_gateway_api_key: Option<String>,
+    /// Optional sender ID or number passed to the SMS gateway.
+    pub sms_from: Option<String>,
+    /// Requests per client address or email within the window before sign-up
+    /// and password reset require human verification; `0` disables it.
+    pub human_verification_threshold: usize,
+    /// Length of the window human verification thresholds are counted over, in seconds.
+    pub human_verification_window_seconds: u64,
+    /// Leading zero bits required by the built-in proof-of-work challenge.
+    pub proof_of_work_difficulty: u32,
+    /// `siteverify` endpoint of an hCaptcha/Turnstile-style service.
+    ///
+    /// When unset, the built-in proof-of-work challenge is used instead.
+    pub human_verifier_url: Option<String>,
+    /// Secret key sent to the human verification service.
+    pub human_verifier_secret: Option<String>,
+    /// Public site key clients render the CAPTCHA widget with.
+    pub human_verifier_site_key: Option<String>,
+    /// Reverse proxies, and the one forwarded header they append to, trusted
+    /// for the client address; empty means the peer address is always used.
+    pub trusted_proxies: TrustedProxies,
     /// Optional cookie domain used when setting auth cookies.
     pub cookie_domain: Option<String>,
     /// Whether auth cookies are marked as `Secure`.
@@ -55,17 +162,24 @@ impl Env {
     /// Required variables:
     /// - `DATABASE_URL`
     /// - `JWT_SECRET`
-    /// - `RESEND_API_KEY`
+    /// - `RESEND_API_KEY` (outside development)
     /// - `RESEND_FROM_EMAIL`
     ///
     /// Optional variables fall back to defaults when unset or empty.
     ///
     /// Notably, `APP_ENV` defaults to `production` when not set.
     ///
+    /// `RESEND_API_KEY` may be left unset in development, in which case emails
+    /// are captured by the dev mailbox.
+    ///
     /// `DOCKER_COMPOSE_AUTO_START_ENABLED` defaults to `false`.
     ///
     /// `AUTO_APPLY_MIGRATIONS_ENABLED` defaults to `false`.
     ///
+    /// `DEVICE_VERIFICATION_URI` defaults to `/device` on `CORS_ALLOWED_ORIGIN`.
+    ///
+    /// `FORWARD_AUTH_LOGIN_URL` defaults to `/auth/log-in` on `CORS_ALLOWED_ORIGIN`.
+    ///
     /// # Errors
     ///
     /// Returns an error if a required variable is missing or if a numeric
@@ -117,9 +231,26 @@ impl Env {
                 None => 604800, // 7 days
             };
 
+        let jwt_service_token_expiry_seconds =
+            match Self::get_optional_var("JWT_SERVICE_TOKEN_EXPIRY_SECONDS") {
+                Some(val) => val.trim().parse::<u64>()?,
+                None => 300, // 5 minutes
+            };
+
+        let token_version_cache_ttl_seconds =
+            match Self::get_optional_var("TOKEN_VERSION_CACHE_TTL_SECONDS") {
+                Some(val) => val.trim().parse::<u64>()?,
+                None => 5,
+            };
+
         // Resend Email Service
-        let resend_api_key = Self::get_required_var("RESEND_API_KEY")?;
+        let resend_api_key = if Self::is_development_env(&app_env) {
+            Self::get_optional_var("RESEND_API_KEY")
+        } else {
+            Some(Self::get_required_var("RESEND_API_KEY")?)
+        };
         let resend_from_email = Self::get_required_var("RESEND_FROM_EMAIL")?;
+        let email_templates_dir = Self::get_optional_var("EMAIL_TEMPLATES_DIR");
 
         // Auth Codes
         let auth_code_expiry_seconds = match Self::get_optional_var("AUTH_CODE_EXPIRY_SECONDS") {
@@ -127,6 +258,226 @@ impl Env {
             None => 600, // 10 minutes
         };
 
+        // Outside development, code hashes must not share key material with JWTs.
+        let auth_code_secret = match Self::get_optional_var("AUTH_CODE_SECRET") {
+            Some(secret) => secret,
+            None if Self::is_development_env(&app_env) => jwt_secret.clone(),
+            None => Self::get_required_var("AUTH_CODE_SECRET")?,
+        };
+        if auth_code_secret == jwt_secret && !Self::is_development_env(&app_env) {
+            return Err(Error::msg(
+                "AUTH_CODE_SECRET must differ from JWT_SECRET outside development",
+            ));
+        }
+
+        let auth_code_length = match Self::get_optional_var("AUTH_CODE_LENGTH") {
+            Some(val) => val.trim().parse::<usize>()?,
+            None => 6,
+        };
+        if !(MIN_CODE_LENGTH..=MAX_CODE_LENGTH).contains(&auth_code_length) {
+            return Err(Error::msg(format!(
+                "AUTH_CODE_LENGTH must be between {} and {}",
+                MIN_CODE_LENGTH, MAX_CODE_LENGTH
+            )));
+        }
+
+        let auth_code_alphabet = match Self::get_optional_var("AUTH_CODE_ALPHABET") {
+            Some(val) => resolve_code_alphabet(&val).map_err(Error::msg)?,
+            None => NUMERIC_ALPHABET.to_string(),
+        };
+
+        let auth_code_min_response_ms = match Self::get_optional_var("AUTH_CODE_MIN_RESPONSE_MS") {
+            Some(val) => val.trim().parse::<u64>()?,
+            None => 250,
+        };
+
+        // Password Policy
+        let password_min_length = match Self::get_optional_var("PASSWORD_MIN_LENGTH") {
+            Some(val) => val.trim().parse::<usize>()?,
+            None => 8,
+        };
+
+        let password_max_length = match Self::get_optional_var("PASSWORD_MAX_LENGTH") {
+            Some(val) => val.trim().parse::<usize>()?,
+            None => 128,
+        };
+
+        let password_min_character_classes =
+            match Self::get_optional_var("PASSWORD_MIN_CHARACTER_CLASSES") {
+                Some(val) => val.trim().parse::<usize>()?,
+                None => 2,
+            };
+
+        let password_min_strength_score =
+            match Self::get_optional_var("PASSWORD_MIN_STRENGTH_SCORE") {
+                Some(val) => val.trim().parse::<u8>()?,
+                None => 2,
+            };
+
+        let password_max_repeated_characters =
+            match Self::get_optional_var("PASSWORD_MAX_REPEATED_CHARACTERS") {
+                Some(val) => val.trim().parse::<usize>()?,
+                None => 3,
+            };
+
+        let password_history_size = match Self::get_optional_var("PASSWORD_HISTORY_SIZE") {
+            Some(val) => val.trim().parse::<usize>()?,
+            None => 5,
+        };
+
+        // Breached Passwords
+        let breached_passwords_index_path = Self::get_optional_var("BREACHED_PASSWORDS_INDEX_PATH");
+
+        let breached_passwords_check_on_log_in =
+            match Self::get_optional_var("BREACHED_PASSWORDS_CHECK_ON_LOG_IN") {
+                Some(val) => val.trim().to_lowercase() == "true",
+                None => false,
+            };
+
+        // Email Domains
+        let email_domain_allowlist = match Self::get_optional_var("EMAIL_DOMAIN_ALLOWLIST") {
+            Some(val) => parse_domain_patterns(&val).map_err(Error::msg)?,
+            None => Vec::new(),
+        };
+
+        let email_domain_allowlist_only =
+            match Self::get_optional_var("EMAIL_DOMAIN_ALLOWLIST_ONLY") {
+                Some(val) => val.trim().to_lowercase() == "true",
+                None => false,
+            };
+        if email_domain_allowlist_only && email_domain_allowlist.is_empty() {
+            return Err(Error::msg(
+                "EMAIL_DOMAIN_ALLOWLIST is required when EMAIL_DOMAIN_ALLOWLIST_ONLY is true",
+            ));
+        }
+
+        let email_domain_denylist = match Self::get_optional_var("EMAIL_DOMAIN_DENYLIST") {
+            Some(val) => parse_domain_patterns(&val).map_err(Error::msg)?,
+            None => Vec::new(),
+        };
+
+        let block_disposable_email_domains =
+            match Self::get_optional_var("BLOCK_DISPOSABLE_EMAIL_DOMAINS") {
+                Some(val) => val.trim().to_lowercase() == "true",
+                None => false,
+            };
+
+        let disposable_email_domains_path = Self::get_optional_var("DISPOSABLE_EMAIL_DOMAINS_PATH");
+
+        // Password Hashing
+        let argon2_memory_kib = match Self::get_optional_var("ARGON2_MEMORY_KIB") {
+            Some(val) => val.trim().parse::<u32>()?,
+            None => argon2::Params::DEFAULT_M_COST,
+        };
+
+        let argon2_iterations = match Self::get_optional_var("ARGON2_ITERATIONS") {
+            Some(val) => val.trim().parse::<u32>()?,
+            None => argon2::Params::DEFAULT_T_COST,
+        };
+
+        let argon2_parallelism = match Self::get_optional_var("ARGON2_PARALLELISM") {
+            Some(val) => val.trim().parse::<u32>()?,
+            None => argon2::Params::DEFAULT_P_COST,
+        };
+
+        argon2::Params::new(
+            argon2_memory_kib,
+            argon2_iterations,
+            argon2_parallelism,
+            None,
+        )
+        .map_err(|error| Error::msg(format!("Invalid Argon2 parameters: {}", error)))?;
+
+        let password_peppers = match Self::get_optional_var("PASSWORD_PEPPERS") {
+            Some(val) => val.parse::<PasswordPeppers>().map_err(Error::msg)?,
+            None => PasswordPeppers::default(),
+        };
+
+        // OAuth Clients
+        let oauth_trusted_client_ids = match Self::get_optional_var("OAUTH_TRUSTED_CLIENT_IDS") {
+            Some(val) => val
+                .split(',')
+                .map(str::trim)
+                .filter(|client_id| !client_id.is_empty())
+                .map(str::to_string)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        // Device Authorization Grant
+        let device_verification_uri = match Self::get_optional_var("DEVICE_VERIFICATION_URI") {
+            Some(uri) => uri,
+            None => format!("{}/device", cors_allowed_origin.trim_end_matches('/')),
+        };
+
+        let device_code_expiry_seconds = match Self::get_optional_var("DEVICE_CODE_EXPIRY_SECONDS")
+        {
+            Some(val) => val.trim().parse::<u64>()?,
+            None => 900, // 15 minutes
+        };
+
+        let device_code_poll_interval_seconds =
+            match Self::get_optional_var("DEVICE_CODE_POLL_INTERVAL_SECONDS") {
+                Some(val) => val.trim().parse::<u64>()?,
+                None => 5,
+            };
+
+        // Forward Auth
+        let forward_auth_login_url = match Self::get_optional_var("FORWARD_AUTH_LOGIN_URL") {
+            Some(url) => url,
+            None => format!("{}/auth/log-in", cors_allowed_origin.trim_end_matches('/')),
+        };
+
+        // SMS Gateway
+        let sms_gateway_url = Self::get_optional_var("SMS_GATEWAY_URL");
+        let sms_gateway_api_key = Self::get_optional_var("SMS_GATEWAY_API_KEY");
+        let sms_from = Self::get_optional_var("SMS_FROM");
+
+        // Human Verification
+        let human_verification_threshold =
+            match Self::get_optional_var("HUMAN_VERIFICATION_THRESHOLD") {
+                Some(val) => val.trim().parse::<usize>()?,
+                None => 5,
+            };
+
+        let human_verification_window_seconds =
+            match Self::get_optional_var("HUMAN_VERIFICATION_WINDOW_SECONDS") {
+                Some(val) => val.trim().parse::<u64>()?,
+                None => 3600, // 1 hour
+            };
+
+        let proof_of_work_difficulty = match Self::get_optional_var("PROOF_OF_WORK_DIFFICULTY") {
+            Some(val) => val.trim().parse::<u32>()?,
+            None => 20,
+        };
+        if !(1..=MAX_PROOF_OF_WORK_DIFFICULTY).contains(&proof_of_work_difficulty) {
+            return Err(Error::msg(format!(
+                "PROOF_OF_WORK_DIFFICULTY must be between 1 and {}",
+                MAX_PROOF_OF_WORK_DIFFICULTY
+            )));
+        }
+
+        let human_verifier_url = Self::get_optional_var("HUMAN_VERIFIER_URL");
+        let human_verifier_secret = Self::get_optional_var("HUMAN_VERIFIER_SECRET");
+        let human_verifier_site_key = Self::get_optional_var("HUMAN_VERIFIER_SITE_KEY");
+        if human_verifier_url.is_some() && human_verifier_secret.is_none() {
+            return Err(Error::msg(
+                "HUMAN_VERIFIER_SECRET is required when HUMAN_VERIFIER_URL is set",
+            ));
+        }
+
+        // Trusted Proxies
+        let trusted_proxy_header = match Self::get_optional_var("TRUSTED_PROXY_HEADER") {
+            Some(val) => val.parse::<ForwardedHeader>().map_err(Error::msg)?,
+            None => ForwardedHeader::default(),
+        };
+
+        let trusted_proxies = match Self::get_optional_var("TRUSTED_PROXIES") {
+            Some(val) => val.parse::<TrustedProxies>().map_err(Error::msg)?,
+            None => TrustedProxies::default(),
+        }
+        .with_header(trusted_proxy_header);
+
         // Cookie Configuration
         let cookie_domain = Self::get_optional_var("COOKIE_DOMAIN");
 
@@ -161,9 +512,48 @@ impl Env {
             jwt_secret,
             jwt_access_token_expiry_seconds,
             jwt_refresh_token_expiry_seconds,
+            jwt_service_token_expiry_seconds,
+            token_version_cache_ttl_seconds,
             resend_api_key,
             resend_from_email,
+            email_templates_dir,
             auth_code_expiry_seconds,
+            auth_code_secret,
+            auth_code_length,
+            auth_code_alphabet,
+            auth_code_min_response_ms,
+            password_min_length,
+            password_max_length,
+            password_min_character_classes,
+            password_min_strength_score,
+            password_max_repeated_characters,
+            password_history_size,
+            breached_passwords_index_path,
+            breached_passwords_check_on_log_in,
+            email_domain_allowlist,
+            email_domain_allowlist_only,
+            email_domain_denylist,
+            block_disposable_email_domains,
+            disposable_email_domains_path,
+            argon2_memory_kib,
+            argon2_iterations,
+            argon2_parallelism,
+            password_peppers,
+            oauth_trusted_client_ids,
+            device_verification_uri,
+            device_code_expiry_seconds,
+            device_code_poll_interval_seconds,
+            forward_auth_login_url,
+            sms_gateway_url,
+            sms_gateway_api_key,
+            sms_from,
+            human_verification_threshold,
+            human_verification_window_seconds,
+            proof_of_work_difficulty,
+            human_verifier_url,
+            human_verifier_secret,
+            human_verifier_site_key,
+            trusted_proxies,
             cookie_domain,
             cookie_secure,
             log_level,
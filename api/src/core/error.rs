@@ -6,6 +6,10 @@
 use actix_web::{HttpResponse, ResponseError, http::StatusCode};
 use serde_json::json;
 use std::fmt;
+use validator::ValidationErrors;
+
+use crate::extractors::ValidationErrorResponse;
+use crate::services::human_verification::HumanChallenge;
 
 /// Standard result type returned by HTTP handlers.
 pub type ApiResult<T> = Result<T, ApiError>;
@@ -15,10 +19,10 @@ pub type ApiResult<T> = Result<T, ApiError>;
 pub enum ApiError {
     /// Email/password combination is invalid.
     InvalidCredentials,
-    /// User attempted auth flow before confirming email.
-    EmailNotConfirmed,
     /// Registration attempted with an email that already exists.
     EmailAlreadyExists,
+    /// Phone confirmation attempted with a number owned by another account.
+    PhoneNumberAlreadyExists,
     /// Provided auth/confirmation code is invalid.
     InvalidAuthCode,
     /// Provided auth/confirmation code has expired.
@@ -29,11 +33,25 @@ pub enum ApiError {
     TokenInvalid,
     /// Request requires authentication and no valid session/token was provided.
     Unauthorized,
+    /// Credential is valid but was not granted the scope the route requires.
+    InsufficientScope(String),
+    /// Cookie-authenticated request failed the CSRF token or origin check.
+    CsrfValidationFailed,
+    /// Sensitive action requires the user to have re-authenticated recently.
+    ReauthenticationRequired,
+    /// Request rate crossed the abuse threshold and the client must complete
+    /// the challenge (again, if its answer was rejected).
+    HumanVerificationRequired(HumanChallenge),
     /// A requested resource was not found.
     NotFound(String),
+    /// Conditional request headers did not match the resource's current version.
+    PreconditionFailed,
 
     /// Request payload failed validation with a custom message.
     ValidationError(String),
+    /// Request payload failed field-level validation that depends on runtime
+    /// state, such as the configured password policy.
+    InvalidFields(ValidationErrorResponse),
     /// Password and password confirmation values did not match.
     PasswordMismatch,
 
@@ -41,6 +59,10 @@ pub enum ApiError {
     DatabaseError(String),
     /// Upstream email provider operation failed.
     EmailServiceError(String),
+    /// Upstream SMS gateway operation failed or SMS delivery is not configured.
+    SmsServiceError(String),
+    /// Upstream human verification service could not check an answer.
+    HumanVerificationServiceError(String),
     /// Unclassified internal application error.
     InternalError(String),
 }
@@ -49,18 +71,41 @@ impl fmt::Display for ApiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ApiError::InvalidCredentials => write!(f, "Invalid email or password"),
-            ApiError::EmailNotConfirmed => write!(f, "Please confirm your email address"),
             ApiError::EmailAlreadyExists => write!(f, "An account with this email already exists"),
+            ApiError::PhoneNumberAlreadyExists => {
+                write!(f, "An account with this phone number already exists")
+            }
             ApiError::InvalidAuthCode => write!(f, "Invalid authentication code"),
             ApiError::AuthCodeExpired => write!(f, "Authentication code has expired"),
             ApiError::TokenExpired => write!(f, "Token has expired"),
             ApiError::TokenInvalid => write!(f, "Invalid token"),
             ApiError::Unauthorized => write!(f, "Unauthorized"),
+            ApiError::InsufficientScope(scope) => {
+                write!(f, "Credential is missing the required scope: {}", scope)
+            }
+            ApiError::CsrfValidationFailed => write!(f, "CSRF validation failed"),
+            ApiError::ReauthenticationRequired => {
+                write!(f, "Please confirm your identity to continue")
+            }
+            ApiError::HumanVerificationRequired(_) => {
+                write!(f, "Please complete the verification challenge to continue")
+            }
             ApiError::NotFound(msg) => write!(f, "{}", msg),
+            ApiError::PreconditionFailed => {
+                write!(
+                    f,
+                    "The resource has been modified since it was last fetched"
+                )
+            }
             ApiError::ValidationError(msg) => write!(f, "{}", msg),
+            ApiError::InvalidFields(_) => write!(f, "Validation failed"),
             ApiError::PasswordMismatch => write!(f, "Passwords do not match"),
             ApiError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
             ApiError::EmailServiceError(msg) => write!(f, "Email service error: {}", msg),
+            ApiError::SmsServiceError(msg) => write!(f, "SMS service error: {}", msg),
+            ApiError::HumanVerificationServiceError(msg) => {
+                write!(f, "Human verification service error: {}", msg)
+            }
             ApiError::InternalError(msg) => write!(f, "Internal error: {}", msg),
         }
     }
@@ -70,40 +115,71 @@ impl ResponseError for ApiError {
     fn status_code(&self) -> StatusCode {
         match self {
             ApiError::InvalidCredentials => StatusCode::UNAUTHORIZED,
-            ApiError::EmailNotConfirmed => StatusCode::FORBIDDEN,
             ApiError::EmailAlreadyExists => StatusCode::CONFLICT,
+            ApiError::PhoneNumberAlreadyExists => StatusCode::CONFLICT,
             ApiError::InvalidAuthCode => StatusCode::BAD_REQUEST,
             ApiError::AuthCodeExpired => StatusCode::BAD_REQUEST,
             ApiError::TokenExpired => StatusCode::UNAUTHORIZED,
             ApiError::TokenInvalid => StatusCode::UNAUTHORIZED,
             ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::InsufficientScope(_) => StatusCode::FORBIDDEN,
+            ApiError::CsrfValidationFailed => StatusCode::FORBIDDEN,
+            ApiError::ReauthenticationRequired => StatusCode::FORBIDDEN,
+            ApiError::HumanVerificationRequired(_) => StatusCode::FORBIDDEN,
             ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
             ApiError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            ApiError::InvalidFields(_) => StatusCode::BAD_REQUEST,
             ApiError::PasswordMismatch => StatusCode::BAD_REQUEST,
             ApiError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ApiError::EmailServiceError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::SmsServiceError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::HumanVerificationServiceError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ApiError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
+        // Field-level failures use the same body as `ValidatedJson` rejections.
+        if let ApiError::InvalidFields(errors) = self {
+            return HttpResponse::build(self.status_code()).json(errors);
+        }
+
         let error_code = match self {
             ApiError::InvalidCredentials => "INVALID_CREDENTIALS",
-            ApiError::EmailNotConfirmed => "EMAIL_NOT_CONFIRMED",
             ApiError::EmailAlreadyExists => "EMAIL_ALREADY_EXISTS",
+            ApiError::PhoneNumberAlreadyExists => "PHONE_NUMBER_ALREADY_EXISTS",
             ApiError::InvalidAuthCode => "INVALID_AUTH_CODE",
             ApiError::AuthCodeExpired => "AUTH_CODE_EXPIRED",
             ApiError::TokenExpired => "TOKEN_EXPIRED",
             ApiError::TokenInvalid => "TOKEN_INVALID",
             ApiError::Unauthorized => "UNAUTHORIZED",
+            ApiError::InsufficientScope(_) => "INSUFFICIENT_SCOPE",
+            ApiError::CsrfValidationFailed => "CSRF_VALIDATION_FAILED",
+            ApiError::ReauthenticationRequired => "REAUTHENTICATION_REQUIRED",
+            ApiError::HumanVerificationRequired(_) => "HUMAN_VERIFICATION_REQUIRED",
             ApiError::NotFound(_) => "NOT_FOUND",
-            ApiError::ValidationError(_) => "VALIDATION_ERROR",
+            ApiError::PreconditionFailed => "PRECONDITION_FAILED",
+            ApiError::ValidationError(_) | ApiError::InvalidFields(_) => "VALIDATION_ERROR",
             ApiError::PasswordMismatch => "PASSWORD_MISMATCH",
             ApiError::DatabaseError(_) => "DATABASE_ERROR",
             ApiError::EmailServiceError(_) => "EMAIL_SERVICE_ERROR",
+            ApiError::SmsServiceError(_) => "SMS_SERVICE_ERROR",
+            ApiError::HumanVerificationServiceError(_) => "HUMAN_VERIFICATION_SERVICE_ERROR",
             ApiError::InternalError(_) => "INTERNAL_ERROR",
         };
 
+        // Verification failures carry the challenge the client must complete.
+        if let ApiError::HumanVerificationRequired(challenge) = self {
+            return HttpResponse::build(self.status_code()).json(json!({
+                "error": {
+                    "code": error_code,
+                    "message": self.to_string(),
+                    "challenge": challenge
+                }
+            }));
+        }
+
         HttpResponse::build(self.status_code()).json(json!({
             "error": {
                 "code": error_code,
@@ -113,6 +189,12 @@ impl ResponseError for ApiError {
     }
 }
 
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        ApiError::InvalidFields(errors.into())
+    }
+}
+
 impl From<sqlx::Error> for ApiError {
     fn from(err: sqlx::Error) -> Self {
         ApiError::DatabaseError(err.to_string())
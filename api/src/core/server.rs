@@ -1,12 +1,18 @@
 //! Actix HTTP server setup and execution.
 //!
-//! This module configures the database pool, CORS middleware, shared app data,
-//! and route registration for the API server.
+//! This module configures the database pool, CORS and CSRF middleware, shared
+//! app data, and route registration for the API server.
+
+use std::sync::Arc;
 
 use actix_cors::Cors;
 use actix_web::{App, HttpServer, middleware::from_fn, web};
 use sqlx::{Pool, Postgres, postgres::PgPoolOptions};
 
+use crate::auth::{
+    breached_passwords::BreachedPasswordIndex, csrf::csrf_protection,
+    email_domains::DisposableEmailDomains, revocation::AccessTokenRevocations,
+};
 use crate::core::{
     app::AppResult,
     app_state::AppState,
@@ -19,13 +25,18 @@ use crate::core::{
 pub struct Server {
     pool: Pool<Postgres>,
     env: Env,
+    access_token_revocations: Arc<AccessTokenRevocations>,
+    breached_passwords: Option<Arc<BreachedPasswordIndex>>,
+    disposable_email_domains: Option<Arc<DisposableEmailDomains>>,
 }
 
 impl Server {
     /// Creates a new server instance and initializes the PostgreSQL pool.
     ///
     /// During startup, this can check for pending database migrations and apply
-    /// them before the HTTP server begins accepting requests.
+    /// them before the HTTP server begins accepting requests. The access token
+    /// denylist and, when configured, the breached password index and
+    /// disposable email domain list are loaded before any request is served.
     ///
     /// # Arguments
     ///
@@ -34,7 +45,8 @@ impl Server {
     /// # Errors
     ///
     /// Returns an error if the database pool cannot connect or, when enabled,
-    /// if database migrations fail to run.
+    /// if database migrations fail to run, or if the access token denylist,
+    /// breached password index or disposable email domain list cannot be loaded.
     pub async fn new(env: Env) -> AppResult<Server> {
         Logger::log_message("Connecting to database");
 
@@ -51,11 +63,46 @@ impl Server {
             Logger::log_success("Database migrations are up to date");
         }
 
-        Ok(Server { pool, env })
+        let access_token_revocations = Arc::new(AccessTokenRevocations::new());
+        access_token_revocations.load(&pool).await?;
+
+        let breached_passwords = match env.breached_passwords_index_path.as_deref() {
+            Some(path) => {
+                let index = BreachedPasswordIndex::open(path)?;
+                Logger::log_success(&format!(
+                    "Loaded breached password index with {} hashes",
+                    index.len()
+                ));
+                Some(Arc::new(index))
+            }
+            None => None,
+        };
+
+        let disposable_email_domains = match env.disposable_email_domains_path.as_deref() {
+            Some(path) => {
+                let domains = DisposableEmailDomains::open(path)?;
+                Logger::log_success(&format!(
+                    "Loaded disposable email domain list with {} domains",
+                    domains.len()
+                ));
+                Some(Arc::new(domains))
+            }
+            None => None,
+        };
+
+        Ok(Server {
+            pool,
+            env,
+            access_token_revocations,
+            breached_passwords,
+            disposable_email_domains,
+        })
     }
 
     /// Starts the Actix HTTP server and blocks until shutdown.
     ///
+    /// Also starts listening for access token revocations from other instances.
+    ///
     /// # Errors
     ///
     /// Returns an I/O error if the server cannot bind or the runtime
@@ -64,7 +111,13 @@ impl Server {
         Logger::log_success(&format!("Server running on port {}", self.env.port));
 
         let env = self.env.clone();
-        let app_state = AppState::new(self.pool.clone(), env.clone());
+        self.access_token_revocations
+            .clone()
+            .listen(self.pool.clone());
+        let app_state = AppState::new(self.pool.clone(), env.clone())
+            .with_access_token_revocations(self.access_token_revocations.clone())
+            .with_breached_passwords(self.breached_passwords.clone())
+            .with_disposable_email_domains(self.disposable_email_domains.clone());
         let http_logging_config = HttpLoggingConfig {
             body_enabled: env.log_http_body_enabled,
             max_body_bytes: env.log_http_max_body_bytes,
@@ -73,13 +126,22 @@ impl Server {
         HttpServer::new(move || {
             let cors = Cors::default()
                 .allowed_origin(&env.cors_allowed_origin)
-                .allowed_methods(vec!["GET", "PUT", "POST", "DELETE", "OPTIONS"])
-                .allowed_headers(vec!["Content-Type", "Authorization"])
+                .allowed_methods(vec!["GET", "PUT", "PATCH", "POST", "DELETE", "OPTIONS"])
+                .allowed_headers(vec![
+                    "Content-Type",
+                    "Authorization",
+                    "X-API-Key",
+                    "X-CSRF-Token",
+                    "If-Match",
+                    "If-Unmodified-Since",
+                ])
+                .expose_headers(vec!["ETag", "Last-Modified"])
                 .supports_credentials();
 
             App::new()
                 .app_data(web::Data::new(app_state.clone()))
                 .app_data(web::Data::new(http_logging_config.clone()))
+                .wrap(from_fn(csrf_protection))
                 .wrap(cors)
                 .wrap(from_fn(Logger::log_request_and_response))
                 .configure(configure_routes)
@@ -7,8 +7,17 @@
 //!
 //! - [`ValidatedJson`] - JSON body extractor that validates payloads with the
 //!   `validator` crate and returns a standardized `400 Bad Request` response.
+//! - [`ValidationErrorResponse`] - The `{"errors": [...]}` body of that
+//!   response, also returned by handlers that validate against runtime state.
+//! - [`Preconditions`] - `If-Match`/`If-Unmodified-Since` headers used for
+//!   optimistic concurrency, plus helpers to emit `ETag`/`Last-Modified`.
 
+mod preconditions;
 mod validated_json;
 
-/// JSON body extractor that deserializes and validates request payloads.
-pub use validated_json::ValidatedJson;
+/// Conditional request headers and version header helpers.
+pub use preconditions::{Preconditions, etag_header, last_modified_header};
+
+/// JSON body extractor that deserializes and validates request payloads, and
+/// the field-level error body it rejects invalid payloads with.
+pub use validated_json::{FieldError, ValidatedJson, ValidationErrorResponse};
@@ -0,0 +1,196 @@
+//! Conditional request headers for optimistic concurrency.
+//!
+//! Resources versioned by an `updated_at` timestamp expose it to clients as an
+//! `ETag` (microsecond precision) and a `Last-Modified` date (second
+//! precision). Clients send the value back in `If-Match` or
+//! `If-Unmodified-Since` so an update is rejected with
+//! `412 Precondition Failed` when someone else changed the resource first.
+
+use std::time::SystemTime;
+
+use actix_web::http::header::{
+    ETag, EntityTag, Header, HttpDate, IfMatch, IfUnmodifiedSince, LastModified,
+};
+use actix_web::{FromRequest, HttpRequest, dev::Payload};
+use chrono::{DateTime, Utc};
+use futures::future::{Ready, ok};
+
+/// Conditions a write must satisfy, parsed from request headers.
+///
+/// Following RFC 9110, `If-Unmodified-Since` is ignored when `If-Match` is
+/// present, and malformed dates are ignored.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Preconditions {
+    /// Versions accepted by `If-Match`, or `None` when any version is accepted.
+    ///
+    /// An empty list means none of the supplied entity tags can match.
+    pub if_match: Option<Vec<DateTime<Utc>>>,
+    /// Latest accepted modification time from `If-Unmodified-Since`.
+    pub if_unmodified_since: Option<DateTime<Utc>>,
+}
+
+impl Preconditions {
+    /// Returns `true` when the request carries no preconditions.
+    pub fn is_empty(&self) -> bool {
+        self.if_match.is_none() && self.if_unmodified_since.is_none()
+    }
+
+    /// Returns `true` when a resource last modified at `updated_at` satisfies the preconditions.
+    ///
+    /// # Arguments
+    ///
+    /// - `updated_at` - Current version of the resource
+    pub fn matches(&self, updated_at: DateTime<Utc>) -> bool {
+        if let Some(versions) = &self.if_match {
+            return versions.contains(&updated_at);
+        }
+
+        match self.if_unmodified_since {
+            Some(since) => truncate_to_seconds(updated_at) <= since,
+            None => true,
+        }
+    }
+}
+
+impl FromRequest for Preconditions {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    /// Parses `If-Match` and `If-Unmodified-Since`; never rejects the request.
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        // A missing `If-Match` header parses as an empty item list.
+        let if_match = match IfMatch::parse(req) {
+            Ok(IfMatch::Items(tags)) if !tags.is_empty() => {
+                Some(tags.iter().filter_map(version_from_tag).collect())
+            }
+            _ => None,
+        };
+
+        let if_unmodified_since = if if_match.is_some() {
+            None
+        } else {
+            IfUnmodifiedSince::parse(req)
+                .ok()
+                .map(|header| DateTime::<Utc>::from(SystemTime::from(header.0)))
+        };
+
+        ok(Preconditions {
+            if_match,
+            if_unmodified_since,
+        })
+    }
+}
+
+/// Builds the `ETag` header for a resource version.
+///
+/// # Arguments
+///
+/// - `updated_at` - Current version of the resource
+pub fn etag_header(updated_at: DateTime<Utc>) -> ETag {
+    ETag(EntityTag::new_strong(
+        updated_at.timestamp_micros().to_string(),
+    ))
+}
+
+/// Builds the `Last-Modified` header for a resource version.
+///
+/// # Arguments
+///
+/// - `updated_at` - Current version of the resource
+pub fn last_modified_header(updated_at: DateTime<Utc>) -> LastModified {
+    LastModified(HttpDate::from(SystemTime::from(updated_at)))
+}
+
+/// Decodes a version produced by [`etag_header`], ignoring foreign or weak tags.
+///
+/// # Arguments
+///
+/// - `tag` - Entity tag supplied by the client
+fn version_from_tag(tag: &EntityTag) -> Option<DateTime<Utc>> {
+    if tag.weak {
+        return None;
+    }
+
+    tag.tag()
+        .parse::<i64>()
+        .ok()
+        .and_then(DateTime::from_timestamp_micros)
+}
+
+/// Drops sub-second precision so timestamps compare like HTTP dates.
+///
+/// # Arguments
+///
+/// - `timestamp` - Timestamp to truncate
+fn truncate_to_seconds(timestamp: DateTime<Utc>) -> DateTime<Utc> {
+    DateTime::from_timestamp(timestamp.timestamp(), 0).unwrap_or(timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::{self, TryIntoHeaderValue};
+    use actix_web::test::TestRequest;
+    use chrono::{DateTime, Duration, Utc};
+
+    use super::{Preconditions, etag_header, last_modified_header};
+
+    fn parse(request: TestRequest) -> Preconditions {
+        let (req, mut payload) = request.to_http_parts();
+        futures::executor::block_on(<Preconditions as actix_web::FromRequest>::from_request(
+            &req,
+            &mut payload,
+        ))
+        .expect("preconditions never reject")
+    }
+
+    fn updated_at() -> DateTime<Utc> {
+        DateTime::from_timestamp_micros(1_760_781_600_123_456).expect("valid timestamp")
+    }
+
+    #[test]
+    // Verifies an ETag produced for a version matches only that exact version.
+    fn if_match_accepts_only_the_tagged_version() {
+        let etag = etag_header(updated_at())
+            .try_into_value()
+            .expect("etag header value");
+        let preconditions = parse(TestRequest::default().insert_header((header::IF_MATCH, etag)));
+
+        assert!(preconditions.matches(updated_at()));
+        assert!(!preconditions.matches(updated_at() + Duration::microseconds(1)));
+    }
+
+    #[test]
+    // Verifies unknown tags never match while `*` and missing headers accept any version.
+    fn if_match_handles_foreign_tags_and_wildcards() {
+        let foreign = parse(TestRequest::default().insert_header((header::IF_MATCH, "\"abc\"")));
+        assert!(!foreign.matches(updated_at()));
+
+        let any = parse(TestRequest::default().insert_header((header::IF_MATCH, "*")));
+        assert!(any.is_empty());
+        assert!(any.matches(updated_at()));
+
+        assert!(parse(TestRequest::default()).is_empty());
+    }
+
+    #[test]
+    // Verifies If-Unmodified-Since compares at second precision and yields to If-Match.
+    fn if_unmodified_since_compares_whole_seconds() {
+        let last_modified = last_modified_header(updated_at())
+            .try_into_value()
+            .expect("last-modified header value");
+        let preconditions = parse(
+            TestRequest::default()
+                .insert_header((header::IF_UNMODIFIED_SINCE, last_modified.clone())),
+        );
+
+        assert!(preconditions.matches(updated_at()));
+        assert!(!preconditions.matches(updated_at() + Duration::seconds(1)));
+
+        let both = parse(
+            TestRequest::default()
+                .insert_header((header::IF_UNMODIFIED_SINCE, last_modified))
+                .insert_header((header::IF_MATCH, "\"abc\"")),
+        );
+        assert_eq!(both.if_unmodified_since, None);
+    }
+}
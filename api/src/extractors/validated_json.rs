@@ -116,6 +116,12 @@ fn convert_validation_errors(errors: ValidationErrors) -> ValidationErrorRespons
     }
 }
 
+impl From<ValidationErrors> for ValidationErrorResponse {
+    fn from(errors: ValidationErrors) -> Self {
+        convert_validation_errors(errors)
+    }
+}
+
 impl<T> FromRequest for ValidatedJson<T>
 where
     T: DeserializeOwned + Validate + 'static,
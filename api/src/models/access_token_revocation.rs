@@ -0,0 +1,35 @@
+//! Access token revocation models for the access token denylist.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A single access token revoked before its expiry, identified by its `jti`.
+///
+/// Rows are only needed until the token would have expired anyway.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct RevokedAccessToken {
+    /// Revoked token identifier.
+    pub jti: String,
+    /// The user the token was issued to.
+    pub user_id: Uuid,
+    /// When the revoked token expires and the row can be dropped.
+    pub expires_at: DateTime<Utc>,
+    /// Timestamp when the token was revoked.
+    pub revoked_at: DateTime<Utc>,
+}
+
+/// A cut-off that revokes every access token issued to a user before it.
+///
+/// Written when all of a user's sessions end at once, such as on a password
+/// change, since their access token identifiers are not stored.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct UserAccessTokenRevocation {
+    /// The user whose tokens are revoked.
+    pub user_id: Uuid,
+    /// Tokens issued before this instant are rejected.
+    pub revoked_before: DateTime<Utc>,
+    /// When the last affected token expires and the row can be dropped.
+    pub expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,37 @@
+//! API key model for long-lived credentials used by scripts and integrations.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A personal access token that authenticates requests on behalf of a user.
+///
+/// Keys are presented as `ak_<prefix>_<secret>`. The prefix is stored in clear
+/// text to look keys up and identify them in listings; only a hash of the
+/// secret is stored. Keys grant a fixed set of scopes and can expire or be
+/// revoked.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[allow(dead_code)]
+pub struct ApiKey {
+    /// Unique identifier for the API key.
+    pub id: Uuid,
+    /// The user this key acts on behalf of.
+    pub user_id: Uuid,
+    /// Human-readable label chosen by the user.
+    pub name: String,
+    /// Public lookup prefix embedded in the key.
+    pub prefix: String,
+    /// Hashed version of the key secret for secure storage.
+    pub secret_hash: String,
+    /// Scopes granted to requests authenticated with this key.
+    pub scopes: Vec<String>,
+    /// When this key expires, if ever.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// When this key was last used to authenticate a request.
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// When this key was revoked, if it has been.
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// Timestamp when the key was created.
+    pub created_at: DateTime<Utc>,
+}
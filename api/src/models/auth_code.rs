@@ -1,5 +1,5 @@
-//! Authentication code model for email confirmation, password reset, and
-//! authenticated email-change verification flows.
+//! Authentication code model for email confirmation, password reset,
+//! authenticated email-change verification, and SMS phone-number flows.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -16,6 +16,12 @@ pub enum AuthCodeType {
     PasswordReset,
     /// Code sent to verify ownership of a new email before applying an email change.
     EmailChange,
+    /// Code sent by SMS to verify ownership of a phone number.
+    PhoneConfirmation,
+    /// Code sent by SMS to allow a user to reset their password.
+    PhonePasswordReset,
+    /// Code sent by SMS to log in without a password.
+    PhoneLogIn,
 }
 
 /// A time-limited authentication code used for email ownership and password
@@ -0,0 +1,49 @@
+//! Device authorization model for the OAuth 2.0 device authorization grant (RFC 8628).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Type};
+use uuid::Uuid;
+
+/// Progress of a device authorization request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "device_authorization_status", rename_all = "snake_case")]
+pub enum DeviceAuthorizationStatus {
+    /// Waiting for a user to approve or deny the user code.
+    Pending,
+    /// Approved by a user; the device may exchange its device code for tokens.
+    Approved,
+    /// Denied by a user.
+    Denied,
+    /// Tokens were issued; the device code cannot be used again.
+    Consumed,
+}
+
+/// A pending or completed login from an input-constrained device such as a CLI.
+///
+/// The device polls with a secret device code, stored only as a hash, while
+/// the user enters the short user code in a browser session to approve it.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[allow(dead_code)]
+pub struct DeviceAuthorization {
+    /// Unique identifier for the device authorization.
+    pub id: Uuid,
+    /// Hashed version of the device code for secure storage.
+    pub device_code_hash: String,
+    /// Normalized user code entered by the user (without separators).
+    pub user_code: String,
+    /// Client identifier supplied by the device, shown to the user on approval.
+    pub client_id: String,
+    /// Current progress of the request.
+    pub status: DeviceAuthorizationStatus,
+    /// User who approved or denied the request.
+    pub user_id: Option<Uuid>,
+    /// Minimum number of seconds the device must wait between polls.
+    pub interval_seconds: i32,
+    /// When the device last polled the token endpoint.
+    pub last_polled_at: Option<DateTime<Utc>>,
+    /// When the device and user codes expire.
+    pub expires_at: DateTime<Utc>,
+    /// Timestamp when the request was created.
+    pub created_at: DateTime<Utc>,
+}
@@ -3,6 +3,10 @@
 //! This module contains all SQLx-compatible structs that map to database tables,
 //! including users and authentication-related entities.
 
+pub mod access_token_revocation;
+pub mod api_key;
 pub mod auth_code;
+pub mod device_authorization;
 pub mod refresh_token;
+pub mod service_account;
 pub mod user;
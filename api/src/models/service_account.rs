@@ -0,0 +1,35 @@
+//! Service account model for non-human identities used in backend-to-backend calls.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A non-human identity that authenticates with the OAuth2 `client_credentials` grant.
+///
+/// Service accounts are owned by a user but never act as that user: tokens
+/// issued to them carry the `service` token type and only the scopes granted
+/// to the account. The client ID is stored in clear text; only a hash of the
+/// client secret is stored.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[allow(dead_code)]
+pub struct ServiceAccount {
+    /// Unique identifier for the service account.
+    pub id: Uuid,
+    /// The user who created and manages this account.
+    pub owner_user_id: Uuid,
+    /// Human-readable label chosen by the owner.
+    pub name: String,
+    /// Public OAuth2 client identifier.
+    pub client_id: String,
+    /// Hashed version of the client secret for secure storage.
+    pub client_secret_hash: String,
+    /// Scopes that tokens issued to this account may carry.
+    pub scopes: Vec<String>,
+    /// When this account last obtained a token.
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// When this account was revoked, if it has been.
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// Timestamp when the account was created.
+    pub created_at: DateTime<Utc>,
+}
@@ -25,6 +25,16 @@ pub struct User {
     pub hashed_password: String,
     /// Whether the user has confirmed their email address.
     pub email_confirmed: bool,
+    /// Preferred locale for emails.
+    pub locale: String,
+    /// Phone number in E.164 format used for SMS codes.
+    pub phone_number: Option<String>,
+    /// Whether the user has confirmed their phone number.
+    pub phone_confirmed: bool,
+    /// Version embedded in access tokens; bumping it rejects all older tokens.
+    pub token_version: i32,
+    /// Whether the user must choose a new password before continuing.
+    pub password_reset_required: bool,
     /// Timestamp when the user account was created.
     pub created_at: DateTime<Utc>,
     /// Timestamp when the user account was last updated.
@@ -0,0 +1,165 @@
+//! Access token revocation repository operations.
+//!
+//! This module centralizes SQL queries for the access token denylist. Inserts
+//! fire a `NOTIFY` on the `access_token_revocations` channel from a database
+//! trigger, so every API instance learns about them.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::models::access_token_revocation::{RevokedAccessToken, UserAccessTokenRevocation};
+
+/// Repository methods for access token revocation persistence.
+pub struct AccessTokenRevocationRepo;
+
+impl AccessTokenRevocationRepo {
+    /// Revokes a single access token by its `jti`.
+    ///
+    /// Revoking an already revoked token is a no-op.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `jti` - Access token identifier
+    /// - `user_id` - User the token was issued to
+    /// - `expires_at` - Token expiry, after which the row can be dropped
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the insert fails.
+    pub async fn revoke_access_token(
+        pool: &Pool<Postgres>,
+        jti: &str,
+        user_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO revoked_access_tokens (jti, user_id, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (jti) DO NOTHING
+            "#,
+            jti,
+            user_id,
+            expires_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Revokes every access token issued to a user before a cut-off.
+    ///
+    /// Returns the stored revocation. The notification is only delivered
+    /// once the transaction commits.
+    ///
+    /// # Arguments
+    ///
+    /// - `tx` - Active database transaction
+    /// - `user_id` - User whose access tokens should be revoked
+    /// - `revoked_before` - Tokens issued before this instant are revoked
+    /// - `expires_at` - Expiry of the newest affected token, after which the row can be dropped
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the upsert fails.
+    pub async fn revoke_user_access_tokens(
+        tx: &mut sqlx::Transaction<'_, Postgres>,
+        user_id: Uuid,
+        revoked_before: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<UserAccessTokenRevocation, sqlx::Error> {
+        let revocation = sqlx::query_as!(
+            UserAccessTokenRevocation,
+            r#"
+            INSERT INTO user_access_token_revocations (user_id, revoked_before, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id) DO UPDATE
+            SET revoked_before = EXCLUDED.revoked_before,
+                expires_at = EXCLUDED.expires_at
+            RETURNING user_id, revoked_before, expires_at
+            "#,
+            user_id,
+            revoked_before,
+            expires_at
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(revocation)
+    }
+
+    /// Lists revoked access tokens that have not expired yet.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the query fails.
+    pub async fn list_active_revoked_access_tokens(
+        pool: &Pool<Postgres>,
+    ) -> Result<Vec<RevokedAccessToken>, sqlx::Error> {
+        let result = sqlx::query_as!(
+            RevokedAccessToken,
+            r#"
+            SELECT jti, user_id, expires_at, revoked_at
+            FROM revoked_access_tokens
+            WHERE expires_at > NOW()
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Lists per-user revocations whose affected tokens have not expired yet.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the query fails.
+    pub async fn list_active_user_access_token_revocations(
+        pool: &Pool<Postgres>,
+    ) -> Result<Vec<UserAccessTokenRevocation>, sqlx::Error> {
+        let result = sqlx::query_as!(
+            UserAccessTokenRevocation,
+            r#"
+            SELECT user_id, revoked_before, expires_at
+            FROM user_access_token_revocations
+            WHERE expires_at > NOW()
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Deletes revocations that no longer affect any unexpired token.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if a delete fails.
+    pub async fn delete_expired_revocations(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM revoked_access_tokens WHERE expires_at <= NOW()"#)
+            .execute(pool)
+            .await?;
+        sqlx::query!(r#"DELETE FROM user_access_token_revocations WHERE expires_at <= NOW()"#)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+//! API key repository operations.
+//!
+//! This module centralizes SQL queries for creating, listing, revoking, and
+//! authenticating with API keys.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+/// API key fields safe to return to the owning user.
+#[derive(Debug, Serialize)]
+pub struct ApiKeySummary {
+    /// Unique API key identifier.
+    pub id: Uuid,
+    /// Human-readable label chosen by the user.
+    pub name: String,
+    /// Public lookup prefix embedded in the key.
+    pub prefix: String,
+    /// Scopes granted to the key.
+    pub scopes: Vec<String>,
+    /// When the key expires, if ever.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// When the key was last used to authenticate a request.
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// Timestamp when the key was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// API key fields required to authenticate a request.
+pub struct ApiKeyForAuth {
+    /// Unique API key identifier.
+    pub id: Uuid,
+    /// User the key acts on behalf of.
+    pub user_id: Uuid,
+    /// Stored secret hash used for verification.
+    pub secret_hash: String,
+    /// Scopes granted to the key.
+    pub scopes: Vec<String>,
+    /// When the key expires, if ever.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Repository methods for API key persistence.
+pub struct ApiKeyRepo;
+
+impl ApiKeyRepo {
+    /// Stores a new API key and returns its summary.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `user_id` - User the key acts on behalf of
+    /// - `name` - Human-readable label
+    /// - `prefix` - Public lookup prefix
+    /// - `secret_hash` - Hash of the key secret
+    /// - `scopes` - Scope names granted to the key
+    /// - `expires_at` - Optional expiration timestamp
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the insert fails.
+    pub async fn create_api_key(
+        pool: &Pool<Postgres>,
+        user_id: Uuid,
+        name: &str,
+        prefix: &str,
+        secret_hash: &str,
+        scopes: &[String],
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<ApiKeySummary, sqlx::Error> {
+        let result = sqlx::query_as!(
+            ApiKeySummary,
+            r#"
+            INSERT INTO api_keys (user_id, name, prefix, secret_hash, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, name, prefix, scopes, expires_at, last_used_at, created_at
+            "#,
+            user_id,
+            name,
+            prefix,
+            secret_hash,
+            scopes,
+            expires_at
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Lists a user's API keys that have not been revoked, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `user_id` - Owner of the keys
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the query fails.
+    pub async fn list_api_keys(
+        pool: &Pool<Postgres>,
+        user_id: Uuid,
+    ) -> Result<Vec<ApiKeySummary>, sqlx::Error> {
+        let result = sqlx::query_as!(
+            ApiKeySummary,
+            r#"
+            SELECT id, name, prefix, scopes, expires_at, last_used_at, created_at
+            FROM api_keys
+            WHERE user_id = $1 AND revoked_at IS NULL
+            ORDER BY created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Revokes one of a user's API keys.
+    ///
+    /// Returns `false` when the key does not exist, belongs to another user, or
+    /// is already revoked.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `user_id` - Owner of the key
+    /// - `api_key_id` - Key to revoke
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails.
+    pub async fn revoke_api_key(
+        pool: &Pool<Postgres>,
+        user_id: Uuid,
+        api_key_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE api_keys
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+            api_key_id,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Finds a non-revoked API key by its public prefix.
+    ///
+    /// Expiry is checked by the caller so expired keys can be reported as such.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `prefix` - Public lookup prefix from the presented key
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the query fails.
+    pub async fn find_active_api_key_by_prefix(
+        pool: &Pool<Postgres>,
+        prefix: &str,
+    ) -> Result<Option<ApiKeyForAuth>, sqlx::Error> {
+        let result = sqlx::query_as!(
+            ApiKeyForAuth,
+            r#"
+            SELECT id, user_id, secret_hash, scopes, expires_at
+            FROM api_keys
+            WHERE prefix = $1 AND revoked_at IS NULL
+            "#,
+            prefix
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Records that an API key was just used.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `api_key_id` - Key that authenticated a request
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails.
+    pub async fn touch_api_key(pool: &Pool<Postgres>, api_key_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE api_keys SET last_used_at = NOW() WHERE id = $1"#,
+            api_key_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -17,10 +17,16 @@ pub struct UserForLogin {
     pub id: Uuid,
     /// User email address.
     pub email: String,
+    /// User first name for personalization in confirmation reminders.
+    pub first_name: String,
+    /// Preferred locale used to pick the confirmation email template.
+    pub locale: String,
     /// Stored password hash used for password verification.
     pub hashed_password: String,
     /// Whether the user has confirmed their email.
     pub email_confirmed: bool,
+    /// Whether the user must choose a new password.
+    pub password_reset_required: bool,
 }
 
 /// User fields required for email confirmation checks.
@@ -37,6 +43,8 @@ pub struct UserForPasswordReset {
     pub id: Uuid,
     /// User first name for personalization in reset communications.
     pub first_name: String,
+    /// Preferred locale used to pick the reset email template.
+    pub locale: String,
 }
 
 /// User fields required when verifying forgot-password codes.
@@ -47,6 +55,16 @@ pub struct UserForVerification {
     pub email: String,
 }
 
+/// User fields required for SMS password-reset and log-in flows.
+pub struct UserForPhoneAuth {
+    /// Unique user identifier.
+    pub id: Uuid,
+    /// User email address.
+    pub email: String,
+    /// Whether the user must choose a new password.
+    pub password_reset_required: bool,
+}
+
 /// User fields required for refresh-token rotation.
 pub struct UserForTokenRefresh {
     /// Unique user identifier.
@@ -59,6 +77,12 @@ pub struct UserForTokenRefresh {
 pub struct UserForPasswordChange {
     /// Unique user identifier.
     pub id: Uuid,
+    /// User first name, checked against the new password.
+    pub first_name: String,
+    /// User last name, checked against the new password.
+    pub last_name: String,
+    /// Optional display name, checked against the new password.
+    pub display_name: Option<String>,
     /// User email address.
     pub email: String,
     /// Stored password hash used to verify the current password.
@@ -74,16 +98,47 @@ pub struct CurrentUser {
     pub first_name: String,
     /// User last name.
     pub last_name: String,
+    /// Optional name shown in place of the full name.
+    pub display_name: Option<String>,
     /// User email address.
     pub email: String,
     /// Whether the user has confirmed their email.
     pub email_confirmed: bool,
+    /// Preferred locale for emails.
+    pub locale: String,
+    /// IANA time zone name.
+    pub timezone: String,
+    /// Free-form client preferences object.
+    pub preferences: serde_json::Value,
+    /// Phone number in E.164 format, when one has been confirmed.
+    pub phone_number: Option<String>,
+    /// Whether the user has confirmed their phone number.
+    pub phone_confirmed: bool,
+    /// Whether the user must choose a new password, for example because their
+    /// current one appeared in a data breach.
+    pub password_reset_required: bool,
     /// Timestamp when the user record was created.
     pub created_at: DateTime<Utc>,
     /// Timestamp when the user record was last updated.
     pub updated_at: DateTime<Utc>,
 }
 
+/// Profile fields to change; `None` leaves the stored value unchanged.
+pub struct ProfileChanges<'a> {
+    /// New first name.
+    pub first_name: Option<&'a str>,
+    /// New last name.
+    pub last_name: Option<&'a str>,
+    /// New display name; `Some(None)` clears it.
+    pub display_name: Option<Option<&'a str>>,
+    /// New preferred locale.
+    pub locale: Option<&'a str>,
+    /// New IANA time zone name.
+    pub timezone: Option<&'a str>,
+    /// Replacement preferences object.
+    pub preferences: Option<&'a serde_json::Value>,
+}
+
 /// Auth code record used during code verification.
 pub struct ValidAuthCode {
     /// Unique auth code identifier.
@@ -163,7 +218,12 @@ impl AuthRepo {
     ) -> Result<Option<UserForLogin>, sqlx::Error> {
         let result = sqlx::query_as!(
             UserForLogin,
-            r#"SELECT id, email, hashed_password, email_confirmed FROM users WHERE LOWER(email) = LOWER($1)"#,
+            r#"
+        SELECT id, email, first_name, locale, hashed_password, email_confirmed,
+            password_reset_required
+        FROM users
+        WHERE LOWER(email) = LOWER($1)
+        "#,
             email
         )
         .fetch_optional(pool)
@@ -213,7 +273,7 @@ impl AuthRepo {
     ) -> Result<Option<UserForPasswordReset>, sqlx::Error> {
         let result = sqlx::query_as!(
             UserForPasswordReset,
-            r#"SELECT id, first_name FROM users WHERE LOWER(email) = LOWER($1)"#,
+            r#"SELECT id, first_name, locale FROM users WHERE LOWER(email) = LOWER($1)"#,
             email
         )
         .fetch_optional(pool)
@@ -247,6 +307,64 @@ impl AuthRepo {
         Ok(result)
     }
 
+    /// Finds a user by confirmed phone number for SMS password-reset and log-in flows.
+    ///
+    /// Unconfirmed phone numbers never match.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `phone_number` - Normalized E.164 phone number to look up
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the query fails.
+    pub async fn find_user_by_confirmed_phone(
+        pool: &Pool<Postgres>,
+        phone_number: &str,
+    ) -> Result<Option<UserForPhoneAuth>, sqlx::Error> {
+        let result = sqlx::query_as!(
+            UserForPhoneAuth,
+            r#"
+        SELECT id, email, password_reset_required
+        FROM users
+        WHERE phone_number = $1 AND phone_confirmed = true
+        "#,
+            phone_number
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Checks whether a phone number is used by any user other than the given user.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `phone_number` - Normalized E.164 phone number to check
+    /// - `user_id` - User ID to exclude from the check
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the query fails.
+    pub async fn check_phone_exists_for_other_user(
+        pool: &Pool<Postgres>,
+        phone_number: &str,
+        user_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT id FROM users WHERE phone_number = $1 AND id != $2"#,
+            phone_number,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result.is_some())
+    }
+
     /// Finds user data needed to rotate refresh/access tokens.
     ///
     /// # Arguments
@@ -272,6 +390,123 @@ impl AuthRepo {
         Ok(result)
     }
 
+    /// Finds the token version that a user's access tokens must carry.
+    ///
+    /// Returns `None` when the user no longer exists.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `user_id` - User identifier to look up
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the query fails.
+    pub async fn find_token_version(
+        pool: &Pool<Postgres>,
+        user_id: Uuid,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        let result =
+            sqlx::query_scalar!(r#"SELECT token_version FROM users WHERE id = $1"#, user_id)
+                .fetch_optional(pool)
+                .await?;
+
+        Ok(result)
+    }
+
+    /// Flags a user as having to choose a new password.
+    ///
+    /// The flag is cleared by [`update_user_password`](Self::update_user_password).
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `user_id` - User to flag
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails.
+    pub async fn require_password_reset(
+        pool: &Pool<Postgres>,
+        user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE users SET password_reset_required = TRUE, updated_at = NOW() WHERE id = $1"#,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replaces a user's password hash with an equivalent one made with
+    /// current hashing parameters.
+    ///
+    /// The password itself is unchanged, so sessions, the forced-reset flag
+    /// and password history are left alone. Nothing is written if the stored
+    /// hash changed since it was read.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `user_id` - User whose hash is upgraded
+    /// - `previous_hash` - Hash the password was verified against
+    /// - `hashed_password` - Replacement hash
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails.
+    pub async fn rehash_user_password(
+        pool: &Pool<Postgres>,
+        user_id: Uuid,
+        previous_hash: &str,
+        hashed_password: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE users SET hashed_password = $3 WHERE id = $1 AND hashed_password = $2"#,
+            user_id,
+            previous_hash,
+            hashed_password
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Increments a user's token version within an existing transaction,
+    /// invalidating every access token issued before.
+    ///
+    /// Returns the new version.
+    ///
+    /// # Arguments
+    ///
+    /// - `tx` - Active database transaction
+    /// - `user_id` - User whose access tokens should be invalidated
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails or the user does not exist.
+    pub async fn increment_token_version(
+        tx: &mut sqlx::Transaction<'_, Postgres>,
+        user_id: Uuid,
+    ) -> Result<i32, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"
+            UPDATE users
+            SET token_version = token_version + 1
+            WHERE id = $1
+            RETURNING token_version
+            "#,
+            user_id
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(result)
+    }
+
     /// Finds user data required for authenticated password changes.
     ///
     /// # Arguments
@@ -288,7 +523,11 @@ impl AuthRepo {
     ) -> Result<Option<UserForPasswordChange>, sqlx::Error> {
         let result = sqlx::query_as!(
             UserForPasswordChange,
-            r#"SELECT id, email, hashed_password FROM users WHERE id = $1"#,
+            r#"
+        SELECT id, first_name, last_name, display_name, email, hashed_password
+        FROM users
+        WHERE id = $1
+        "#,
             user_id
         )
         .fetch_optional(pool)
@@ -314,7 +553,9 @@ impl AuthRepo {
         let result = sqlx::query_as!(
             CurrentUser,
             r#"
-        SELECT id, first_name, last_name, email, email_confirmed, created_at, updated_at
+        SELECT id, first_name, last_name, display_name, email, email_confirmed, locale, timezone,
+               preferences, phone_number, phone_confirmed, password_reset_required, created_at,
+               updated_at
         FROM users
         WHERE id = $1
         "#,
@@ -335,6 +576,7 @@ impl AuthRepo {
     /// - `last_name` - User last name
     /// - `email` - User email address
     /// - `hashed_password` - Password hash to persist
+    /// - `locale` - Preferred locale for emails
     ///
     /// # Errors
     ///
@@ -345,17 +587,19 @@ impl AuthRepo {
         last_name: &str,
         email: &str,
         hashed_password: &str,
+        locale: &str,
     ) -> Result<Uuid, sqlx::Error> {
         let user_id = sqlx::query_scalar!(
             r#"
-        INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed)
-        VALUES ($1, $2, $3, $4, false)
+        INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed, locale)
+        VALUES ($1, $2, $3, $4, false, $5)
         RETURNING id
         "#,
             first_name,
             last_name,
             email,
-            hashed_password
+            hashed_password,
+            locale
         )
         .fetch_one(pool)
         .await?;
@@ -363,6 +607,51 @@ impl AuthRepo {
         Ok(user_id)
     }
 
+    /// Creates a user with a confirmed email and an existing password hash,
+    /// unless the email is already taken.
+    ///
+    /// Used when importing users from another system. Returns the new
+    /// user's ID, or `None` if a user with the email already exists.
+    ///
+    /// # Arguments
+    ///
+    /// - `tx` - Active database transaction
+    /// - `first_name` - User first name
+    /// - `last_name` - User last name
+    /// - `email` - Normalized user email
+    /// - `hashed_password` - Password hash carried over from the old system
+    /// - `locale` - Preferred locale for emails
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the insert fails.
+    pub async fn import_user(
+        tx: &mut sqlx::Transaction<'_, Postgres>,
+        first_name: &str,
+        last_name: &str,
+        email: &str,
+        hashed_password: &str,
+        locale: &str,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let user_id = sqlx::query_scalar!(
+            r#"
+        INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed, locale)
+        VALUES ($1, $2, $3, $4, true, $5)
+        ON CONFLICT (email) DO NOTHING
+        RETURNING id
+        "#,
+            first_name,
+            last_name,
+            email,
+            hashed_password,
+            locale
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(user_id)
+    }
+
     /// Marks a user's email as confirmed within an existing transaction.
     ///
     /// # Arguments
@@ -404,7 +693,11 @@ impl AuthRepo {
         hashed_password: &str,
     ) -> Result<(), sqlx::Error> {
         sqlx::query!(
-            r#"UPDATE users SET hashed_password = $1, updated_at = NOW() WHERE id = $2"#,
+            r#"
+            UPDATE users
+            SET hashed_password = $1, password_reset_required = FALSE, updated_at = NOW()
+            WHERE id = $2
+            "#,
             hashed_password,
             user_id
         )
@@ -457,6 +750,120 @@ impl AuthRepo {
         Ok(updated.is_some())
     }
 
+    /// Locks a user row and returns its current version for a conditional update.
+    ///
+    /// # Arguments
+    ///
+    /// - `tx` - Active database transaction
+    /// - `user_id` - User whose row should be locked
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the query fails.
+    pub async fn lock_user_for_update(
+        tx: &mut sqlx::Transaction<'_, Postgres>,
+        user_id: Uuid,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT updated_at FROM users WHERE id = $1 FOR UPDATE"#,
+            user_id
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Applies profile changes and returns the updated user.
+    ///
+    /// `updated_at` is maintained by the `users_set_updated_at` trigger.
+    ///
+    /// # Arguments
+    ///
+    /// - `tx` - Active database transaction
+    /// - `user_id` - User whose profile should be updated
+    /// - `changes` - Fields to change
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails or the user does not exist.
+    pub async fn update_user_profile(
+        tx: &mut sqlx::Transaction<'_, Postgres>,
+        user_id: Uuid,
+        changes: &ProfileChanges<'_>,
+    ) -> Result<CurrentUser, sqlx::Error> {
+        let result = sqlx::query_as!(
+            CurrentUser,
+            r#"
+            UPDATE users
+            SET first_name = COALESCE($2, first_name),
+                last_name = COALESCE($3, last_name),
+                display_name = CASE WHEN $4 THEN $5 ELSE display_name END,
+                locale = COALESCE($6, locale),
+                timezone = COALESCE($7, timezone),
+                preferences = COALESCE($8, preferences)
+            WHERE id = $1
+            RETURNING id, first_name, last_name, display_name, email, email_confirmed, locale,
+                      timezone, preferences, phone_number, phone_confirmed,
+                      password_reset_required, created_at, updated_at
+            "#,
+            user_id,
+            changes.first_name,
+            changes.last_name,
+            changes.display_name.is_some(),
+            changes.display_name.flatten(),
+            changes.locale,
+            changes.timezone,
+            changes.preferences
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Sets and confirms a user's phone number if no other user owns it.
+    ///
+    /// Returns `false` when another account already uses the phone number.
+    ///
+    /// # Arguments
+    ///
+    /// - `tx` - Active database transaction
+    /// - `user_id` - User whose phone number should be updated
+    /// - `phone_number` - Normalized E.164 phone number to persist
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails.
+    pub async fn update_user_phone_if_available(
+        tx: &mut sqlx::Transaction<'_, Postgres>,
+        user_id: Uuid,
+        phone_number: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let updated = sqlx::query!(
+            r#"
+            UPDATE users
+            SET phone_number = $1,
+                phone_confirmed = true,
+                updated_at = NOW()
+            WHERE id = $2
+              AND NOT EXISTS (
+                  SELECT 1
+                  FROM users AS existing_user
+                  WHERE existing_user.phone_number = $1
+                    AND existing_user.id != $2
+              )
+            RETURNING id
+            "#,
+            phone_number,
+            user_id
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(updated.is_some())
+    }
+
     /// Stores a hashed authentication code for a user.
     ///
     /// # Arguments
@@ -578,6 +985,37 @@ impl AuthRepo {
         Ok(())
     }
 
+    /// Invalidates all active auth codes of a given type for a user.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `user_id` - User whose codes should be invalidated
+    /// - `code_type` - Authentication code purpose to invalidate
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails.
+    pub async fn invalidate_auth_codes(
+        pool: &Pool<Postgres>,
+        user_id: Uuid,
+        code_type: AuthCodeType,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE auth_codes
+            SET used = true
+            WHERE user_id = $1 AND code_type = $2 AND used = false
+            "#,
+            user_id,
+            code_type as AuthCodeType
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Invalidates all active password reset codes for a user.
     ///
     /// # Arguments
@@ -823,4 +1261,27 @@ impl AuthRepo {
 
         Ok(())
     }
+
+    /// Deletes a user account.
+    ///
+    /// Refresh tokens, auth codes, API keys, service accounts, and other rows
+    /// owned by the user are removed by `ON DELETE CASCADE`.
+    ///
+    /// Returns `true` when a user was deleted.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `user_id` - User to delete
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the delete fails.
+    pub async fn delete_user(pool: &Pool<Postgres>, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(r#"DELETE FROM users WHERE id = $1"#, user_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
 }
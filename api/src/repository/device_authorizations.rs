@@ -0,0 +1,215 @@
+//! Device authorization repository operations.
+//!
+//! This module centralizes SQL queries for the OAuth 2.0 device authorization
+//! grant: creating requests, approving or denying them from a browser
+//! session, and polling from the device.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::models::device_authorization::DeviceAuthorizationStatus;
+
+/// Device authorization fields returned to a polling device.
+pub struct DeviceAuthorizationPoll {
+    /// Unique device authorization identifier.
+    pub id: Uuid,
+    /// Client identifier supplied when the request was created.
+    pub client_id: String,
+    /// Current progress of the request.
+    pub status: DeviceAuthorizationStatus,
+    /// Minimum number of seconds between polls.
+    pub interval_seconds: i32,
+    /// When the device code expires.
+    pub expires_at: DateTime<Utc>,
+    /// When the device polled before this poll, if ever.
+    pub previous_polled_at: Option<DateTime<Utc>>,
+}
+
+/// Repository methods for device authorization persistence.
+pub struct DeviceAuthorizationRepo;
+
+impl DeviceAuthorizationRepo {
+    /// Stores a new pending device authorization request.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `device_code_hash` - Hash of the device code
+    /// - `user_code` - Normalized user code
+    /// - `client_id` - Client identifier supplied by the device
+    /// - `interval_seconds` - Minimum number of seconds between polls
+    /// - `expires_at` - Expiration timestamp for both codes
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the insert fails.
+    pub async fn create_device_authorization(
+        pool: &Pool<Postgres>,
+        device_code_hash: &str,
+        user_code: &str,
+        client_id: &str,
+        interval_seconds: i32,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO device_authorizations
+                (device_code_hash, user_code, client_id, interval_seconds, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            device_code_hash,
+            user_code,
+            client_id,
+            interval_seconds,
+            expires_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records a user's decision on a pending, unexpired device authorization.
+    ///
+    /// Returns the client identifier of the request, or `None` when no pending
+    /// request matches the user code.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `user_code` - Normalized user code entered by the user
+    /// - `user_id` - User approving or denying the request
+    /// - `status` - [`DeviceAuthorizationStatus::Approved`] or [`DeviceAuthorizationStatus::Denied`]
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails.
+    pub async fn decide_device_authorization(
+        pool: &Pool<Postgres>,
+        user_code: &str,
+        user_id: Uuid,
+        status: DeviceAuthorizationStatus,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"
+            UPDATE device_authorizations
+            SET status = $3, user_id = $2
+            WHERE user_code = $1 AND status = 'pending' AND expires_at > NOW()
+            RETURNING client_id
+            "#,
+            user_code,
+            user_id,
+            status as DeviceAuthorizationStatus
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Records a poll from the device and returns the request's state.
+    ///
+    /// The previous poll time is returned so the caller can enforce the
+    /// polling interval.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `device_code_hash` - Hash of the device code presented by the device
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails.
+    pub async fn poll_device_authorization(
+        pool: &Pool<Postgres>,
+        device_code_hash: &str,
+    ) -> Result<Option<DeviceAuthorizationPoll>, sqlx::Error> {
+        let result = sqlx::query_as!(
+            DeviceAuthorizationPoll,
+            r#"
+            UPDATE device_authorizations AS current
+            SET last_polled_at = NOW()
+            FROM (
+                SELECT id, last_polled_at
+                FROM device_authorizations
+                WHERE device_code_hash = $1
+                FOR UPDATE
+            ) AS previous
+            WHERE current.id = previous.id
+            RETURNING current.id, current.client_id,
+                      current.status AS "status: DeviceAuthorizationStatus",
+                      current.interval_seconds, current.expires_at,
+                      previous.last_polled_at AS previous_polled_at
+            "#,
+            device_code_hash
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Increases the polling interval of a device that polled too quickly.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `device_authorization_id` - Device authorization to slow down
+    /// - `increment_seconds` - Seconds added to the interval
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails.
+    pub async fn slow_down_device_authorization(
+        pool: &Pool<Postgres>,
+        device_authorization_id: Uuid,
+        increment_seconds: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE device_authorizations
+            SET interval_seconds = interval_seconds + $2
+            WHERE id = $1
+            "#,
+            device_authorization_id,
+            increment_seconds
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks an approved device authorization as consumed.
+    ///
+    /// Returns the approving user's ID, or `None` when the request is not
+    /// approved or was already consumed by a concurrent poll.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `device_authorization_id` - Device authorization to consume
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails.
+    pub async fn consume_approved_device_authorization(
+        pool: &Pool<Postgres>,
+        device_authorization_id: Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"
+            UPDATE device_authorizations
+            SET status = 'consumed'
+            WHERE id = $1 AND status = 'approved'
+            RETURNING user_id AS "user_id!"
+            "#,
+            device_authorization_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result)
+    }
+}
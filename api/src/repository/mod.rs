@@ -5,6 +5,16 @@
 //!
 //! # Modules
 //!
+//! - [`access_token_revocations`] - Access token denylist inserts, loading, and cleanup queries
+//! - [`api_keys`] - API key creation, listing, revocation, and lookup queries
 //! - [`auth`] - User, authentication code, and refresh token queries
+//! - [`device_authorizations`] - Device authorization grant creation, approval, and polling queries
+//! - [`password_history`] - Recent password hashes used to prevent password reuse
+//! - [`service_accounts`] - Service account creation, listing, revocation, and lookup queries
 
+pub mod access_token_revocations;
+pub mod api_keys;
 pub mod auth;
+pub mod device_authorizations;
+pub mod password_history;
+pub mod service_accounts;
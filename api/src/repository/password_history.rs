@@ -0,0 +1,113 @@
+//! Password history repository operations.
+//!
+//! This module centralizes SQL queries for the `password_history` table, which
+//! keeps each user's most recent password hashes so old passwords cannot be
+//! reused. Writes happen in the same transaction as
+//! [`AuthRepo::update_user_password`](crate::repository::auth::AuthRepo::update_user_password).
+
+use sqlx::Postgres;
+use uuid::Uuid;
+
+/// Repository methods for password history persistence.
+pub struct PasswordHistoryRepo;
+
+impl PasswordHistoryRepo {
+    /// Returns a user's most recent password hashes, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// - `tx` - Active database transaction
+    /// - `user_id` - User whose history to read
+    /// - `limit` - Maximum number of hashes to return
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the query fails.
+    pub async fn find_recent_password_hashes(
+        tx: &mut sqlx::Transaction<'_, Postgres>,
+        user_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let hashes = sqlx::query_scalar!(
+            r#"
+            SELECT hashed_password
+            FROM password_history
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+            user_id,
+            limit as i64
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        Ok(hashes)
+    }
+
+    /// Records a newly set password hash and drops entries beyond the most
+    /// recent `history_size`.
+    ///
+    /// Users whose history is still empty, such as accounts created before
+    /// the history existed, get the replaced hash recorded first so the
+    /// history always covers their last passwords.
+    ///
+    /// # Arguments
+    ///
+    /// - `tx` - Active database transaction
+    /// - `user_id` - User whose password changed
+    /// - `previous_hash` - Hash of the password being replaced
+    /// - `hashed_password` - Hash of the new password
+    /// - `history_size` - Number of hashes to keep per user
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if an insert or the cleanup fails.
+    pub async fn record_password(
+        tx: &mut sqlx::Transaction<'_, Postgres>,
+        user_id: Uuid,
+        previous_hash: &str,
+        hashed_password: &str,
+        history_size: usize,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO password_history (user_id, hashed_password, created_at)
+            SELECT $1, $2, NOW() - INTERVAL '1 microsecond'
+            WHERE NOT EXISTS (SELECT 1 FROM password_history WHERE user_id = $1)
+            "#,
+            user_id,
+            previous_hash
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query!(
+            r#"INSERT INTO password_history (user_id, hashed_password) VALUES ($1, $2)"#,
+            user_id,
+            hashed_password
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM password_history
+            WHERE user_id = $1
+              AND id NOT IN (
+                  SELECT id
+                  FROM password_history
+                  WHERE user_id = $1
+                  ORDER BY created_at DESC
+                  LIMIT $2
+              )
+            "#,
+            user_id,
+            history_size as i64
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+}
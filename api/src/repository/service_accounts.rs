@@ -0,0 +1,231 @@
+//! Service account repository operations.
+//!
+//! This module centralizes SQL queries for creating, listing, revoking, and
+//! authenticating service accounts.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+/// Service account fields safe to return to the owning user.
+#[derive(Debug, Serialize)]
+pub struct ServiceAccountSummary {
+    /// Unique service account identifier.
+    pub id: Uuid,
+    /// Human-readable label chosen by the owner.
+    pub name: String,
+    /// Public OAuth2 client identifier.
+    pub client_id: String,
+    /// Scopes that tokens issued to the account may carry.
+    pub scopes: Vec<String>,
+    /// When the account last obtained a token.
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// Timestamp when the account was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Service account fields required to authenticate a client.
+pub struct ServiceAccountForAuth {
+    /// Unique service account identifier.
+    pub id: Uuid,
+    /// The user who created and manages the account.
+    pub owner_user_id: Uuid,
+    /// Public OAuth2 client identifier.
+    pub client_id: String,
+    /// Stored client secret hash used for verification.
+    pub client_secret_hash: String,
+    /// Scopes that tokens issued to the account may carry.
+    pub scopes: Vec<String>,
+}
+
+/// Repository methods for service account persistence.
+pub struct ServiceAccountRepo;
+
+impl ServiceAccountRepo {
+    /// Stores a new service account and returns its summary.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `owner_user_id` - User who manages the account
+    /// - `name` - Human-readable label
+    /// - `client_id` - Public OAuth2 client identifier
+    /// - `client_secret_hash` - Hash of the client secret
+    /// - `scopes` - Scope names the account may request
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the insert fails.
+    pub async fn create_service_account(
+        pool: &Pool<Postgres>,
+        owner_user_id: Uuid,
+        name: &str,
+        client_id: &str,
+        client_secret_hash: &str,
+        scopes: &[String],
+    ) -> Result<ServiceAccountSummary, sqlx::Error> {
+        let result = sqlx::query_as!(
+            ServiceAccountSummary,
+            r#"
+            INSERT INTO service_accounts (owner_user_id, name, client_id, client_secret_hash, scopes)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, name, client_id, scopes, last_used_at, created_at
+            "#,
+            owner_user_id,
+            name,
+            client_id,
+            client_secret_hash,
+            scopes
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Lists a user's service accounts that have not been revoked, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `owner_user_id` - Owner of the accounts
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the query fails.
+    pub async fn list_service_accounts(
+        pool: &Pool<Postgres>,
+        owner_user_id: Uuid,
+    ) -> Result<Vec<ServiceAccountSummary>, sqlx::Error> {
+        let result = sqlx::query_as!(
+            ServiceAccountSummary,
+            r#"
+            SELECT id, name, client_id, scopes, last_used_at, created_at
+            FROM service_accounts
+            WHERE owner_user_id = $1 AND revoked_at IS NULL
+            ORDER BY created_at DESC
+            "#,
+            owner_user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Revokes one of a user's service accounts.
+    ///
+    /// Returns `false` when the account does not exist, belongs to another
+    /// user, or is already revoked.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `owner_user_id` - Owner of the account
+    /// - `service_account_id` - Account to revoke
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails.
+    pub async fn revoke_service_account(
+        pool: &Pool<Postgres>,
+        owner_user_id: Uuid,
+        service_account_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE service_accounts
+            SET revoked_at = NOW()
+            WHERE id = $1 AND owner_user_id = $2 AND revoked_at IS NULL
+            "#,
+            service_account_id,
+            owner_user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Finds a non-revoked service account by its client ID.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `client_id` - Client identifier presented to the token endpoint
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the query fails.
+    pub async fn find_active_service_account_by_client_id(
+        pool: &Pool<Postgres>,
+        client_id: &str,
+    ) -> Result<Option<ServiceAccountForAuth>, sqlx::Error> {
+        let result = sqlx::query_as!(
+            ServiceAccountForAuth,
+            r#"
+            SELECT id, owner_user_id, client_id, client_secret_hash, scopes
+            FROM service_accounts
+            WHERE client_id = $1 AND revoked_at IS NULL
+            "#,
+            client_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Checks whether a service account exists and has not been revoked.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `service_account_id` - Account named in a service token
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the query fails.
+    pub async fn is_service_account_active(
+        pool: &Pool<Postgres>,
+        service_account_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM service_accounts WHERE id = $1 AND revoked_at IS NULL
+            ) AS "active!"
+            "#,
+            service_account_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Records that a service account just obtained a token.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` - Database connection pool
+    /// - `service_account_id` - Account that authenticated
+    ///
+    /// # Errors
+    ///
+    /// Returns `sqlx::Error` if the update fails.
+    pub async fn touch_service_account(
+        pool: &Pool<Postgres>,
+        service_account_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE service_accounts SET last_used_at = NOW() WHERE id = $1"#,
+            service_account_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
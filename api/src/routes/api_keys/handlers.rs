@@ -0,0 +1,133 @@
+//! HTTP handler functions for API key endpoints.
+//!
+//! Keys can only be managed from a session: an API key cannot create, list,
+//! or revoke other keys.
+
+use actix_web::{HttpResponse, delete, get, post, web};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::auth::api_keys::generate_api_key;
+use crate::auth::middleware::AuthenticatedUser;
+use crate::core::app_state::AppState;
+use crate::core::error::{ApiError, ApiResult};
+use crate::extractors::ValidatedJson;
+use crate::repository::api_keys::ApiKeyRepo;
+
+use super::payloads::{
+    CreateApiKeyRequest, CreateApiKeyResponse, ListApiKeysResponse, RevokeApiKeyResponse,
+};
+
+/// Creates an API key for the authenticated user.
+///
+/// The full key is returned only in this response; afterwards only its
+/// prefix is shown.
+///
+/// # Route
+///
+/// `POST /auth/api-keys`
+///
+/// # Request Body ([`CreateApiKeyRequest`])
+///
+/// - `name` - Human-readable label for the key
+/// - `scopes` - Scopes granted to the key
+/// - `expires_in_days` - Optional lifetime in days (1 to 365)
+///
+/// # Response Body ([`CreateApiKeyResponse`])
+///
+/// - `key` - Full API key (`ak_<prefix>_<secret>`)
+/// - `api_key` - Stored key details (`id`, `name`, `prefix`, `scopes`, `expires_at`, `last_used_at`, `created_at`)
+///
+/// # Errors
+///
+/// - `Unauthorized` - If not authenticated with a session
+/// - `InternalError` - If database operations fail
+#[post("/auth/api-keys")]
+pub async fn create_api_key(
+    state: web::Data<AppState>,
+    auth_user: AuthenticatedUser,
+    body: ValidatedJson<CreateApiKeyRequest>,
+) -> ApiResult<HttpResponse> {
+    let body = body.into_inner();
+
+    let mut scopes = body.scopes;
+    scopes.sort();
+    scopes.dedup();
+
+    let expires_at = body
+        .expires_in_days
+        .map(|days| Utc::now() + Duration::days(i64::from(days)));
+
+    let generated = generate_api_key();
+    let api_key = ApiKeyRepo::create_api_key(
+        &state.pool,
+        auth_user.user_id,
+        body.name.trim(),
+        &generated.prefix,
+        &generated.secret_hash,
+        &scopes,
+        expires_at,
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(CreateApiKeyResponse {
+        key: generated.key,
+        api_key,
+    }))
+}
+
+/// Lists the authenticated user's active API keys.
+///
+/// # Route
+///
+/// `GET /auth/api-keys`
+///
+/// # Response Body ([`ListApiKeysResponse`])
+///
+/// - `api_keys` - Non-revoked keys, newest first (secrets are never returned)
+///
+/// # Errors
+///
+/// - `Unauthorized` - If not authenticated with a session
+#[get("/auth/api-keys")]
+pub async fn list_api_keys(
+    state: web::Data<AppState>,
+    auth_user: AuthenticatedUser,
+) -> ApiResult<HttpResponse> {
+    let api_keys = ApiKeyRepo::list_api_keys(&state.pool, auth_user.user_id).await?;
+
+    Ok(HttpResponse::Ok().json(ListApiKeysResponse { api_keys }))
+}
+
+/// Revokes one of the authenticated user's API keys.
+///
+/// Requests using the key fail immediately after revocation.
+///
+/// # Route
+///
+/// `DELETE /auth/api-keys/{id}`
+///
+/// # Response Body ([`RevokeApiKeyResponse`])
+///
+/// - `message` - Success message
+///
+/// # Errors
+///
+/// - `Unauthorized` - If not authenticated with a session
+/// - `NotFound` - If the key doesn't exist, belongs to another user, or is already revoked
+#[delete("/auth/api-keys/{id}")]
+pub async fn revoke_api_key(
+    state: web::Data<AppState>,
+    auth_user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> ApiResult<HttpResponse> {
+    let api_key_id = path.into_inner();
+
+    if !ApiKeyRepo::revoke_api_key(&state.pool, auth_user.user_id, api_key_id).await? {
+        return Err(ApiError::NotFound("API key not found".to_string()));
+    }
+
+    Ok(HttpResponse::Ok().json(RevokeApiKeyResponse {
+        message: "API key revoked.".to_string(),
+    }))
+}
@@ -0,0 +1,25 @@
+//! API key management endpoints.
+//!
+//! This module lets an authenticated user manage personal access tokens for
+//! scripts and integrations:
+//! - Creating a scoped, optionally expiring key (the secret is shown once)
+//! - Listing active keys
+//! - Revoking a key
+//!
+//! Requests authenticated with an API key are handled by the
+//! [`ApiKeyAuth`](crate::auth::principal::ApiKeyAuth) and
+//! [`Principal`](crate::auth::principal::Principal) extractors.
+//!
+//! # Module Structure
+//!
+//! - [`handlers`] - HTTP handler functions for API key endpoints
+//! - [`payloads`] - Request and response data structures
+
+pub mod handlers;
+pub mod payloads;
+
+// Re-export handlers at module level for easy route registration
+pub use handlers::{create_api_key, list_api_keys, revoke_api_key};
+
+// Re-export payload types that are used by other modules
+pub use payloads::CreateApiKeyRequest;
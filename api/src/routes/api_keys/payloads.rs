@@ -0,0 +1,54 @@
+//! Request and response payloads for API key endpoints.
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::repository::api_keys::ApiKeySummary;
+use crate::validators::scopes::validate_scopes;
+
+/// Request body for creating an API key.
+///
+/// See [`create_api_key`](super::handlers::create_api_key) for the handler that processes this request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateApiKeyRequest {
+    /// Human-readable label for the key.
+    #[validate(length(min = 1, max = 100, message = "Name must have 1 to 100 characters"))]
+    pub name: String,
+
+    /// Scopes granted to the key (e.g. `profile:read`).
+    #[validate(custom(function = "validate_scopes"))]
+    pub scopes: Vec<String>,
+
+    /// Number of days until the key expires; omit for a key that never expires.
+    #[validate(range(min = 1, max = 365, message = "Expiry must be between 1 and 365 days"))]
+    pub expires_in_days: Option<u32>,
+}
+
+/// Response body for a newly created API key.
+///
+/// See [`create_api_key`](super::handlers::create_api_key) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct CreateApiKeyResponse {
+    /// Full API key; it is only returned once and cannot be recovered.
+    pub key: String,
+    /// Stored key details.
+    pub api_key: ApiKeySummary,
+}
+
+/// Response body listing a user's API keys.
+///
+/// See [`list_api_keys`](super::handlers::list_api_keys) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct ListApiKeysResponse {
+    /// Active (non-revoked) keys, newest first.
+    pub api_keys: Vec<ApiKeySummary>,
+}
+
+/// Response body for API key revocation.
+///
+/// See [`revoke_api_key`](super::handlers::revoke_api_key) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct RevokeApiKeyResponse {
+    /// Success message.
+    pub message: String,
+}
@@ -3,11 +3,15 @@
 //! This module provides HTTP handlers for all authentication-related endpoints:
 //! - User registration and email confirmation
 //! - Login and logout with JWT tokens stored in HTTP-only cookies
+//! - Token-in-body login and refresh for non-browser clients using bearer tokens
 //! - Session refresh via refresh-token rotation
 //! - Password reset flow (forgot password, verify code, set new password)
 //! - Authenticated password change with current-password verification
 //! - Authenticated email-change request and confirmation
-//! - Current user retrieval for authenticated sessions
+//! - Phone number confirmation and SMS password-reset and log-in codes
+//! - Current user retrieval and profile updates for authenticated sessions
+//! - Step-up re-authentication guarding email change, account deletion, and
+//!   revoking every session
 //!
 //! # Module Structure
 //!
@@ -19,9 +23,16 @@ pub mod payloads;
 
 // Re-export handlers at module level for easy route registration
 pub use handlers::{
-    change_password, confirm_email, confirm_email_change, current_user, forgot_password, log_in,
-    log_out, refresh_session, request_email_change, set_password, sign_up, verify_forgot_password,
+    change_password, confirm_email, confirm_email_change, confirm_phone, current_user,
+    delete_current_user, forgot_password, forgot_password_by_phone, log_in, log_in_for_token,
+    log_in_with_phone_code, log_out, log_out_everywhere, reauthenticate, refresh_session,
+    refresh_session_for_token, request_email_change, request_phone_confirmation,
+    request_phone_log_in_code, set_password, sign_up, update_current_user, verify_forgot_password,
+    verify_forgot_password_by_phone, verify_session,
 };
 
 // Re-export payload types that are used by other modules
-pub use payloads::{ChangePasswordRequest, SetPasswordRequest, SignUpRequest};
+pub use payloads::{
+    ChangePasswordRequest, ReauthenticateRequest, RequestEmailChangeRequest, SetPasswordRequest,
+    SignUpRequest, UpdateCurrentUserRequest,
+};
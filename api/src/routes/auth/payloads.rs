@@ -8,13 +8,20 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::repository::auth::CurrentUser;
+use crate::validators::locale::validate_locale;
 use crate::validators::password_match::{
     validate_change_password_match, validate_set_password_match, validate_signup_passwords_match,
 };
+use crate::validators::phone_number::validate_phone_number;
+use crate::validators::profile::{
+    validate_preferences, validate_profile_update_not_empty, validate_timezone,
+};
+use crate::validators::reauthentication::validate_reauthentication_factor;
 
 /// Request body for user registration.
 ///
-/// Validates that passwords match and meet minimum length requirements.
+/// Validates that passwords match. The password policy is checked by the
+/// handler with [`validate_signup_password_policy`](crate::validators::password_policy::validate_signup_password_policy).
 ///
 /// See [`sign_up`](super::handlers::sign_up) for the handler that processes this request.
 #[derive(Debug, Deserialize, Validate)]
@@ -32,13 +39,22 @@ pub struct SignUpRequest {
     #[validate(email(message = "Email is invalid"))]
     pub email: String,
 
-    /// User's chosen password (minimum 8 characters).
-    #[validate(length(min = 8, message = "Password must have at least 8 characters"))]
+    /// User's chosen password (must satisfy the password policy).
+    #[validate(length(min = 1, message = "Password is required"))]
     pub password: String,
 
     /// Password confirmation (must match `password`).
     #[validate(length(min = 1, message = "Confirm password is required"))]
     pub confirm: String,
+
+    /// Preferred locale for emails (for example `en` or `es`); defaults to `en`.
+    #[serde(default)]
+    #[validate(custom(function = "validate_locale"))]
+    pub locale: Option<String>,
+
+    /// Answer to the human verification challenge, once one is required.
+    #[serde(default)]
+    pub human_verification: Option<String>,
 }
 
 /// Response body for successful user registration.
@@ -144,17 +160,61 @@ pub struct LogInResponse {
     pub message: String,
     /// The authenticated user's unique identifier.
     pub user_id: Uuid,
+    /// Whether the user must choose a new password, for example because the
+    /// one they logged in with appeared in a data breach.
+    pub password_reset_required: bool,
 }
 
 /// Response body for logout.
 ///
-/// See [`log_out`](super::handlers::log_out) for the handler that produces this response.
+/// See [`log_out`](super::handlers::log_out) and
+/// [`log_out_everywhere`](super::handlers::log_out_everywhere) for the handlers that produce this response.
 #[derive(Debug, Serialize)]
 pub struct LogOutResponse {
     /// Success message.
     pub message: String,
 }
 
+/// Request body for step-up re-authentication.
+///
+/// Exactly one of `password` or `auth_code` must be provided.
+///
+/// See [`reauthenticate`](super::handlers::reauthenticate) for the handler that processes this request.
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_reauthentication_factor"))]
+pub struct ReauthenticateRequest {
+    /// The user's current password.
+    #[validate(length(min = 1, message = "Password is required"))]
+    pub password: Option<String>,
+
+    /// SMS log-in code sent to the user's confirmed phone number.
+    #[validate(length(min = 1, message = "Auth code is required"))]
+    pub auth_code: Option<String>,
+}
+
+/// Response body for successful re-authentication.
+///
+/// See [`reauthenticate`](super::handlers::reauthenticate) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct ReauthenticateResponse {
+    /// Success message.
+    pub message: String,
+    /// Replacement access token, returned only to bearer-token clients.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+    /// Seconds for which sensitive actions are allowed without re-authenticating again.
+    pub expires_in: u64,
+}
+
+/// Response body for account deletion.
+///
+/// See [`delete_current_user`](super::handlers::delete_current_user) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct DeleteAccountResponse {
+    /// Success message.
+    pub message: String,
+}
+
 /// Response body for successful token refresh.
 ///
 /// See [`refresh_session`](super::handlers::refresh_session) for the handler that produces this response.
@@ -164,15 +224,103 @@ pub struct RefreshSessionResponse {
     pub message: String,
 }
 
+/// Request body for rotating a refresh token outside of cookies.
+///
+/// See [`refresh_session_for_token`](super::handlers::refresh_session_for_token) for the handler that processes this request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct RefreshTokenRequest {
+    /// Refresh token from a previous token response.
+    #[validate(length(min = 1, message = "Refresh token is required"))]
+    pub refresh_token: String,
+}
+
+/// Response body for token-in-body log-in and refresh.
+///
+/// See [`log_in_for_token`](super::handlers::log_in_for_token) and
+/// [`refresh_session_for_token`](super::handlers::refresh_session_for_token) for the handlers that produce this response.
+#[derive(Debug, Serialize)]
+pub struct SessionTokenResponse {
+    /// Access token sent as `Authorization: Bearer <access_token>`.
+    pub access_token: String,
+    /// Refresh token used to obtain a new token pair.
+    pub refresh_token: String,
+    /// Token type; always `Bearer`.
+    pub token_type: String,
+    /// Access token lifetime in seconds.
+    pub expires_in: u64,
+    /// The authenticated user's unique identifier.
+    pub user_id: Uuid,
+}
+
 /// Response body containing the authenticated user's information.
 ///
-/// See [`current_user`](super::handlers::current_user) for the handler that produces this response.
+/// See [`current_user`](super::handlers::current_user) and
+/// [`update_current_user`](super::handlers::update_current_user) for the handlers that produce this response.
 #[derive(Debug, Serialize)]
 pub struct CurrentUserResponse {
     /// The current user's profile data.
     pub user: CurrentUser,
 }
 
+/// Request body for updating the authenticated user's profile.
+///
+/// Every field is optional; omitted fields are left unchanged. `display_name`
+/// may be set to `null` to clear it. `preferences` replaces the stored object.
+///
+/// See [`update_current_user`](super::handlers::update_current_user) for the handler that processes this request.
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_profile_update_not_empty"))]
+pub struct UpdateCurrentUserRequest {
+    /// User's first name.
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "First name must have 1 to 100 characters"
+    ))]
+    pub first_name: Option<String>,
+
+    /// User's last name.
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Last name must have 1 to 100 characters"
+    ))]
+    pub last_name: Option<String>,
+
+    /// Name shown in place of the full name; `Some(None)` clears it.
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Display name must have 1 to 100 characters"
+    ))]
+    pub display_name: Option<Option<String>>,
+
+    /// Preferred locale for emails (e.g. `en`, `es`, `pt-BR`).
+    #[validate(custom(function = "validate_locale"))]
+    pub locale: Option<String>,
+
+    /// IANA time zone name (e.g. `Europe/Madrid`).
+    #[validate(custom(function = "validate_timezone"))]
+    pub timezone: Option<String>,
+
+    /// Free-form client preferences object.
+    #[validate(custom(function = "validate_preferences"))]
+    pub preferences: Option<serde_json::Value>,
+}
+
+/// Deserializes a field that distinguishes "absent" from explicit `null`.
+///
+/// Combined with `#[serde(default)]`, an absent field becomes `None` and an
+/// explicit `null` becomes `Some(None)`.
+fn deserialize_nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
 /// Request body for initiating password reset.
 ///
 /// See [`forgot_password`](super::handlers::forgot_password) for the handler that processes this request.
@@ -181,6 +329,10 @@ pub struct ForgotPasswordRequest {
     /// Email address of the account to reset.
     #[validate(email(message = "Email is invalid"))]
     pub email: String,
+
+    /// Answer to the human verification challenge, once one is required.
+    #[serde(default)]
+    pub human_verification: Option<String>,
 }
 
 /// Response body for forgot password request.
@@ -217,8 +369,8 @@ pub struct VerifyForgotPasswordResponse {
 
 /// Request body for changing password while authenticated.
 ///
-/// Validates that new-password and confirmation fields match and meet minimum
-/// length requirements.
+/// Validates that new-password and confirmation fields match. The password
+/// policy is checked by the handler with [`validate_change_password_policy`](crate::validators::password_policy::validate_change_password_policy).
 ///
 /// See [`change_password`](super::handlers::change_password) for the handler that processes this request.
 #[derive(Debug, Deserialize, Validate)]
@@ -228,8 +380,8 @@ pub struct ChangePasswordRequest {
     #[validate(length(min = 1, message = "Current password is required"))]
     pub current_password: String,
 
-    /// The new password (minimum 8 characters).
-    #[validate(length(min = 8, message = "New password must have at least 8 characters"))]
+    /// The new password (must satisfy the password policy).
+    #[validate(length(min = 1, message = "New password is required"))]
     pub new_password: String,
 
     /// Confirmation for `new_password`.
@@ -248,14 +400,15 @@ pub struct ChangePasswordResponse {
 
 /// Request body for setting a new password.
 ///
-/// Validates that passwords match and meet minimum length requirements.
+/// Validates that passwords match. The password policy is checked by the
+/// handler with [`validate_set_password_policy`](crate::validators::password_policy::validate_set_password_policy).
 ///
 /// See [`set_password`](super::handlers::set_password) for the handler that processes this request.
 #[derive(Debug, Deserialize, Validate)]
 #[validate(schema(function = "validate_set_password_match"))]
 pub struct SetPasswordRequest {
-    /// The new password (minimum 8 characters).
-    #[validate(length(min = 8, message = "Password must have at least 8 characters"))]
+    /// The new password (must satisfy the password policy).
+    #[validate(length(min = 1, message = "Password is required"))]
     pub password: String,
 
     /// Password confirmation (must match `password`).
@@ -271,3 +424,133 @@ pub struct SetPasswordResponse {
     /// Success message.
     pub message: String,
 }
+
+/// Request body for sending a phone confirmation code.
+///
+/// See [`request_phone_confirmation`](super::handlers::request_phone_confirmation) for the handler that processes this request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct RequestPhoneConfirmationRequest {
+    /// Phone number to verify, in international format.
+    #[validate(custom(function = "validate_phone_number"))]
+    pub phone_number: String,
+}
+
+/// Response body for phone confirmation-code requests.
+///
+/// See [`request_phone_confirmation`](super::handlers::request_phone_confirmation) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct RequestPhoneConfirmationResponse {
+    /// Generic status message.
+    pub message: String,
+}
+
+/// Request body for confirming a phone number.
+///
+/// See [`confirm_phone`](super::handlers::confirm_phone) for the handler that processes this request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ConfirmPhoneRequest {
+    /// Phone number being confirmed, in international format.
+    #[validate(custom(function = "validate_phone_number"))]
+    pub phone_number: String,
+
+    /// The confirmation code sent to `phone_number`.
+    #[validate(length(min = 1, message = "Auth code is required"))]
+    pub auth_code: String,
+}
+
+/// Response body for successful phone confirmation.
+///
+/// See [`confirm_phone`](super::handlers::confirm_phone) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct ConfirmPhoneResponse {
+    /// Success message.
+    pub message: String,
+}
+
+/// Request body for initiating password reset by SMS.
+///
+/// See [`forgot_password_by_phone`](super::handlers::forgot_password_by_phone) for the handler that processes this request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ForgotPasswordByPhoneRequest {
+    /// Confirmed phone number of the account, in international format.
+    #[validate(custom(function = "validate_phone_number"))]
+    pub phone_number: String,
+}
+
+/// Response body for SMS password reset initiation.
+///
+/// See [`forgot_password_by_phone`](super::handlers::forgot_password_by_phone) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct ForgotPasswordByPhoneResponse {
+    /// Generic status message.
+    pub message: String,
+}
+
+/// Request body for verifying an SMS password reset code.
+///
+/// See [`verify_forgot_password_by_phone`](super::handlers::verify_forgot_password_by_phone) for the handler that processes this request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifyForgotPasswordByPhoneRequest {
+    /// Confirmed phone number of the account, in international format.
+    #[validate(custom(function = "validate_phone_number"))]
+    pub phone_number: String,
+
+    /// The password reset code sent by SMS.
+    #[validate(length(min = 1, message = "Auth code is required"))]
+    pub auth_code: String,
+}
+
+/// Response body for successful SMS password reset code verification.
+///
+/// See [`verify_forgot_password_by_phone`](super::handlers::verify_forgot_password_by_phone) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct VerifyForgotPasswordByPhoneResponse {
+    /// Success message.
+    pub message: String,
+}
+
+/// Request body for sending a log-in code by SMS.
+///
+/// See [`request_phone_log_in_code`](super::handlers::request_phone_log_in_code) for the handler that processes this request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct RequestPhoneLogInCodeRequest {
+    /// Confirmed phone number of the account, in international format.
+    #[validate(custom(function = "validate_phone_number"))]
+    pub phone_number: String,
+}
+
+/// Response body for SMS log-in code requests.
+///
+/// See [`request_phone_log_in_code`](super::handlers::request_phone_log_in_code) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct RequestPhoneLogInCodeResponse {
+    /// Generic status message.
+    pub message: String,
+}
+
+/// Request body for logging in with an SMS code.
+///
+/// See [`log_in_with_phone_code`](super::handlers::log_in_with_phone_code) for the handler that processes this request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct PhoneLogInRequest {
+    /// Confirmed phone number of the account, in international format.
+    #[validate(custom(function = "validate_phone_number"))]
+    pub phone_number: String,
+
+    /// The log-in code sent by SMS.
+    #[validate(length(min = 1, message = "Auth code is required"))]
+    pub auth_code: String,
+
+    /// Whether the login session should persist across browser restarts.
+    #[serde(default)]
+    pub remember_me: bool,
+}
+
+/// Query parameters for the forward-auth endpoint.
+///
+/// See [`verify_session`](super::handlers::verify_session) for the handler that processes this request.
+#[derive(Debug, Deserialize)]
+pub struct VerifySessionQuery {
+    /// Redirect unauthenticated requests to the log-in page instead of returning `401`.
+    pub redirect: Option<bool>,
+}
@@ -0,0 +1,16 @@
+//! Development-only endpoints.
+//!
+//! These routes are only served when the API runs in development and exist to
+//! make local auth flows usable without external services:
+//! - Browsing emails captured by the in-memory dev mailbox
+//!
+//! # Module Structure
+//!
+//! - [`handlers`] - HTTP handler functions for development endpoints
+//! - [`payloads`] - Response data structures
+
+pub mod handlers;
+pub mod payloads;
+
+// Re-export handlers at module level for easy route registration
+pub use handlers::{list_mailbox_messages, show_mailbox_message, show_mailbox_message_html};
@@ -0,0 +1,52 @@
+//! Response payloads for development endpoints.
+//!
+//! This module contains the data structures serialized by the dev mailbox
+//! handlers.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::services::dev_mailbox::MailboxMessage;
+
+/// Summary of a captured email shown in mailbox listings.
+#[derive(Debug, Serialize)]
+pub struct MailboxMessageSummary {
+    /// Unique message identifier.
+    pub id: Uuid,
+    /// Recipient email address.
+    pub to: String,
+    /// Email subject line.
+    pub subject: String,
+    /// Timestamp when the message was captured.
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<MailboxMessage> for MailboxMessageSummary {
+    fn from(message: MailboxMessage) -> Self {
+        Self {
+            id: message.id,
+            to: message.to,
+            subject: message.subject,
+            created_at: message.created_at,
+        }
+    }
+}
+
+/// Response body listing captured emails.
+///
+/// See [`list_mailbox_messages`](super::handlers::list_mailbox_messages) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct MailboxListResponse {
+    /// Captured messages, newest first.
+    pub messages: Vec<MailboxMessageSummary>,
+}
+
+/// Response body containing a single captured email.
+///
+/// See [`show_mailbox_message`](super::handlers::show_mailbox_message) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct MailboxMessageResponse {
+    /// The captured message including its rendered HTML body.
+    pub message: MailboxMessage,
+}
@@ -2,8 +2,16 @@
 //!
 //! This module organizes all route handlers by domain:
 //!
+//! - [`api_keys`] - API key management routes (create, list, revoke)
 //! - [`auth`] - Authentication routes (sign-up, login, logout, password reset, email change)
+//! - [`dev`] - Development-only routes (dev mailbox)
 //! - [`health`] - Health check endpoint for monitoring
+//! - [`oauth`] - OAuth2 token, device authorization, introspection, and revocation endpoints
+//! - [`service_accounts`] - Service account management routes (create, list, revoke)
 
+pub mod api_keys;
 pub mod auth;
+pub mod dev;
 pub mod health;
+pub mod oauth;
+pub mod service_accounts;
@@ -0,0 +1,129 @@
+//! OAuth2 error responses (RFC 6749 section 5.2, RFC 7009 section 2.2.1, and
+//! RFC 8628 section 3.5).
+
+use actix_web::{HttpResponse, ResponseError, http::StatusCode, http::header};
+use serde_json::json;
+use std::fmt;
+
+use crate::core::error::ApiError;
+
+/// Result type returned by OAuth2 handlers.
+pub type OAuthResult<T> = Result<T, OAuthError>;
+
+/// OAuth2 error variants exposed by the token, device authorization,
+/// introspection, and revocation endpoints.
+#[derive(Debug)]
+pub enum OAuthError {
+    /// Request is missing a parameter or is otherwise malformed.
+    InvalidRequest(String),
+    /// Client authentication failed.
+    InvalidClient,
+    /// The authenticated client may not perform this request.
+    UnauthorizedClient(String),
+    /// Requested scope is unknown or exceeds the scopes granted to the client.
+    InvalidScope(String),
+    /// Grant type is not supported by the server.
+    UnsupportedGrantType,
+    /// Device code or other grant is unknown, already used, or bound to another client.
+    InvalidGrant(String),
+    /// Device authorization is still waiting for the user.
+    AuthorizationPending,
+    /// Device polled faster than its interval; the interval was increased.
+    SlowDown,
+    /// The user denied the device authorization.
+    AccessDenied,
+    /// The device code expired before it was approved and exchanged.
+    ExpiredToken,
+    /// The token cannot be revoked because it is self-contained.
+    UnsupportedTokenType,
+    /// Unexpected server-side failure.
+    ServerError(String),
+}
+
+impl OAuthError {
+    /// Returns the OAuth2 `error` code for the variant.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            OAuthError::InvalidRequest(_) => "invalid_request",
+            OAuthError::InvalidClient => "invalid_client",
+            OAuthError::UnauthorizedClient(_) => "unauthorized_client",
+            OAuthError::InvalidScope(_) => "invalid_scope",
+            OAuthError::UnsupportedGrantType => "unsupported_grant_type",
+            OAuthError::InvalidGrant(_) => "invalid_grant",
+            OAuthError::AuthorizationPending => "authorization_pending",
+            OAuthError::SlowDown => "slow_down",
+            OAuthError::AccessDenied => "access_denied",
+            OAuthError::ExpiredToken => "expired_token",
+            OAuthError::UnsupportedTokenType => "unsupported_token_type",
+            OAuthError::ServerError(_) => "server_error",
+        }
+    }
+}
+
+impl fmt::Display for OAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OAuthError::InvalidRequest(msg) => write!(f, "{}", msg),
+            OAuthError::InvalidClient => write!(f, "Client authentication failed"),
+            OAuthError::UnauthorizedClient(msg) => write!(f, "{}", msg),
+            OAuthError::InvalidScope(scope) => write!(f, "Scope is not allowed: {}", scope),
+            OAuthError::UnsupportedGrantType => write!(f, "Grant type is not supported"),
+            OAuthError::InvalidGrant(msg) => write!(f, "{}", msg),
+            OAuthError::AuthorizationPending => {
+                write!(f, "The authorization request is still pending")
+            }
+            OAuthError::SlowDown => write!(f, "Polling too frequently; slow down"),
+            OAuthError::AccessDenied => write!(f, "The authorization request was denied"),
+            OAuthError::ExpiredToken => write!(f, "The device code has expired"),
+            OAuthError::UnsupportedTokenType => {
+                write!(f, "Revocation is not supported for this token type")
+            }
+            OAuthError::ServerError(_) => write!(f, "The server encountered an error"),
+        }
+    }
+}
+
+impl ResponseError for OAuthError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            OAuthError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            OAuthError::InvalidClient => StatusCode::UNAUTHORIZED,
+            OAuthError::UnauthorizedClient(_) => StatusCode::FORBIDDEN,
+            OAuthError::InvalidScope(_) => StatusCode::BAD_REQUEST,
+            OAuthError::UnsupportedGrantType => StatusCode::BAD_REQUEST,
+            OAuthError::InvalidGrant(_) => StatusCode::BAD_REQUEST,
+            OAuthError::AuthorizationPending => StatusCode::BAD_REQUEST,
+            OAuthError::SlowDown => StatusCode::BAD_REQUEST,
+            OAuthError::AccessDenied => StatusCode::BAD_REQUEST,
+            OAuthError::ExpiredToken => StatusCode::BAD_REQUEST,
+            OAuthError::UnsupportedTokenType => StatusCode::BAD_REQUEST,
+            OAuthError::ServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        response.insert_header((header::CACHE_CONTROL, "no-store"));
+
+        if matches!(self, OAuthError::InvalidClient) {
+            response.insert_header((header::WWW_AUTHENTICATE, "Basic realm=\"oauth\""));
+        }
+
+        response.json(json!({
+            "error": self.error_code(),
+            "error_description": self.to_string()
+        }))
+    }
+}
+
+impl From<sqlx::Error> for OAuthError {
+    fn from(err: sqlx::Error) -> Self {
+        OAuthError::ServerError(err.to_string())
+    }
+}
+
+impl From<ApiError> for OAuthError {
+    fn from(err: ApiError) -> Self {
+        OAuthError::ServerError(err.to_string())
+    }
+}
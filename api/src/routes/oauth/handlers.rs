@@ -0,0 +1,921 @@
+//! HTTP handler functions for OAuth2 endpoints.
+//!
+//! Device approval and denial are called by the browser frontend with a
+//! session, so they use the API's JSON error envelope instead of OAuth2
+//! errors.
+
+use actix_web::{HttpRequest, HttpResponse, http::header, post, web};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::auth::device_codes::{
+    format_user_code, generate_device_code, generate_user_code, hash_device_code,
+    normalize_user_code,
+};
+use crate::auth::jwt::{
+    AccessTokenClaims, RefreshTokenClaims, ServiceTokenClaims, create_service_token,
+    decode_access_token, decode_refresh_token, decode_service_token,
+};
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::scopes::Scope;
+use crate::auth::secrets::verify_secret;
+use crate::core::app_state::AppState;
+use crate::core::error::{ApiError, ApiResult};
+use crate::extractors::ValidatedJson;
+use crate::models::device_authorization::DeviceAuthorizationStatus;
+use crate::repository::auth::AuthRepo;
+use crate::repository::device_authorizations::DeviceAuthorizationRepo;
+use crate::repository::service_accounts::{ServiceAccountForAuth, ServiceAccountRepo};
+use crate::routes::auth::handlers::create_session_tokens;
+
+use super::errors::{OAuthError, OAuthResult};
+use super::payloads::{
+    DeviceAuthorizationRequest, DeviceAuthorizationResponse, DeviceDecisionRequest,
+    DeviceDecisionResponse, IntrospectionResponse, TokenHintRequest, TokenRequest, TokenResponse,
+};
+
+/// Grant type URN of the device authorization grant (RFC 8628 section 3.4).
+pub const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Seconds added to a device's polling interval each time it polls too quickly.
+const SLOW_DOWN_INCREMENT_SECONDS: i32 = 5;
+
+/// Longest accepted device `client_id`.
+const MAX_DEVICE_CLIENT_ID_LENGTH: usize = 100;
+
+/// Issues tokens for the OAuth2 `client_credentials` and device code grants.
+///
+/// - `client_credentials` - Service accounts authenticate with HTTP Basic
+///   (`client_id:client_secret`) or with `client_id` and `client_secret` form
+///   fields, but not both, and receive a service token. Service tokens carry
+///   the `service` token type, so they are never accepted where a user
+///   session is required.
+/// - `urn:ietf:params:oauth:grant-type:device_code` - Devices poll with the
+///   `device_code` and `client_id` from
+///   [`request_device_authorization`] and, once a user approves, receive a
+///   session access token and a refresh token stored in `refresh_tokens`.
+///
+/// # Route
+///
+/// `POST /oauth/token` (`application/x-www-form-urlencoded`)
+///
+/// # Request Body ([`TokenRequest`])
+///
+/// - `grant_type` - `client_credentials` or `urn:ietf:params:oauth:grant-type:device_code`
+/// - `client_id` - Client ID (when not using HTTP Basic, and always for the device code grant)
+/// - `client_secret` - Client secret (when not using HTTP Basic)
+/// - `scope` - Optional space-delimited scopes; defaults to all scopes granted to the client
+/// - `device_code` - Device code (device code grant only)
+///
+/// # Response Body ([`TokenResponse`])
+///
+/// - `access_token` - Signed service or session access token
+/// - `token_type` - Always `Bearer`
+/// - `expires_in` - Access token lifetime in seconds
+/// - `refresh_token` - Refresh token (device code grant only)
+/// - `scope` - Space-delimited scopes granted to the token (`client_credentials` only)
+///
+/// # Errors
+///
+/// - `invalid_request` - If a parameter is missing or credentials are sent twice
+/// - `unsupported_grant_type` - If `grant_type` is not supported
+/// - `invalid_client` - If the client is unknown, revoked, or the secret is wrong
+/// - `invalid_scope` - If a requested scope was not granted to the client
+/// - `invalid_grant` - If the device code is unknown, used, or bound to another client
+/// - `authorization_pending` - If the user has not approved the device yet
+/// - `slow_down` - If the device polled before its interval elapsed
+/// - `access_denied` - If the user denied the device
+/// - `expired_token` - If the device code expired
+#[post("/oauth/token")]
+pub async fn issue_token(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: web::Form<TokenRequest>,
+) -> OAuthResult<HttpResponse> {
+    let body = body.into_inner();
+
+    let response = match body.grant_type.as_deref() {
+        Some("client_credentials") => client_credentials_grant(&state, &req, &body).await?,
+        Some(DEVICE_CODE_GRANT_TYPE) => device_code_grant(&state, &body).await?,
+        Some(_) => return Err(OAuthError::UnsupportedGrantType),
+        None => {
+            return Err(OAuthError::InvalidRequest(
+                "Missing grant_type parameter".to_string(),
+            ));
+        }
+    };
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .json(response))
+}
+
+/// Starts a device authorization for a CLI or other input-constrained client.
+///
+/// The device shows `user_code` and `verification_uri` to the user, then
+/// polls [`issue_token`] with the `device_code` every `interval` seconds.
+///
+/// # Route
+///
+/// `POST /oauth/device/code` (`application/x-www-form-urlencoded`)
+///
+/// # Request Body ([`DeviceAuthorizationRequest`])
+///
+/// - `client_id` - Identifier of the device client, such as `cli`
+///
+/// # Response Body ([`DeviceAuthorizationResponse`])
+///
+/// - `device_code` - Secret code the device polls with
+/// - `user_code` - Code the user enters, formatted as `XXXX-XXXX`
+/// - `verification_uri` - Page where the user enters the code
+/// - `verification_uri_complete` - Verification page with the code prefilled
+/// - `expires_in` - Lifetime of both codes in seconds
+/// - `interval` - Minimum number of seconds between polls
+///
+/// # Errors
+///
+/// - `invalid_request` - If `client_id` is missing or too long
+#[post("/oauth/device/code")]
+pub async fn request_device_authorization(
+    state: web::Data<AppState>,
+    body: web::Form<DeviceAuthorizationRequest>,
+) -> OAuthResult<HttpResponse> {
+    let client_id = body
+        .client_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|client_id| !client_id.is_empty())
+        .ok_or_else(|| OAuthError::InvalidRequest("Missing client_id parameter".to_string()))?;
+    if client_id.len() > MAX_DEVICE_CLIENT_ID_LENGTH {
+        return Err(OAuthError::InvalidRequest(format!(
+            "client_id must not exceed {MAX_DEVICE_CLIENT_ID_LENGTH} characters"
+        )));
+    }
+
+    let device_code = generate_device_code();
+    let user_code = generate_user_code();
+    let expires_at = Utc::now() + Duration::seconds(state.env.device_code_expiry_seconds as i64);
+
+    DeviceAuthorizationRepo::create_device_authorization(
+        &state.pool,
+        &hash_device_code(&device_code),
+        &user_code,
+        client_id,
+        state.env.device_code_poll_interval_seconds as i32,
+        expires_at,
+    )
+    .await?;
+
+    let display_code = format_user_code(&user_code);
+    let verification_uri = state.env.device_verification_uri.clone();
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .json(DeviceAuthorizationResponse {
+            device_code,
+            verification_uri_complete: format!("{verification_uri}?user_code={display_code}"),
+            user_code: display_code,
+            verification_uri,
+            expires_in: state.env.device_code_expiry_seconds,
+            interval: state.env.device_code_poll_interval_seconds,
+        }))
+}
+
+/// Reports whether a token is active (RFC 7662).
+///
+/// Resource servers authenticate as a service account, like the
+/// `client_credentials` grant. Only clients listed in
+/// `OAUTH_TRUSTED_CLIENT_IDS` may introspect, since any user can create a
+/// service account and the response reveals the token's owner. Access,
+/// refresh, and service tokens are all
+/// recognized; `token_type_hint` is accepted but not needed. Refresh tokens
+/// are active only while their `refresh_tokens` row is unrevoked, and
+/// service tokens only while their service account is.
+///
+/// # Route
+///
+/// `POST /oauth/introspect` (`application/x-www-form-urlencoded`)
+///
+/// # Request Body ([`TokenHintRequest`])
+///
+/// - `token` - Token to introspect
+/// - `token_type_hint` - Optional `access_token` or `refresh_token`
+/// - `client_id` - Client ID (when not using HTTP Basic)
+/// - `client_secret` - Client secret (when not using HTTP Basic)
+///
+/// # Response Body ([`IntrospectionResponse`])
+///
+/// - `active` - Whether the token is currently valid; other fields are omitted when `false`
+/// - `sub` - User ID, or service account ID for service tokens
+/// - `exp` - Expiration timestamp (Unix epoch seconds)
+/// - `iat` - Issued-at timestamp (Unix epoch seconds)
+/// - `scope` - Space-delimited scopes the token grants
+/// - `token_type` - `access_token` or `refresh_token`
+/// - `client_id` - Service account client ID (service tokens only)
+/// - `username` - User email (access tokens only)
+///
+/// # Errors
+///
+/// - `invalid_request` - If `token` is missing or client credentials are malformed
+/// - `invalid_client` - If client authentication fails
+/// - `unauthorized_client` - If the client is not trusted to introspect tokens
+#[post("/oauth/introspect")]
+pub async fn introspect_token(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: web::Form<TokenHintRequest>,
+) -> OAuthResult<HttpResponse> {
+    let body = body.into_inner();
+    let client = authenticate_client(
+        &state,
+        &req,
+        body.client_id.as_deref(),
+        body.client_secret.as_deref(),
+    )
+    .await?;
+    if !is_trusted_client(&state, &client) {
+        return Err(OAuthError::UnauthorizedClient(
+            "Client is not allowed to introspect tokens".to_string(),
+        ));
+    }
+    let token = required_token(&body)?;
+
+    let response = match decode_any_token(&state, token) {
+        Some(DecodedToken::Access(claims)) => introspect_access_token(&state, claims).await?,
+        Some(DecodedToken::Service(claims)) => introspect_service_token(&state, claims).await?,
+        Some(DecodedToken::Refresh(claims)) => introspect_refresh_token(&state, claims).await?,
+        None => IntrospectionResponse::inactive(),
+    };
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .json(response))
+}
+
+/// Revokes a token (RFC 7009).
+///
+/// Clients authenticate as a service account and may only revoke tokens of
+/// the user who owns the account, unless they are listed in
+/// `OAUTH_TRUSTED_CLIENT_IDS`. Refresh tokens are revoked in
+/// `refresh_tokens`, ending the session at its next refresh. Access tokens are
+/// added to the access token denylist and stop working immediately. Unknown,
+/// expired, or already revoked tokens are answered with success, as the RFC
+/// requires.
+///
+/// # Route
+///
+/// `POST /oauth/revoke` (`application/x-www-form-urlencoded`)
+///
+/// # Request Body ([`TokenHintRequest`])
+///
+/// - `token` - Token to revoke
+/// - `token_type_hint` - Optional `access_token` or `refresh_token`
+/// - `client_id` - Client ID (when not using HTTP Basic)
+/// - `client_secret` - Client secret (when not using HTTP Basic)
+///
+/// # Response Body
+///
+/// Empty on success.
+///
+/// # Errors
+///
+/// - `invalid_request` - If `token` is missing or client credentials are malformed
+/// - `invalid_client` - If client authentication fails
+/// - `unauthorized_client` - If the token belongs to a user other than the client's owner
+/// - `unsupported_token_type` - If the token is a self-contained service token
+#[post("/oauth/revoke")]
+pub async fn revoke_token(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: web::Form<TokenHintRequest>,
+) -> OAuthResult<HttpResponse> {
+    let body = body.into_inner();
+    let client = authenticate_client(
+        &state,
+        &req,
+        body.client_id.as_deref(),
+        body.client_secret.as_deref(),
+    )
+    .await?;
+    let token = required_token(&body)?;
+
+    match decode_any_token(&state, token) {
+        Some(DecodedToken::Refresh(claims)) => {
+            if let Ok(user_id) = Uuid::parse_str(&claims.sub) {
+                require_token_owner(&state, &client, user_id)?;
+                AuthRepo::revoke_refresh_token(&state.pool, &hash_refresh_token_jti(&claims.jti))
+                    .await?;
+            }
+        }
+        Some(DecodedToken::Access(claims)) => {
+            if let Ok(user_id) = Uuid::parse_str(&claims.sub) {
+                require_token_owner(&state, &client, user_id)?;
+                state
+                    .access_token_revocations
+                    .revoke_token(&state.pool, user_id, &claims)
+                    .await?;
+            }
+        }
+        Some(DecodedToken::Service(_)) => {
+            return Err(OAuthError::UnsupportedTokenType);
+        }
+        None => {}
+    }
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .finish())
+}
+
+/// Approves a pending device authorization for the authenticated user.
+///
+/// The device's next poll receives a session for this user.
+///
+/// # Route
+///
+/// `POST /auth/device/approve`
+///
+/// # Request Body ([`DeviceDecisionRequest`])
+///
+/// - `user_code` - Code shown on the device
+///
+/// # Response Body ([`DeviceDecisionResponse`])
+///
+/// - `message` - Success message
+/// - `client_id` - Client identifier of the approved device
+///
+/// # Errors
+///
+/// - `Unauthorized` - If not authenticated with a session
+/// - `InvalidAuthCode` - If no pending, unexpired authorization matches the code
+#[post("/auth/device/approve")]
+pub async fn approve_device(
+    state: web::Data<AppState>,
+    auth_user: AuthenticatedUser,
+    body: ValidatedJson<DeviceDecisionRequest>,
+) -> ApiResult<HttpResponse> {
+    let client_id = decide_device(
+        &state,
+        auth_user,
+        &body.into_inner().user_code,
+        DeviceAuthorizationStatus::Approved,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(DeviceDecisionResponse {
+        message: "Device approved.".to_string(),
+        client_id,
+    }))
+}
+
+/// Denies a pending device authorization.
+///
+/// The device's next poll receives `access_denied`.
+///
+/// # Route
+///
+/// `POST /auth/device/deny`
+///
+/// # Request Body ([`DeviceDecisionRequest`])
+///
+/// - `user_code` - Code shown on the device
+///
+/// # Response Body ([`DeviceDecisionResponse`])
+///
+/// - `message` - Success message
+/// - `client_id` - Client identifier of the denied device
+///
+/// # Errors
+///
+/// - `Unauthorized` - If not authenticated with a session
+/// - `InvalidAuthCode` - If no pending, unexpired authorization matches the code
+#[post("/auth/device/deny")]
+pub async fn deny_device(
+    state: web::Data<AppState>,
+    auth_user: AuthenticatedUser,
+    body: ValidatedJson<DeviceDecisionRequest>,
+) -> ApiResult<HttpResponse> {
+    let client_id = decide_device(
+        &state,
+        auth_user,
+        &body.into_inner().user_code,
+        DeviceAuthorizationStatus::Denied,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(DeviceDecisionResponse {
+        message: "Device denied.".to_string(),
+        client_id,
+    }))
+}
+
+/// Records the user's decision on the device authorization matching `user_code`.
+///
+/// # Arguments
+///
+/// - `state` - Application state with the database pool
+/// - `auth_user` - User deciding on the request
+/// - `user_code` - Code as typed by the user
+/// - `status` - Decision to record
+///
+/// # Errors
+///
+/// - `InvalidAuthCode` - If no pending, unexpired authorization matches the code
+async fn decide_device(
+    state: &AppState,
+    auth_user: AuthenticatedUser,
+    user_code: &str,
+    status: DeviceAuthorizationStatus,
+) -> ApiResult<String> {
+    let user_code = normalize_user_code(user_code);
+
+    DeviceAuthorizationRepo::decide_device_authorization(
+        &state.pool,
+        &user_code,
+        auth_user.user_id,
+        status,
+    )
+    .await?
+    .ok_or(ApiError::InvalidAuthCode)
+}
+
+/// Issues a service token for an authenticated service account.
+///
+/// # Arguments
+///
+/// - `state` - Application state with configuration and database pool
+/// - `req` - Incoming HTTP request
+/// - `body` - Parsed token request form
+///
+/// # Errors
+///
+/// Returns the client authentication and scope errors of [`issue_token`].
+async fn client_credentials_grant(
+    state: &AppState,
+    req: &HttpRequest,
+    body: &TokenRequest,
+) -> OAuthResult<TokenResponse> {
+    let service_account = authenticate_client(
+        state,
+        req,
+        body.client_id.as_deref(),
+        body.client_secret.as_deref(),
+    )
+    .await?;
+    let scopes = granted_scopes(&service_account.scopes, body.scope.as_deref())?;
+
+    let access_token = create_service_token(
+        service_account.id,
+        &service_account.client_id,
+        &scopes,
+        &state.env.jwt_secret,
+        state.env.jwt_service_token_expiry_seconds,
+    )?;
+
+    ServiceAccountRepo::touch_service_account(&state.pool, service_account.id).await?;
+
+    Ok(TokenResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: state.env.jwt_service_token_expiry_seconds,
+        refresh_token: None,
+        scope: Some(scopes.join(" ")),
+    })
+}
+
+/// Exchanges an approved device code for a user session.
+///
+/// Every poll is recorded; polling again before the interval has elapsed
+/// returns `slow_down` and lengthens the interval.
+///
+/// # Arguments
+///
+/// - `state` - Application state with configuration and database pool
+/// - `body` - Parsed token request form
+///
+/// # Errors
+///
+/// Returns the device code errors of [`issue_token`].
+async fn device_code_grant(state: &AppState, body: &TokenRequest) -> OAuthResult<TokenResponse> {
+    let (Some(device_code), Some(client_id)) = (&body.device_code, &body.client_id) else {
+        return Err(OAuthError::InvalidRequest(
+            "Missing device_code or client_id parameter".to_string(),
+        ));
+    };
+
+    let poll = DeviceAuthorizationRepo::poll_device_authorization(
+        &state.pool,
+        &hash_device_code(device_code),
+    )
+    .await?
+    .filter(|poll| &poll.client_id == client_id)
+    .ok_or_else(|| OAuthError::InvalidGrant("Invalid device code".to_string()))?;
+
+    let now = Utc::now();
+    if poll.expires_at <= now {
+        return Err(OAuthError::ExpiredToken);
+    }
+
+    let polled_too_soon = poll.previous_polled_at.is_some_and(|previous| {
+        now - previous < Duration::seconds(i64::from(poll.interval_seconds))
+    });
+    if polled_too_soon && poll.status == DeviceAuthorizationStatus::Pending {
+        DeviceAuthorizationRepo::slow_down_device_authorization(
+            &state.pool,
+            poll.id,
+            SLOW_DOWN_INCREMENT_SECONDS,
+        )
+        .await?;
+        return Err(OAuthError::SlowDown);
+    }
+
+    match poll.status {
+        DeviceAuthorizationStatus::Pending => return Err(OAuthError::AuthorizationPending),
+        DeviceAuthorizationStatus::Denied => return Err(OAuthError::AccessDenied),
+        DeviceAuthorizationStatus::Consumed => {
+            return Err(OAuthError::InvalidGrant(
+                "Device code has already been used".to_string(),
+            ));
+        }
+        DeviceAuthorizationStatus::Approved => {}
+    }
+
+    let user_id =
+        DeviceAuthorizationRepo::consume_approved_device_authorization(&state.pool, poll.id)
+            .await?
+            .ok_or_else(|| {
+                OAuthError::InvalidGrant("Device code has already been used".to_string())
+            })?;
+    let user = AuthRepo::find_user_for_token_refresh(&state.pool, user_id)
+        .await?
+        .ok_or(OAuthError::AccessDenied)?;
+
+    // Device sessions behave like "remember me" sessions: CLIs keep them on disk.
+    let tokens = create_session_tokens(state, user.id, &user.email, true).await?;
+
+    Ok(TokenResponse {
+        access_token: tokens.access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: state.env.jwt_access_token_expiry_seconds,
+        refresh_token: Some(tokens.refresh_token),
+        scope: None,
+    })
+}
+
+/// Authenticates the client from HTTP Basic credentials or form fields.
+///
+/// # Arguments
+///
+/// - `state` - Application state with the database pool
+/// - `req` - Incoming HTTP request
+/// - `client_id` - `client_id` form field, if any
+/// - `client_secret` - `client_secret` form field, if any
+///
+/// # Errors
+///
+/// - `invalid_request` - If credentials are missing, malformed, or sent with both methods
+/// - `invalid_client` - If the client is unknown, revoked, or the secret is wrong
+async fn authenticate_client(
+    state: &AppState,
+    req: &HttpRequest,
+    client_id: Option<&str>,
+    client_secret: Option<&str>,
+) -> OAuthResult<ServiceAccountForAuth> {
+    let basic = basic_credentials(req)?;
+    let form = match (client_id, client_secret) {
+        (Some(client_id), Some(client_secret)) => {
+            Some((client_id.to_string(), client_secret.to_string()))
+        }
+        (None, None) => None,
+        _ => {
+            return Err(OAuthError::InvalidRequest(
+                "client_id and client_secret must be sent together".to_string(),
+            ));
+        }
+    };
+
+    let (client_id, client_secret) = match (basic, form) {
+        (Some(credentials), None) | (None, Some(credentials)) => credentials,
+        (Some(_), Some(_)) => {
+            return Err(OAuthError::InvalidRequest(
+                "Use only one client authentication method".to_string(),
+            ));
+        }
+        (None, None) => return Err(OAuthError::InvalidClient),
+    };
+
+    let service_account =
+        ServiceAccountRepo::find_active_service_account_by_client_id(&state.pool, &client_id)
+            .await?
+            .ok_or(OAuthError::InvalidClient)?;
+
+    if !verify_secret(&client_secret, &service_account.client_secret_hash) {
+        return Err(OAuthError::InvalidClient);
+    }
+
+    Ok(service_account)
+}
+
+/// Returns whether the client is listed in `OAUTH_TRUSTED_CLIENT_IDS`.
+///
+/// # Arguments
+///
+/// - `state` - Application state with configuration
+/// - `client` - Authenticated service account
+fn is_trusted_client(state: &AppState, client: &ServiceAccountForAuth) -> bool {
+    state
+        .env
+        .oauth_trusted_client_ids
+        .iter()
+        .any(|client_id| client_id == &client.client_id)
+}
+
+/// Checks that the client may revoke a token issued to `user_id`.
+///
+/// Service accounts act for the user who owns them, so only that user's
+/// tokens count as issued to the client. Trusted clients may revoke any
+/// user's tokens.
+///
+/// # Arguments
+///
+/// - `state` - Application state with configuration
+/// - `client` - Authenticated service account
+/// - `user_id` - User the token was issued to
+///
+/// # Errors
+///
+/// Returns `unauthorized_client` when the token belongs to another user.
+fn require_token_owner(
+    state: &AppState,
+    client: &ServiceAccountForAuth,
+    user_id: Uuid,
+) -> OAuthResult<()> {
+    if client.owner_user_id == user_id || is_trusted_client(state, client) {
+        return Ok(());
+    }
+
+    Err(OAuthError::UnauthorizedClient(
+        "Token was not issued to this client".to_string(),
+    ))
+}
+
+/// A token that passed signature, expiry, and token type checks.
+enum DecodedToken {
+    /// User session access token.
+    Access(AccessTokenClaims),
+    /// Service account token.
+    Service(ServiceTokenClaims),
+    /// User session refresh token.
+    Refresh(RefreshTokenClaims),
+}
+
+/// Decodes a token of any type issued by this server.
+///
+/// Returns `None` when the token is malformed, expired, or not signed by this
+/// server.
+///
+/// # Arguments
+///
+/// - `state` - Application state with the JWT secret
+/// - `token` - Token presented by the client
+fn decode_any_token(state: &AppState, token: &str) -> Option<DecodedToken> {
+    let secret = &state.env.jwt_secret;
+
+    if let Ok(claims) = decode_access_token(token, secret) {
+        return Some(DecodedToken::Access(claims));
+    }
+    if let Ok(claims) = decode_service_token(token, secret) {
+        return Some(DecodedToken::Service(claims));
+    }
+    decode_refresh_token(token, secret)
+        .ok()
+        .map(DecodedToken::Refresh)
+}
+
+/// Builds the introspection response for a user access token.
+///
+/// Revoked and outdated tokens, and tokens of deleted users, are inactive.
+///
+/// # Arguments
+///
+/// - `state` - Application state with the database pool
+/// - `claims` - Decoded access token claims
+///
+/// # Errors
+///
+/// Returns `server_error` if the token version lookup fails.
+async fn introspect_access_token(
+    state: &AppState,
+    claims: AccessTokenClaims,
+) -> OAuthResult<IntrospectionResponse> {
+    let Ok(user_id) = Uuid::parse_str(&claims.sub) else {
+        return Ok(IntrospectionResponse::inactive());
+    };
+    if state.access_token_revocations.is_revoked(&claims) {
+        return Ok(IntrospectionResponse::inactive());
+    }
+    if state.token_versions.current(&state.pool, user_id).await? != Some(claims.token_version) {
+        return Ok(IntrospectionResponse::inactive());
+    }
+
+    Ok(IntrospectionResponse {
+        active: true,
+        sub: Some(claims.sub),
+        exp: Some(claims.exp),
+        iat: Some(claims.iat),
+        scope: Some(session_scope()),
+        token_type: Some("access_token".to_string()),
+        client_id: None,
+        username: Some(claims.email),
+    })
+}
+
+/// Builds the introspection response for a service token.
+///
+/// # Arguments
+///
+/// - `state` - Application state with the database pool
+/// - `claims` - Decoded service token claims
+///
+/// # Errors
+///
+/// Returns `server_error` if the service account lookup fails.
+async fn introspect_service_token(
+    state: &AppState,
+    claims: ServiceTokenClaims,
+) -> OAuthResult<IntrospectionResponse> {
+    let Ok(service_account_id) = Uuid::parse_str(&claims.sub) else {
+        return Ok(IntrospectionResponse::inactive());
+    };
+    if !ServiceAccountRepo::is_service_account_active(&state.pool, service_account_id).await? {
+        return Ok(IntrospectionResponse::inactive());
+    }
+
+    Ok(IntrospectionResponse {
+        active: true,
+        sub: Some(claims.sub),
+        exp: Some(claims.exp),
+        iat: Some(claims.iat),
+        scope: Some(claims.scope),
+        token_type: Some("access_token".to_string()),
+        client_id: Some(claims.client_id),
+        username: None,
+    })
+}
+
+/// Builds the introspection response for a user refresh token.
+///
+/// # Arguments
+///
+/// - `state` - Application state with the database pool
+/// - `claims` - Decoded refresh token claims
+///
+/// # Errors
+///
+/// Returns `server_error` if the refresh token lookup fails.
+async fn introspect_refresh_token(
+    state: &AppState,
+    claims: RefreshTokenClaims,
+) -> OAuthResult<IntrospectionResponse> {
+    let Ok(user_id) = Uuid::parse_str(&claims.sub) else {
+        return Ok(IntrospectionResponse::inactive());
+    };
+    let token_hash = hash_refresh_token_jti(&claims.jti);
+    if !AuthRepo::is_refresh_token_active(&state.pool, user_id, &token_hash).await? {
+        return Ok(IntrospectionResponse::inactive());
+    }
+
+    Ok(IntrospectionResponse {
+        active: true,
+        sub: Some(claims.sub),
+        exp: Some(claims.exp),
+        iat: Some(claims.iat),
+        scope: Some(session_scope()),
+        token_type: Some("refresh_token".to_string()),
+        client_id: None,
+        username: None,
+    })
+}
+
+/// Returns the space-delimited scopes held by every user session.
+fn session_scope() -> String {
+    Scope::ALL
+        .iter()
+        .map(|scope| scope.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Hashes a refresh token `jti` the way it is stored in `refresh_tokens`.
+///
+/// # Arguments
+///
+/// - `jti` - Refresh token identifier
+fn hash_refresh_token_jti(jti: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(jti.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Returns the non-empty `token` parameter of an introspection or revocation request.
+///
+/// # Arguments
+///
+/// - `body` - Parsed request form
+///
+/// # Errors
+///
+/// Returns `invalid_request` when `token` is missing or empty.
+fn required_token(body: &TokenHintRequest) -> OAuthResult<&str> {
+    body.token
+        .as_deref()
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| OAuthError::InvalidRequest("Missing token parameter".to_string()))
+}
+
+/// Reads `client_id:client_secret` from an HTTP Basic `Authorization` header.
+///
+/// Returns `None` when the request has no Basic credentials.
+///
+/// # Arguments
+///
+/// - `req` - Incoming HTTP request
+///
+/// # Errors
+///
+/// Returns `invalid_request` when the Basic credentials cannot be decoded.
+fn basic_credentials(req: &HttpRequest) -> OAuthResult<Option<(String, String)>> {
+    let Some(value) = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return Ok(None);
+    };
+
+    let Some((scheme, encoded)) = value.trim().split_once(' ') else {
+        return Ok(None);
+    };
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return Ok(None);
+    }
+
+    let malformed = || OAuthError::InvalidRequest("Malformed Basic credentials".to_string());
+    let decoded = STANDARD.decode(encoded.trim()).map_err(|_| malformed())?;
+    let decoded = String::from_utf8(decoded).map_err(|_| malformed())?;
+    let (client_id, client_secret) = decoded.split_once(':').ok_or_else(malformed)?;
+
+    Ok(Some((client_id.to_string(), client_secret.to_string())))
+}
+
+/// Resolves the scopes to grant from the client's scopes and the requested scopes.
+///
+/// # Arguments
+///
+/// - `allowed` - Scopes granted to the service account
+/// - `requested` - Space-delimited `scope` parameter, if any
+///
+/// # Errors
+///
+/// Returns `invalid_scope` when a requested scope was not granted to the client.
+fn granted_scopes(allowed: &[String], requested: Option<&str>) -> OAuthResult<Vec<String>> {
+    let Some(requested) = requested.filter(|scope| !scope.trim().is_empty()) else {
+        return Ok(allowed.to_vec());
+    };
+
+    let mut scopes = Vec::new();
+    for scope in requested.split_whitespace() {
+        if !allowed.iter().any(|allowed| allowed == scope) {
+            return Err(OAuthError::InvalidScope(scope.to_string()));
+        }
+        scopes.push(scope.to_string());
+    }
+    scopes.sort();
+    scopes.dedup();
+
+    Ok(scopes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::granted_scopes;
+    use crate::routes::oauth::OAuthError;
+
+    #[test]
+    // Verifies omitted scopes default to the client's scopes and extra scopes are rejected.
+    fn granted_scopes_are_limited_to_the_client() {
+        let allowed = vec!["profile:read".to_string(), "profile:write".to_string()];
+
+        assert_eq!(granted_scopes(&allowed, None).expect("defaults"), allowed);
+        assert_eq!(
+            granted_scopes(&allowed, Some("profile:read profile:read")).expect("subset"),
+            vec!["profile:read".to_string()]
+        );
+        assert!(matches!(
+            granted_scopes(&["profile:read".to_string()], Some("profile:write")),
+            Err(OAuthError::InvalidScope(scope)) if scope == "profile:write"
+        ));
+    }
+}
@@ -0,0 +1,32 @@
+//! OAuth2 endpoints for non-human clients.
+//!
+//! This module implements OAuth2 grants for clients that cannot use the
+//! browser cookie flow:
+//! - `client_credentials` grant for service accounts (RFC 6749 section 4.4)
+//! - Device authorization grant for CLIs (RFC 8628), including the session
+//!   routes where a user approves or denies a device
+//! - Token introspection (RFC 7662) for trusted resource servers and
+//!   revocation (RFC 7009) for service accounts acting for their owner
+//!
+//! Requests are form-encoded and errors use the OAuth2 error format
+//! (`error`, `error_description`) rather than the API's JSON error envelope,
+//! so standard OAuth2 client libraries work unchanged.
+//!
+//! # Module Structure
+//!
+//! - [`errors`] - OAuth2 error responses
+//! - [`handlers`] - HTTP handler functions for OAuth2 endpoints
+//! - [`payloads`] - Request and response data structures
+
+pub mod errors;
+pub mod handlers;
+pub mod payloads;
+
+// Re-export handlers at module level for easy route registration
+pub use handlers::{
+    approve_device, deny_device, introspect_token, issue_token, request_device_authorization,
+    revoke_token,
+};
+
+// Re-export types that are used by other modules
+pub use errors::{OAuthError, OAuthResult};
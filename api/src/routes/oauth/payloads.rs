@@ -0,0 +1,161 @@
+//! Request and response payloads for OAuth2 endpoints.
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Form body for the token endpoint.
+///
+/// Every field is optional so missing parameters are reported as OAuth2
+/// `invalid_request` errors instead of generic deserialization failures.
+///
+/// See [`issue_token`](super::handlers::issue_token) for the handler that processes this request.
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    /// Grant type: `client_credentials` or `urn:ietf:params:oauth:grant-type:device_code`.
+    pub grant_type: Option<String>,
+    /// Client ID, when not sent with HTTP Basic authentication, or the
+    /// device's client ID for the device code grant.
+    pub client_id: Option<String>,
+    /// Client secret, when not sent with HTTP Basic authentication.
+    pub client_secret: Option<String>,
+    /// Space-delimited scopes to request; defaults to every scope granted to the client.
+    pub scope: Option<String>,
+    /// Device code returned by the device authorization endpoint.
+    pub device_code: Option<String>,
+}
+
+/// Successful token endpoint response (RFC 6749 section 5.1).
+///
+/// See [`issue_token`](super::handlers::issue_token) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    /// Signed service token.
+    pub access_token: String,
+    /// How to present the token; always `Bearer`.
+    pub token_type: String,
+    /// Token lifetime in seconds.
+    pub expires_in: u64,
+    /// Refresh token for `POST /auth/refresh/token`, issued by the device code grant.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Space-delimited scopes granted to service tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+/// Form body for the device authorization endpoint.
+///
+/// See [`request_device_authorization`](super::handlers::request_device_authorization) for the handler that processes this request.
+#[derive(Debug, Deserialize)]
+pub struct DeviceAuthorizationRequest {
+    /// Identifier of the device client, such as `cli`; shown to the user on approval.
+    pub client_id: Option<String>,
+}
+
+/// Device authorization response (RFC 8628 section 3.2).
+///
+/// See [`request_device_authorization`](super::handlers::request_device_authorization) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct DeviceAuthorizationResponse {
+    /// Secret code the device polls the token endpoint with.
+    pub device_code: String,
+    /// Short code the user enters in the browser, formatted as `XXXX-XXXX`.
+    pub user_code: String,
+    /// Page where the user enters the user code.
+    pub verification_uri: String,
+    /// Verification page with the user code prefilled.
+    pub verification_uri_complete: String,
+    /// Lifetime of the device and user codes in seconds.
+    pub expires_in: u64,
+    /// Minimum number of seconds between token polls.
+    pub interval: u64,
+}
+
+/// Request body for approving or denying a device authorization.
+///
+/// See [`approve_device`](super::handlers::approve_device) and
+/// [`deny_device`](super::handlers::deny_device) for the handlers that process this request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct DeviceDecisionRequest {
+    /// User code shown on the device; case, spaces, and dashes are ignored.
+    #[validate(length(min = 1, max = 32, message = "User code is required"))]
+    pub user_code: String,
+}
+
+/// Response body for an approved or denied device authorization.
+///
+/// See [`approve_device`](super::handlers::approve_device) and
+/// [`deny_device`](super::handlers::deny_device) for the handlers that produce this response.
+#[derive(Debug, Serialize)]
+pub struct DeviceDecisionResponse {
+    /// Success message.
+    pub message: String,
+    /// Client identifier of the device that requested authorization.
+    pub client_id: String,
+}
+
+/// Form body for token introspection and revocation.
+///
+/// See [`introspect_token`](super::handlers::introspect_token) and
+/// [`revoke_token`](super::handlers::revoke_token) for the handlers that process this request.
+#[derive(Debug, Deserialize)]
+pub struct TokenHintRequest {
+    /// Token to introspect or revoke.
+    pub token: Option<String>,
+    /// Optional hint about the token type (`access_token` or `refresh_token`).
+    ///
+    /// Every token type is recognized without it.
+    pub token_type_hint: Option<String>,
+    /// Client ID, when not sent with HTTP Basic authentication.
+    pub client_id: Option<String>,
+    /// Client secret, when not sent with HTTP Basic authentication.
+    pub client_secret: Option<String>,
+}
+
+/// Token introspection response (RFC 7662 section 2.2).
+///
+/// See [`introspect_token`](super::handlers::introspect_token) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct IntrospectionResponse {
+    /// Whether the token is currently valid.
+    pub active: bool,
+    /// User ID, or service account ID for service tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// Expiration timestamp (Unix epoch seconds).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<usize>,
+    /// Issued-at timestamp (Unix epoch seconds).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<usize>,
+    /// Space-delimited scopes the token grants.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// `access_token` or `refresh_token`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    /// Service account client ID, for service tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// User email, for user access tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+}
+
+impl IntrospectionResponse {
+    /// Response for tokens that are invalid, expired, or revoked.
+    ///
+    /// RFC 7662 requires that nothing else is revealed about such tokens.
+    pub fn inactive() -> Self {
+        IntrospectionResponse {
+            active: false,
+            sub: None,
+            exp: None,
+            iat: None,
+            scope: None,
+            token_type: None,
+            client_id: None,
+            username: None,
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! HTTP handler functions for service account endpoints.
+//!
+//! Service accounts can only be managed from a session: neither API keys nor
+//! service tokens can create, list, or revoke them. Service tokens can only
+//! look up the account they were issued to.
+
+use actix_web::{HttpResponse, delete, get, post, web};
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::principal::ServiceAccountAuth;
+use crate::auth::service_accounts::generate_client_credentials;
+use crate::core::app_state::AppState;
+use crate::core::error::{ApiError, ApiResult};
+use crate::extractors::ValidatedJson;
+use crate::repository::service_accounts::ServiceAccountRepo;
+
+use super::payloads::{
+    CreateServiceAccountRequest, CreateServiceAccountResponse, CurrentServiceAccountResponse,
+    ListServiceAccountsResponse, RevokeServiceAccountResponse,
+};
+
+/// Creates a service account owned by the authenticated user.
+///
+/// The client secret is returned only in this response.
+///
+/// # Route
+///
+/// `POST /auth/service-accounts`
+///
+/// # Request Body ([`CreateServiceAccountRequest`])
+///
+/// - `name` - Human-readable label for the account
+/// - `scopes` - Scopes the account may request
+///
+/// # Response Body ([`CreateServiceAccountResponse`])
+///
+/// - `client_secret` - Client secret for the `client_credentials` grant
+/// - `service_account` - Stored account details (`id`, `name`, `client_id`, `scopes`, `last_used_at`, `created_at`)
+///
+/// # Errors
+///
+/// - `Unauthorized` - If not authenticated with a session
+/// - `InternalError` - If database operations fail
+#[post("/auth/service-accounts")]
+pub async fn create_service_account(
+    state: web::Data<AppState>,
+    auth_user: AuthenticatedUser,
+    body: ValidatedJson<CreateServiceAccountRequest>,
+) -> ApiResult<HttpResponse> {
+    let body = body.into_inner();
+
+    let mut scopes = body.scopes;
+    scopes.sort();
+    scopes.dedup();
+
+    let generated = generate_client_credentials();
+    let service_account = ServiceAccountRepo::create_service_account(
+        &state.pool,
+        auth_user.user_id,
+        body.name.trim(),
+        &generated.client_id,
+        &generated.client_secret_hash,
+        &scopes,
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(CreateServiceAccountResponse {
+        client_secret: generated.client_secret,
+        service_account,
+    }))
+}
+
+/// Lists the authenticated user's active service accounts.
+///
+/// # Route
+///
+/// `GET /auth/service-accounts`
+///
+/// # Response Body ([`ListServiceAccountsResponse`])
+///
+/// - `service_accounts` - Non-revoked accounts, newest first (secrets are never returned)
+///
+/// # Errors
+///
+/// - `Unauthorized` - If not authenticated with a session
+#[get("/auth/service-accounts")]
+pub async fn list_service_accounts(
+    state: web::Data<AppState>,
+    auth_user: AuthenticatedUser,
+) -> ApiResult<HttpResponse> {
+    let service_accounts =
+        ServiceAccountRepo::list_service_accounts(&state.pool, auth_user.user_id).await?;
+
+    Ok(HttpResponse::Ok().json(ListServiceAccountsResponse { service_accounts }))
+}
+
+/// Revokes one of the authenticated user's service accounts.
+///
+/// The account can no longer obtain tokens, and tokens already issued to it
+/// are rejected by [`ServiceAccountAuth`], as in [`current_service_account`].
+///
+/// # Route
+///
+/// `DELETE /auth/service-accounts/{id}`
+///
+/// # Response Body ([`RevokeServiceAccountResponse`])
+///
+/// - `message` - Success message
+///
+/// # Errors
+///
+/// - `Unauthorized` - If not authenticated with a session
+/// - `NotFound` - If the account doesn't exist, belongs to another user, or is already revoked
+#[delete("/auth/service-accounts/{id}")]
+pub async fn revoke_service_account(
+    state: web::Data<AppState>,
+    auth_user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> ApiResult<HttpResponse> {
+    let service_account_id = path.into_inner();
+
+    if !ServiceAccountRepo::revoke_service_account(
+        &state.pool,
+        auth_user.user_id,
+        service_account_id,
+    )
+    .await?
+    {
+        return Err(ApiError::NotFound("Service account not found".to_string()));
+    }
+
+    Ok(HttpResponse::Ok().json(RevokeServiceAccountResponse {
+        message: "Service account revoked.".to_string(),
+    }))
+}
+
+/// Returns the service account a service token was issued to.
+///
+/// Backend services call this to check their token and see which scopes it
+/// carries. Tokens of revoked accounts are rejected.
+///
+/// # Route
+///
+/// `GET /auth/service-accounts/me`
+///
+/// # Response Body ([`CurrentServiceAccountResponse`])
+///
+/// - `service_account_id` - Identifier of the service account
+/// - `client_id` - Public OAuth2 client identifier
+/// - `scopes` - Scopes granted to the token
+///
+/// # Errors
+///
+/// - `Unauthorized` - If no bearer token is present
+/// - `TokenInvalid` - If the token is not a service token or the account was revoked
+/// - `TokenExpired` - If the token has expired
+#[get("/auth/service-accounts/me")]
+pub async fn current_service_account(auth: ServiceAccountAuth) -> ApiResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(CurrentServiceAccountResponse {
+        service_account_id: auth.service_account_id,
+        client_id: auth.client_id,
+        scopes: auth.scopes,
+    }))
+}
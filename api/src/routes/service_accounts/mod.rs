@@ -0,0 +1,29 @@
+//! Service account management endpoints.
+//!
+//! This module lets an authenticated user manage non-human identities for
+//! backend-to-backend calls:
+//! - Creating a scoped service account (the client secret is shown once)
+//! - Listing active service accounts
+//! - Revoking a service account
+//!
+//! Service tokens can look up their own account with
+//! `GET /auth/service-accounts/me`.
+//!
+//! Service accounts obtain tokens from the OAuth2 token endpoint in
+//! [`oauth`](crate::routes::oauth).
+//!
+//! # Module Structure
+//!
+//! - [`handlers`] - HTTP handler functions for service account endpoints
+//! - [`payloads`] - Request and response data structures
+
+pub mod handlers;
+pub mod payloads;
+
+// Re-export handlers at module level for easy route registration
+pub use handlers::{
+    create_service_account, current_service_account, list_service_accounts, revoke_service_account,
+};
+
+// Re-export payload types that are used by other modules
+pub use payloads::CreateServiceAccountRequest;
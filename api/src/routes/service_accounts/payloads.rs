@@ -0,0 +1,65 @@
+//! Request and response payloads for service account endpoints.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::auth::scopes::Scope;
+use crate::repository::service_accounts::ServiceAccountSummary;
+use crate::validators::scopes::validate_scopes;
+
+/// Request body for creating a service account.
+///
+/// See [`create_service_account`](super::handlers::create_service_account) for the handler that processes this request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateServiceAccountRequest {
+    /// Human-readable label for the account.
+    #[validate(length(min = 1, max = 100, message = "Name must have 1 to 100 characters"))]
+    pub name: String,
+
+    /// Scopes the account may request (e.g. `profile:read`).
+    #[validate(custom(function = "validate_scopes"))]
+    pub scopes: Vec<String>,
+}
+
+/// Response body for a newly created service account.
+///
+/// See [`create_service_account`](super::handlers::create_service_account) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct CreateServiceAccountResponse {
+    /// Client secret; it is only returned once and cannot be recovered.
+    pub client_secret: String,
+    /// Stored account details, including the `client_id`.
+    pub service_account: ServiceAccountSummary,
+}
+
+/// Response body listing a user's service accounts.
+///
+/// See [`list_service_accounts`](super::handlers::list_service_accounts) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct ListServiceAccountsResponse {
+    /// Active (non-revoked) accounts, newest first.
+    pub service_accounts: Vec<ServiceAccountSummary>,
+}
+
+/// Response body for service account revocation.
+///
+/// See [`revoke_service_account`](super::handlers::revoke_service_account) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct RevokeServiceAccountResponse {
+    /// Success message.
+    pub message: String,
+}
+
+/// Response body describing the service account a service token belongs to.
+///
+/// See [`current_service_account`](super::handlers::current_service_account) for the handler that produces this response.
+#[derive(Debug, Serialize)]
+pub struct CurrentServiceAccountResponse {
+    /// Identifier of the service account.
+    pub service_account_id: Uuid,
+    /// Public OAuth2 client identifier.
+    pub client_id: String,
+    /// Scopes granted to the token.
+    pub scopes: Vec<Scope>,
+}
@@ -0,0 +1,291 @@
+//! In-memory email backend for local development.
+//!
+//! This module provides [`DevMailbox`], an [`EmailSender`] implementation that
+//! stores rendered messages instead of delivering them. Stored messages are
+//! exposed through the development-only `/dev/mailbox` routes so confirmation
+//! and reset codes can be read without a real email provider.
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::core::error::ApiError;
+use crate::services::email::{EmailSender, EmailTag, OutgoingEmail};
+use crate::services::email_templates::{EmailTemplates, RenderedEmail};
+
+/// Maximum number of messages retained before the oldest are discarded.
+const MAX_STORED_MESSAGES: usize = 200;
+
+/// A rendered email captured by the development mailbox.
+#[derive(Debug, Clone, Serialize)]
+pub struct MailboxMessage {
+    /// Unique message identifier.
+    pub id: Uuid,
+    /// Sender email address.
+    pub from: String,
+    /// Recipient email address.
+    pub to: String,
+    /// Email subject line.
+    pub subject: String,
+    /// Rendered HTML body.
+    pub html: String,
+    /// Rendered plain-text body.
+    pub text: String,
+    /// Reply-to address, when set.
+    pub reply_to: Option<String>,
+    /// File names of attachments.
+    pub attachments: Vec<String>,
+    /// Provider tags attached to the email.
+    pub tags: Vec<EmailTag>,
+    /// Idempotency key used to deduplicate retried sends, when set.
+    pub idempotency_key: Option<String>,
+    /// Timestamp when the message was captured.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Email sender that keeps rendered messages in memory.
+#[derive(Debug)]
+pub struct DevMailbox {
+    from_email: String,
+    templates: Arc<EmailTemplates>,
+    messages: Mutex<Vec<MailboxMessage>>,
+}
+
+impl DevMailbox {
+    /// Creates an empty development mailbox.
+    ///
+    /// # Arguments
+    ///
+    /// - `from_email` - Sender address recorded on captured messages
+    /// - `templates` - Templates used to render captured messages
+    pub fn new(from_email: &str, templates: Arc<EmailTemplates>) -> Self {
+        Self {
+            from_email: from_email.to_string(),
+            templates,
+            messages: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns all captured messages, newest first.
+    pub fn messages(&self) -> Vec<MailboxMessage> {
+        let messages = self.messages.lock().expect("dev mailbox mutex poisoned");
+        messages.iter().rev().cloned().collect()
+    }
+
+    /// Finds a captured message by ID.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` - Message identifier
+    pub fn find(&self, id: Uuid) -> Option<MailboxMessage> {
+        let messages = self.messages.lock().expect("dev mailbox mutex poisoned");
+        messages.iter().find(|message| message.id == id).cloned()
+    }
+
+    /// Stores a rendered message for a recipient.
+    ///
+    /// Emails whose idempotency key matches a stored message are dropped,
+    /// mirroring how the email provider deduplicates retried sends.
+    ///
+    /// # Arguments
+    ///
+    /// - `email` - Recipient, message content, and delivery options
+    /// - `rendered` - Rendered subject, HTML body, and plain-text body
+    fn store(&self, email: OutgoingEmail, rendered: RenderedEmail) {
+        let mut messages = self.messages.lock().expect("dev mailbox mutex poisoned");
+
+        if let Some(key) = &email.idempotency_key
+            && messages
+                .iter()
+                .any(|message| message.idempotency_key.as_ref() == Some(key))
+        {
+            info!("Dev mailbox skipped duplicate email with idempotency key {key}");
+            return;
+        }
+
+        let message = MailboxMessage {
+            id: Uuid::new_v4(),
+            from: self.from_email.clone(),
+            to: email.to,
+            subject: rendered.subject,
+            html: rendered.html,
+            text: rendered.text,
+            reply_to: email.reply_to,
+            attachments: email
+                .attachments
+                .into_iter()
+                .map(|attachment| attachment.filename)
+                .collect(),
+            tags: email.tags,
+            idempotency_key: email.idempotency_key,
+            created_at: Utc::now(),
+        };
+
+        info!(
+            "Dev mailbox captured \"{}\" for {} (GET /dev/mailbox/{})",
+            message.subject, message.to, message.id
+        );
+
+        messages.push(message);
+
+        if messages.len() > MAX_STORED_MESSAGES {
+            let overflow = messages.len() - MAX_STORED_MESSAGES;
+            messages.drain(..overflow);
+        }
+    }
+}
+
+#[async_trait]
+impl EmailSender for DevMailbox {
+    async fn send(&self, email: OutgoingEmail) -> Result<(), ApiError> {
+        let rendered = email.render(&self.templates)?;
+
+        self.store(email, rendered);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{DevMailbox, MAX_STORED_MESSAGES};
+    use crate::services::email::{EmailAttachment, EmailMessage, EmailSender, OutgoingEmail};
+    use crate::services::email_templates::EmailTemplates;
+
+    fn mailbox() -> DevMailbox {
+        DevMailbox::new(
+            "noreply@example.dev",
+            Arc::new(EmailTemplates::new(None, 600)),
+        )
+    }
+
+    fn confirmation(to: &str, code: &str) -> OutgoingEmail {
+        OutgoingEmail::new(
+            to,
+            "en",
+            EmailMessage::Confirmation {
+                first_name: "Taylor".to_string(),
+                code: code.to_string(),
+            },
+        )
+    }
+
+    #[actix_web::test]
+    // Verifies captured messages are listed newest first with rendered content.
+    async fn mailbox_stores_rendered_messages_newest_first() {
+        let mailbox = mailbox();
+
+        mailbox
+            .send(confirmation("first@example.com", "123456"))
+            .await
+            .expect("confirmation email should be stored");
+        mailbox
+            .send(OutgoingEmail::new(
+                "second@example.com",
+                "en",
+                EmailMessage::PasswordReset {
+                    first_name: "Jordan".to_string(),
+                    code: "654321".to_string(),
+                },
+            ))
+            .await
+            .expect("reset email should be stored");
+
+        let messages = mailbox.messages();
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].to, "second@example.com");
+        assert_eq!(messages[0].subject, "Reset your password");
+        assert!(messages[0].html.contains("654321"));
+        assert!(messages[0].text.contains("654321"));
+        assert_eq!(messages[1].from, "noreply@example.dev");
+        assert!(messages[1].html.contains("Taylor"));
+    }
+
+    #[actix_web::test]
+    // Verifies messages can be looked up by ID.
+    async fn find_returns_stored_message_by_id() {
+        let mailbox = mailbox();
+
+        mailbox
+            .send(OutgoingEmail::new(
+                "next@example.com",
+                "en",
+                EmailMessage::EmailChange {
+                    first_name: "Taylor".to_string(),
+                    code: "111111".to_string(),
+                },
+            ))
+            .await
+            .expect("email-change email should be stored");
+
+        let id = mailbox.messages()[0].id;
+
+        assert_eq!(
+            mailbox.find(id).map(|message| message.to),
+            Some("next@example.com".to_string())
+        );
+        assert!(mailbox.find(uuid::Uuid::new_v4()).is_none());
+    }
+
+    #[actix_web::test]
+    // Verifies delivery options are recorded and duplicate idempotency keys are dropped.
+    async fn mailbox_records_delivery_options_and_deduplicates_idempotent_sends() {
+        let mailbox = mailbox();
+        let email = confirmation("user@example.com", "123456")
+            .with_reply_to("support@example.dev")
+            .with_tag("category", "confirmation")
+            .with_attachment(EmailAttachment {
+                filename: "terms.txt".to_string(),
+                content_type: Some("text/plain".to_string()),
+                content: b"terms".to_vec(),
+            })
+            .with_idempotency_key("confirmation-1");
+
+        mailbox
+            .send(email.clone())
+            .await
+            .expect("email should be stored");
+        mailbox
+            .send(email)
+            .await
+            .expect("duplicate email should be accepted");
+
+        let messages = mailbox.messages();
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].reply_to.as_deref(), Some("support@example.dev"));
+        assert_eq!(messages[0].attachments, vec!["terms.txt".to_string()]);
+        assert_eq!(messages[0].tags[0].name, "category");
+        assert_eq!(
+            messages[0].idempotency_key.as_deref(),
+            Some("confirmation-1")
+        );
+    }
+
+    #[actix_web::test]
+    // Verifies the mailbox discards the oldest messages past its retention limit.
+    async fn mailbox_discards_oldest_messages_past_limit() {
+        let mailbox = mailbox();
+
+        for index in 0..=MAX_STORED_MESSAGES {
+            mailbox
+                .send(confirmation(&format!("user{index}@example.com"), "123456"))
+                .await
+                .expect("confirmation email should be stored");
+        }
+
+        let messages = mailbox.messages();
+
+        assert_eq!(messages.len(), MAX_STORED_MESSAGES);
+        assert_eq!(
+            messages.last().map(|message| message.to.as_str()),
+            Some("user1@example.com")
+        );
+    }
+}
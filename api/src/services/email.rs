@@ -1,75 +1,215 @@
 //! Transactional email delivery helpers.
 //!
-//! This module wraps the Resend client used to send account confirmation,
-//! password reset, and email-change verification emails.
+//! This module defines the message model shared by every email backend and the
+//! Resend-backed [`EmailService`]:
+//!
+//! - [`EmailMessage`] - Typed content of an email (which template and its variables)
+//! - [`OutgoingEmail`] - Recipient, locale, and delivery options wrapped around a message
+//! - [`EmailSender`] - Single-method abstraction implemented by every backend
+//!
+//! Message bodies are rendered from localized templates by [`EmailTemplates`].
+//! Adding a new email type only requires a new [`EmailMessage`] variant and its
+//! templates; backends do not change.
+
+use std::sync::Arc;
 
 use async_trait::async_trait;
-use resend_rs::{Resend, types::CreateEmailBaseOptions};
+use resend_rs::{
+    Resend,
+    types::{CreateAttachment, CreateEmailBaseOptions, Tag},
+};
+use serde::Serialize;
 
 use crate::core::error::ApiError;
+use crate::services::email_templates::{EmailTemplate, EmailTemplates, RenderedEmail};
 
-/// Abstraction for sending authentication-related transactional emails.
-#[async_trait]
-pub trait EmailSender {
-    /// Sends an account confirmation email with a one-time verification code.
+/// Typed content of a transactional email.
+///
+/// Variant fields are exposed to the matching template as variables.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum EmailMessage {
+    /// Account confirmation email with a one-time verification code.
+    Confirmation {
+        /// Recipient first name shown in the email body.
+        first_name: String,
+        /// Confirmation code to include in the email.
+        code: String,
+    },
+    /// Password reset email with a one-time verification code.
+    PasswordReset {
+        /// Recipient first name shown in the email body.
+        first_name: String,
+        /// Password reset code to include in the email.
+        code: String,
+    },
+    /// Email-change verification email sent to the new address.
+    EmailChange {
+        /// Recipient first name shown in the email body.
+        first_name: String,
+        /// Email-change verification code to include in the email.
+        code: String,
+    },
+}
+
+impl EmailMessage {
+    /// Returns the template used to render this message.
+    pub fn template(&self) -> EmailTemplate {
+        match self {
+            EmailMessage::Confirmation { .. } => EmailTemplate::Confirmation,
+            EmailMessage::PasswordReset { .. } => EmailTemplate::PasswordReset,
+            EmailMessage::EmailChange { .. } => EmailTemplate::EmailChange,
+        }
+    }
+
+    /// Returns the one-time code carried by this message, if any.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            EmailMessage::Confirmation { code, .. }
+            | EmailMessage::PasswordReset { code, .. }
+            | EmailMessage::EmailChange { code, .. } => Some(code),
+        }
+    }
+}
+
+/// File attached to an outgoing email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailAttachment {
+    /// File name shown to the recipient.
+    pub filename: String,
+    /// MIME type; derived from the file name by the provider when omitted.
+    pub content_type: Option<String>,
+    /// Raw file content.
+    pub content: Vec<u8>,
+}
+
+/// Name/value tag attached to an outgoing email for provider-side analytics.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EmailTag {
+    /// Tag name.
+    pub name: String,
+    /// Tag value.
+    pub value: String,
+}
+
+/// A message addressed to a recipient, plus delivery options.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingEmail {
+    /// Recipient email address.
+    pub to: String,
+    /// Recipient's preferred locale used to pick the template.
+    pub locale: String,
+    /// Typed message content.
+    pub message: EmailMessage,
+    /// Optional reply-to address.
+    pub reply_to: Option<String>,
+    /// Files attached to the email.
+    pub attachments: Vec<EmailAttachment>,
+    /// Provider tags attached to the email.
+    pub tags: Vec<EmailTag>,
+    /// Optional key that prevents the same email from being delivered twice.
+    pub idempotency_key: Option<String>,
+}
+
+impl OutgoingEmail {
+    /// Creates an email without delivery options.
     ///
     /// # Arguments
     ///
-    /// - `to_email` - Recipient email address
-    /// - `first_name` - Recipient first name shown in the email body
-    /// - `code` - Confirmation code to include in the email
+    /// - `to` - Recipient email address
+    /// - `locale` - Recipient's preferred locale used to pick the template
+    /// - `message` - Typed message content
+    pub fn new(to: &str, locale: &str, message: EmailMessage) -> Self {
+        Self {
+            to: to.to_string(),
+            locale: locale.to_string(),
+            message,
+            reply_to: None,
+            attachments: Vec::new(),
+            tags: Vec::new(),
+            idempotency_key: None,
+        }
+    }
+
+    /// Sets the reply-to address.
     ///
-    /// # Errors
+    /// # Arguments
+    ///
+    /// - `reply_to` - Address replies should be sent to
+    pub fn with_reply_to(mut self, reply_to: &str) -> Self {
+        self.reply_to = Some(reply_to.to_string());
+        self
+    }
+
+    /// Adds a file attachment.
+    ///
+    /// # Arguments
+    ///
+    /// - `attachment` - File to attach
+    pub fn with_attachment(mut self, attachment: EmailAttachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
+    /// Adds a provider tag.
+    ///
+    /// # Arguments
     ///
-    /// Returns [`ApiError::EmailServiceError`] when email delivery fails.
-    async fn send_confirmation_email(
-        &self,
-        to_email: &str,
-        first_name: &str,
-        code: &str,
-    ) -> Result<(), ApiError>;
+    /// - `name` - Tag name
+    /// - `value` - Tag value
+    pub fn with_tag(mut self, name: &str, value: &str) -> Self {
+        self.tags.push(EmailTag {
+            name: name.to_string(),
+            value: value.to_string(),
+        });
+        self
+    }
 
-    /// Sends a password reset email with a one-time verification code.
+    /// Sets the idempotency key used to deduplicate retried sends.
     ///
     /// # Arguments
     ///
-    /// - `to_email` - Recipient email address
-    /// - `first_name` - Recipient first name shown in the email body
-    /// - `code` - Password reset code to include in the email
+    /// - `key` - Unique key for this logical email
+    pub fn with_idempotency_key(mut self, key: &str) -> Self {
+        self.idempotency_key = Some(key.to_string());
+        self
+    }
+
+    /// Renders the message body for the recipient's locale.
+    ///
+    /// # Arguments
+    ///
+    /// - `templates` - Templates used to render the message
     ///
     /// # Errors
     ///
-    /// Returns [`ApiError::EmailServiceError`] when email delivery fails.
-    async fn send_password_reset_email(
-        &self,
-        to_email: &str,
-        first_name: &str,
-        code: &str,
-    ) -> Result<(), ApiError>;
+    /// Returns [`ApiError::InternalError`] when the email cannot be rendered.
+    pub fn render(&self, templates: &EmailTemplates) -> Result<RenderedEmail, ApiError> {
+        templates.render(self.message.template(), &self.locale, &self.message)
+    }
+}
 
-    /// Sends an email-change verification email with a one-time confirmation code.
+/// Abstraction for sending transactional emails.
+#[async_trait]
+pub trait EmailSender {
+    /// Renders and delivers an email.
     ///
     /// # Arguments
     ///
-    /// - `to_email` - New email address being verified
-    /// - `first_name` - Recipient first name shown in the email body
-    /// - `code` - Email-change verification code to include in the email
+    /// - `email` - Recipient, message content, and delivery options
     ///
     /// # Errors
     ///
-    /// Returns [`ApiError::EmailServiceError`] when email delivery fails.
-    async fn send_email_change_email(
-        &self,
-        to_email: &str,
-        first_name: &str,
-        code: &str,
-    ) -> Result<(), ApiError>;
+    /// Returns [`ApiError::EmailServiceError`] when email delivery fails, or
+    /// [`ApiError::InternalError`] when the email cannot be rendered.
+    async fn send(&self, email: OutgoingEmail) -> Result<(), ApiError>;
 }
 
 /// Service for sending transactional emails through Resend.
 pub struct EmailService {
     client: Resend,
     from_email: String,
+    templates: Arc<EmailTemplates>,
 }
 
 impl EmailService {
@@ -79,148 +219,68 @@ impl EmailService {
     ///
     /// - `api_key` - Resend API key used to authenticate email requests
     /// - `from_email` - Sender email address used for outgoing messages
-    pub fn new(api_key: &str, from_email: &str) -> Self {
+    /// - `templates` - Templates used to render message bodies
+    pub fn new(api_key: &str, from_email: &str, templates: Arc<EmailTemplates>) -> Self {
         let client = Resend::new(api_key);
         Self {
             client,
             from_email: from_email.to_string(),
+            templates,
         }
     }
 }
 
 #[async_trait]
 impl EmailSender for EmailService {
-    /// Sends an account confirmation email with a one-time verification code.
+    /// Renders an email and delivers it through Resend.
+    ///
+    /// Idempotency keys are forwarded as Resend's `Idempotency-Key` header.
     ///
     /// # Arguments
     ///
-    /// - `to_email` - Recipient email address
-    /// - `first_name` - Recipient first name shown in the email body
-    /// - `code` - Confirmation code to include in the email
+    /// - `email` - Recipient, message content, and delivery options
     ///
     /// # Errors
     ///
     /// Returns [`ApiError::EmailServiceError`] when the upstream email provider
-    /// rejects the request or is unavailable.
-    async fn send_confirmation_email(
-        &self,
-        to_email: &str,
-        first_name: &str,
-        code: &str,
-    ) -> Result<(), ApiError> {
-        let html_body = format!(
-            r#"
-            <h2>Confirm your account</h2>
-            <p>Hi {},</p>
-            <p>Your confirmation code is: <strong>{}</strong></p>
-            <p>This code expires in 10 minutes.</p>
-            <p>If you didn't create an account, please ignore this email.</p>
-            "#,
-            first_name, code
-        );
-
-        let email = CreateEmailBaseOptions::new(
-            &self.from_email,
-            vec![to_email.to_string()],
-            "Confirm your account",
-        )
-        .with_html(&html_body);
-
-        self.client
-            .emails
-            .send(email)
-            .await
-            .map_err(|e| ApiError::EmailServiceError(e.to_string()))?;
+    /// rejects the request or is unavailable, or [`ApiError::InternalError`]
+    /// when the email cannot be rendered.
+    async fn send(&self, email: OutgoingEmail) -> Result<(), ApiError> {
+        let rendered = email.render(&self.templates)?;
 
-        Ok(())
-    }
+        let mut options =
+            CreateEmailBaseOptions::new(&self.from_email, [email.to.as_str()], &rendered.subject)
+                .with_html(&rendered.html)
+                .with_text(&rendered.text);
 
-    /// Sends a password reset email with a one-time verification code.
-    ///
-    /// # Arguments
-    ///
-    /// - `to_email` - Recipient email address
-    /// - `first_name` - Recipient first name shown in the email body
-    /// - `code` - Password reset code to include in the email
-    ///
-    /// # Errors
-    ///
-    /// Returns [`ApiError::EmailServiceError`] when the upstream email provider
-    /// rejects the request or is unavailable.
-    async fn send_password_reset_email(
-        &self,
-        to_email: &str,
-        first_name: &str,
-        code: &str,
-    ) -> Result<(), ApiError> {
-        let html_body = format!(
-            r#"
-            <h2>Reset your password</h2>
-            <p>Hi {},</p>
-            <p>Your password reset code is: <strong>{}</strong></p>
-            <p>This code expires in 10 minutes.</p>
-            <p>If you didn't request a password reset, please ignore this email.</p>
-            "#,
-            first_name, code
-        );
-
-        let email = CreateEmailBaseOptions::new(
-            &self.from_email,
-            vec![to_email.to_string()],
-            "Reset your password",
-        )
-        .with_html(&html_body);
-
-        self.client
-            .emails
-            .send(email)
-            .await
-            .map_err(|e| ApiError::EmailServiceError(e.to_string()))?;
+        if let Some(reply_to) = &email.reply_to {
+            options = options.with_reply(reply_to);
+        }
 
-        Ok(())
-    }
+        for attachment in email.attachments {
+            let mut file = CreateAttachment::from_content(attachment.content)
+                .with_filename(&attachment.filename);
+            if let Some(content_type) = &attachment.content_type {
+                file = file.with_content_type(content_type);
+            }
+            options = options.with_attachment(file);
+        }
 
-    /// Sends an email-change verification email with a one-time confirmation code.
-    ///
-    /// # Arguments
-    ///
-    /// - `to_email` - New email address being verified
-    /// - `first_name` - Recipient first name shown in the email body
-    /// - `code` - Email-change verification code to include in the email
-    ///
-    /// # Errors
-    ///
-    /// Returns [`ApiError::EmailServiceError`] when the upstream email provider
-    /// rejects the request or is unavailable.
-    async fn send_email_change_email(
-        &self,
-        to_email: &str,
-        first_name: &str,
-        code: &str,
-    ) -> Result<(), ApiError> {
-        let html_body = format!(
-            r#"
-            <h2>Confirm your new email</h2>
-            <p>Hi {},</p>
-            <p>Your email-change code is: <strong>{}</strong></p>
-            <p>This code expires in 10 minutes.</p>
-            <p>If you didn't request this change, you can safely ignore this email.</p>
-            "#,
-            first_name, code
-        );
-
-        let email = CreateEmailBaseOptions::new(
-            &self.from_email,
-            vec![to_email.to_string()],
-            "Confirm your new email",
-        )
-        .with_html(&html_body);
-
-        self.client
-            .emails
-            .send(email)
-            .await
-            .map_err(|e| ApiError::EmailServiceError(e.to_string()))?;
+        for tag in &email.tags {
+            options = options.with_tag(Tag::new(&tag.name, &tag.value));
+        }
+
+        let result = match &email.idempotency_key {
+            Some(key) => {
+                self.client
+                    .emails
+                    .send(options.with_idempotency_key(key))
+                    .await
+            }
+            None => self.client.emails.send(options).await,
+        };
+
+        result.map_err(|e| ApiError::EmailServiceError(e.to_string()))?;
 
         Ok(())
     }
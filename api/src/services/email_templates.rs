@@ -0,0 +1,444 @@
+//! Localized transactional email templates.
+//!
+//! This module renders authentication emails with MiniJinja. Every email has an
+//! HTML body and a plain-text alternative, both extending a shared layout and
+//! pulling in shared partials:
+//!
+//! ```text
+//! layout.html, layout.txt         - Shared layouts
+//! partials/*.html, partials/*.txt - Shared partials (for example the code box)
+//! {locale}/{template}.html        - Localized HTML body
+//! {locale}/{template}.txt         - Localized text body; defines the `subject` block
+//! ```
+//!
+//! The built-in templates in `api/templates/email` are compiled into the
+//! binary. When an override directory is configured (`EMAIL_TEMPLATES_DIR`),
+//! files found there take precedence over the built-ins, so branding can be
+//! changed by editing files and restarting the API without a rebuild.
+
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use minijinja::{Environment, ErrorKind, Value, context};
+use serde::Serialize;
+
+use crate::core::error::ApiError;
+
+/// Locale used when no template exists for the recipient's locale.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Templates bundled into the binary, keyed by template name.
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "layout.html",
+        include_str!("../../templates/email/layout.html"),
+    ),
+    (
+        "layout.txt",
+        include_str!("../../templates/email/layout.txt"),
+    ),
+    (
+        "partials/code.html",
+        include_str!("../../templates/email/partials/code.html"),
+    ),
+    (
+        "partials/code.txt",
+        include_str!("../../templates/email/partials/code.txt"),
+    ),
+    (
+        "en/confirmation.html",
+        include_str!("../../templates/email/en/confirmation.html"),
+    ),
+    (
+        "en/confirmation.txt",
+        include_str!("../../templates/email/en/confirmation.txt"),
+    ),
+    (
+        "en/password_reset.html",
+        include_str!("../../templates/email/en/password_reset.html"),
+    ),
+    (
+        "en/password_reset.txt",
+        include_str!("../../templates/email/en/password_reset.txt"),
+    ),
+    (
+        "en/email_change.html",
+        include_str!("../../templates/email/en/email_change.html"),
+    ),
+    (
+        "en/email_change.txt",
+        include_str!("../../templates/email/en/email_change.txt"),
+    ),
+    (
+        "es/confirmation.html",
+        include_str!("../../templates/email/es/confirmation.html"),
+    ),
+    (
+        "es/confirmation.txt",
+        include_str!("../../templates/email/es/confirmation.txt"),
+    ),
+    (
+        "es/password_reset.html",
+        include_str!("../../templates/email/es/password_reset.html"),
+    ),
+    (
+        "es/password_reset.txt",
+        include_str!("../../templates/email/es/password_reset.txt"),
+    ),
+    (
+        "es/email_change.html",
+        include_str!("../../templates/email/es/email_change.html"),
+    ),
+    (
+        "es/email_change.txt",
+        include_str!("../../templates/email/es/email_change.txt"),
+    ),
+];
+
+/// Transactional email templates available to the email backends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailTemplate {
+    /// Account confirmation email sent after sign-up.
+    Confirmation,
+    /// Password reset email sent by the forgot-password flow.
+    PasswordReset,
+    /// Verification email sent to a new address during an email change.
+    EmailChange,
+}
+
+impl EmailTemplate {
+    /// Returns the template file stem used inside each locale directory.
+    pub fn name(self) -> &'static str {
+        match self {
+            EmailTemplate::Confirmation => "confirmation",
+            EmailTemplate::PasswordReset => "password_reset",
+            EmailTemplate::EmailChange => "email_change",
+        }
+    }
+}
+
+/// Subject, HTML body, and plain-text body of a rendered email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    /// Email subject line.
+    pub subject: String,
+    /// HTML email body.
+    pub html: String,
+    /// Plain-text alternative body.
+    pub text: String,
+}
+
+/// Template environment used to render localized transactional emails.
+#[derive(Debug)]
+pub struct EmailTemplates {
+    env: Environment<'static>,
+}
+
+impl EmailTemplates {
+    /// Creates a template environment.
+    ///
+    /// # Arguments
+    ///
+    /// - `overrides_dir` - Optional directory whose templates take precedence over the built-ins
+    /// - `auth_code_expiry_seconds` - Auth code lifetime exposed to templates as `code_expiry_minutes`
+    pub fn new(overrides_dir: Option<&Path>, auth_code_expiry_seconds: u64) -> Self {
+        let overrides_dir = overrides_dir.map(Path::to_path_buf);
+
+        if let Some(dir) = overrides_dir.as_ref().filter(|dir| !dir.is_dir()) {
+            warn!(
+                "Email template directory {} does not exist; using built-in templates",
+                dir.display()
+            );
+        }
+
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_keep_trailing_newline(true);
+        env.add_global(
+            "code_expiry_minutes",
+            auth_code_expiry_seconds.div_ceil(60).max(1),
+        );
+        env.set_loader(move |name| load_template(overrides_dir.as_deref(), name));
+
+        Self { env }
+    }
+
+    /// Renders an email for the closest available locale.
+    ///
+    /// Locales are resolved from most to least specific (`pt-br`, then `pt`)
+    /// before falling back to [`DEFAULT_LOCALE`].
+    ///
+    /// # Arguments
+    ///
+    /// - `template` - Email template to render
+    /// - `locale` - Recipient's preferred locale
+    /// - `context` - Template variables (for example `first_name` and `code`)
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::InternalError`] when a template is missing or fails to render.
+    pub fn render<S: Serialize>(
+        &self,
+        template: EmailTemplate,
+        locale: &str,
+        context: S,
+    ) -> Result<RenderedEmail, ApiError> {
+        let locale = self.resolve_locale(template, locale)?;
+        let ctx = context! { locale => locale, ..Value::from_serialize(&context) };
+
+        let html = self
+            .env
+            .get_template(&format!("{locale}/{}.html", template.name()))
+            .and_then(|html_template| html_template.render(&ctx))
+            .map_err(render_error)?;
+
+        let text_template = self
+            .env
+            .get_template(&format!("{locale}/{}.txt", template.name()))
+            .map_err(render_error)?;
+        let mut captured = text_template.render_captured(&ctx).map_err(render_error)?;
+        let subject = captured
+            .with_state_mut(|state| state.render_block("subject"))
+            .map_err(render_error)?;
+        let text = captured.into_output();
+
+        Ok(RenderedEmail {
+            subject: subject.trim().to_string(),
+            html: html.trim().to_string(),
+            text: text.trim().to_string(),
+        })
+    }
+
+    /// Returns the most specific locale with an HTML template for `template`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::InternalError`] when a candidate template fails to
+    /// parse or no template exists for the default locale.
+    fn resolve_locale(&self, template: EmailTemplate, locale: &str) -> Result<String, ApiError> {
+        for candidate in locale_candidates(locale) {
+            match self
+                .env
+                .get_template(&format!("{candidate}/{}.html", template.name()))
+            {
+                Ok(_) => return Ok(candidate),
+                Err(error) if error.kind() == ErrorKind::TemplateNotFound => continue,
+                Err(error) => return Err(render_error(error)),
+            }
+        }
+
+        Err(ApiError::InternalError(format!(
+            "No email template found for {}",
+            template.name()
+        )))
+    }
+}
+
+/// Returns locale directories to try, from most to least specific.
+///
+/// Locales are lowercased and `_` is treated as `-`. Values containing
+/// characters outside `[a-z0-9-]` are ignored so user-provided locales can
+/// never escape the template directory.
+///
+/// # Arguments
+///
+/// - `locale` - Recipient's preferred locale (for example `pt-BR`)
+pub fn locale_candidates(locale: &str) -> Vec<String> {
+    let normalized = locale.trim().to_lowercase().replace('_', "-");
+    let mut candidates = Vec::new();
+
+    let is_safe = !normalized.is_empty()
+        && normalized
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+
+    if is_safe {
+        let mut subtags: Vec<&str> = normalized.split('-').filter(|s| !s.is_empty()).collect();
+        while !subtags.is_empty() {
+            candidates.push(subtags.join("-"));
+            subtags.pop();
+        }
+    }
+
+    if !candidates
+        .iter()
+        .any(|candidate| candidate == DEFAULT_LOCALE)
+    {
+        candidates.push(DEFAULT_LOCALE.to_string());
+    }
+
+    candidates
+}
+
+/// Loads a template source from the override directory or the built-ins.
+///
+/// # Arguments
+///
+/// - `overrides_dir` - Optional directory checked before the built-in templates
+/// - `name` - Template name relative to the template root
+///
+/// # Errors
+///
+/// Returns a MiniJinja error when an override file exists but cannot be read.
+fn load_template(
+    overrides_dir: Option<&Path>,
+    name: &str,
+) -> Result<Option<String>, minijinja::Error> {
+    if name
+        .split('/')
+        .any(|segment| segment == ".." || segment.is_empty())
+    {
+        return Ok(None);
+    }
+
+    if let Some(dir) = overrides_dir {
+        let path: PathBuf = dir.join(name);
+
+        match std::fs::read_to_string(&path) {
+            Ok(source) => return Ok(Some(source)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => {
+                return Err(minijinja::Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!("failed to read email template {}", path.display()),
+                )
+                .with_source(error));
+            }
+        }
+    }
+
+    Ok(BUILTIN_TEMPLATES
+        .iter()
+        .find(|(builtin_name, _)| *builtin_name == name)
+        .map(|(_, source)| source.to_string()))
+}
+
+/// Maps a template error to an API error.
+///
+/// # Arguments
+///
+/// - `error` - MiniJinja rendering or loading error
+fn render_error(error: minijinja::Error) -> ApiError {
+    ApiError::InternalError(format!("Failed to render email template: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use minijinja::context;
+    use uuid::Uuid;
+
+    use super::{EmailTemplate, EmailTemplates, locale_candidates};
+
+    #[test]
+    // Verifies rendered emails include subject, HTML, text, and the configured code expiry.
+    fn render_includes_subject_bodies_and_configured_expiry() {
+        let templates = EmailTemplates::new(None, 900);
+
+        let email = templates
+            .render(
+                EmailTemplate::Confirmation,
+                "en",
+                context! { first_name => "Taylor", code => "123456" },
+            )
+            .expect("email should render");
+
+        assert_eq!(email.subject, "Confirm your account");
+        assert!(email.html.starts_with("<!DOCTYPE html>"));
+        assert!(email.html.contains("Hi Taylor,"));
+        assert!(email.html.contains("123456"));
+        assert!(email.html.contains("expires in 15 minutes"));
+        assert!(email.text.starts_with("Hi Taylor,"));
+        assert!(email.text.contains("123456"));
+        assert!(email.text.contains("expires in 15 minutes"));
+        assert!(!email.text.contains('<'));
+    }
+
+    #[test]
+    // Verifies expiry wording handles a single minute.
+    fn render_uses_singular_minute_for_short_expiry() {
+        let templates = EmailTemplates::new(None, 45);
+
+        let email = templates
+            .render(
+                EmailTemplate::PasswordReset,
+                "en",
+                context! { first_name => "Taylor", code => "123456" },
+            )
+            .expect("email should render");
+
+        assert!(email.text.contains("expires in 1 minute."));
+    }
+
+    #[test]
+    // Verifies regional locales fall back to their language and unknown locales to English.
+    fn render_selects_closest_available_locale() {
+        let templates = EmailTemplates::new(None, 600);
+        let ctx = context! { first_name => "Taylor", code => "123456" };
+
+        let spanish = templates
+            .render(EmailTemplate::EmailChange, "es-MX", &ctx)
+            .expect("spanish email should render");
+        let unknown = templates
+            .render(EmailTemplate::EmailChange, "xx", &ctx)
+            .expect("fallback email should render");
+
+        assert_eq!(spanish.subject, "Confirma tu nuevo correo");
+        assert!(spanish.html.contains("lang=\"es\""));
+        assert_eq!(unknown.subject, "Confirm your new email");
+    }
+
+    #[test]
+    // Verifies HTML bodies escape user-provided values while text bodies keep them verbatim.
+    fn render_escapes_html_but_not_text() {
+        let templates = EmailTemplates::new(None, 600);
+
+        let email = templates
+            .render(
+                EmailTemplate::Confirmation,
+                "en",
+                context! { first_name => "<b>Taylor</b>", code => "123456" },
+            )
+            .expect("email should render");
+
+        assert!(email.html.contains("&lt;b&gt;Taylor"));
+        assert!(!email.html.contains("<b>Taylor"));
+        assert!(email.text.contains("<b>Taylor</b>"));
+    }
+
+    #[test]
+    // Verifies templates in the override directory take precedence over built-ins.
+    fn render_prefers_override_directory_templates() {
+        let dir = std::env::temp_dir().join(format!("email-templates-{}", Uuid::new_v4()));
+        fs::create_dir_all(dir.join("partials")).expect("override dir should be created");
+        fs::write(
+            dir.join("partials/code.html"),
+            "<p class=\"brand-code\">{{ code }}</p>",
+        )
+        .expect("override partial should be written");
+
+        let templates = EmailTemplates::new(Some(&dir), 600);
+        let email = templates
+            .render(
+                EmailTemplate::Confirmation,
+                "en",
+                context! { first_name => "Taylor", code => "123456" },
+            )
+            .expect("email should render");
+
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(email.html.contains("<p class=\"brand-code\">123456</p>"));
+        assert_eq!(email.subject, "Confirm your account");
+    }
+
+    #[test]
+    // Verifies locale candidates are normalized and reject path-like values.
+    fn locale_candidates_normalize_and_sanitize_input() {
+        assert_eq!(locale_candidates("pt_BR"), vec!["pt-br", "pt", "en"]);
+        assert_eq!(locale_candidates(" EN "), vec!["en"]);
+        assert_eq!(locale_candidates("../secrets"), vec!["en"]);
+        assert_eq!(locale_candidates(""), vec!["en"]);
+    }
+}
@@ -0,0 +1,476 @@
+//! Human verification for abuse-prone endpoints.
+//!
+//! Sign-up and password reset send email, so scripts can use them to spam
+//! arbitrary inboxes. Once a client or email address crosses the configured
+//! request rate, those endpoints ask for proof that a human (or at least a
+//! client willing to spend CPU time) is behind the request:
+//!
+//! - [`HumanChallenge`] - What the client must complete, returned with the error
+//! - [`HumanVerifier`] - Challenge-and-verify abstraction implemented by every backend
+//! - [`ProofOfWorkVerifier`] - Built-in hashcash-style proof of work
+//! - [`HttpHumanVerifier`] - hCaptcha/Turnstile-style `siteverify` services
+//!
+//! A proof-of-work challenge is a signed, expiring token. The client finds a
+//! nonce such that `SHA-256("{challenge}:{nonce}")` starts with `difficulty`
+//! zero bits and sends `"{challenge}:{nonce}"` back; see
+//! [`solve_proof_of_work`] for a reference solver.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::core::error::ApiError;
+
+/// How long a proof-of-work challenge can be solved and redeemed.
+pub const PROOF_OF_WORK_CHALLENGE_TTL_SECONDS: i64 = 300;
+
+/// Largest supported proof-of-work difficulty, in leading zero bits.
+pub const MAX_PROOF_OF_WORK_DIFFICULTY: u32 = 32;
+
+/// Number of redeemed challenges at which expired entries are first pruned.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Label the proof-of-work key is derived under by [`ProofOfWorkVerifier::with_derived_key`].
+const PROOF_OF_WORK_KEY_LABEL: &[u8] = b"human-verification:proof-of-work-key";
+
+/// How long [`HttpHumanVerifier`] waits to connect to the verification service.
+pub const HUMAN_VERIFIER_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long [`HttpHumanVerifier`] waits for the verification service to answer.
+pub const HUMAN_VERIFIER_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A challenge the client must complete before the request is accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HumanChallenge {
+    /// Find a nonce whose hash with `challenge` has `difficulty` leading zero bits.
+    ProofOfWork {
+        /// Signed challenge token to hash.
+        challenge: String,
+        /// Required number of leading zero bits.
+        difficulty: u32,
+    },
+    /// Complete a CAPTCHA widget and send its response token.
+    Captcha {
+        /// Public site key for the widget, when configured.
+        site_key: Option<String>,
+    },
+}
+
+/// Abstraction for challenging clients and checking their answers.
+#[async_trait]
+pub trait HumanVerifier {
+    /// Returns a challenge for a client to complete.
+    fn challenge(&self) -> HumanChallenge;
+
+    /// Checks a client's answer to a challenge.
+    ///
+    /// # Arguments
+    ///
+    /// - `response` - Answer sent by the client
+    /// - `client_address` - Client IP address, when known
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::HumanVerificationServiceError`] when the answer
+    /// cannot be checked, such as when a verification service is unreachable.
+    async fn verify(&self, response: &str, client_address: Option<&str>) -> Result<bool, ApiError>;
+}
+
+/// Built-in verifier that asks clients to solve a hashcash-style puzzle.
+///
+/// Challenges are stateless HMAC-signed tokens, so any instance sharing the
+/// secret can verify them. Redeemed challenges are remembered until they
+/// expire so a solution cannot be replayed on this instance. Expired entries
+/// are pruned once the number of entries doubles since the last prune.
+pub struct ProofOfWorkVerifier {
+    secret: Vec<u8>,
+    difficulty: u32,
+    redeemed: Mutex<RedeemedChallenges>,
+}
+
+/// Redeemed challenges and the pruning schedule, guarded together.
+struct RedeemedChallenges {
+    expiry_by_challenge: HashMap<String, Instant>,
+    /// Entry count at which expired entries are next pruned.
+    prune_at: usize,
+}
+
+impl ProofOfWorkVerifier {
+    /// Creates a proof-of-work verifier.
+    ///
+    /// # Arguments
+    ///
+    /// - `secret` - Key challenges are signed with
+    /// - `difficulty` - Required leading zero bits, at most [`MAX_PROOF_OF_WORK_DIFFICULTY`]
+    pub fn new(secret: &[u8], difficulty: u32) -> Self {
+        Self {
+            secret: secret.to_vec(),
+            difficulty: difficulty.min(MAX_PROOF_OF_WORK_DIFFICULTY),
+            redeemed: Mutex::new(RedeemedChallenges {
+                expiry_by_challenge: HashMap::new(),
+                prune_at: PRUNE_THRESHOLD,
+            }),
+        }
+    }
+
+    /// Creates a proof-of-work verifier keyed from a secret that has other uses.
+    ///
+    /// Challenges are signed with `HMAC-SHA256(root_secret, label)` rather than
+    /// the root secret itself, so the key is not shared with the secret's other
+    /// purposes, such as signing JWTs.
+    ///
+    /// # Arguments
+    ///
+    /// - `root_secret` - Secret to derive the challenge key from
+    /// - `difficulty` - Required leading zero bits, at most [`MAX_PROOF_OF_WORK_DIFFICULTY`]
+    pub fn with_derived_key(root_secret: &[u8], difficulty: u32) -> Self {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(root_secret).expect("HMAC accepts keys of any length");
+        mac.update(PROOF_OF_WORK_KEY_LABEL);
+        Self::new(&mac.finalize().into_bytes(), difficulty)
+    }
+
+    /// Signs the challenge fields with the verifier secret.
+    fn signature(&self, expires_at: i64, salt: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(format!("proof-of-work:{expires_at}.{salt}.{}", self.difficulty).as_bytes());
+        mac
+    }
+
+    /// Returns the challenge's expiry if it was issued by this verifier.
+    fn check_challenge(&self, challenge: &str) -> Option<i64> {
+        let mut parts = challenge.split('.');
+        let (expires_at, salt, signature) = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() {
+            return None;
+        }
+
+        let expires_at = expires_at.parse::<i64>().ok()?;
+        let signature = hex::decode(signature).ok()?;
+        self.signature(expires_at, salt)
+            .verify_slice(&signature)
+            .ok()?;
+
+        Some(expires_at)
+    }
+
+    /// Records a challenge as redeemed, returning `false` if it already was.
+    fn redeem(&self, challenge: &str, expires_at: i64) -> bool {
+        let mut redeemed = self.redeemed.lock().unwrap_or_else(|e| e.into_inner());
+
+        if redeemed.expiry_by_challenge.len() >= redeemed.prune_at {
+            let now = Instant::now();
+            redeemed
+                .expiry_by_challenge
+                .retain(|_, expires| *expires > now);
+            redeemed.prune_at = (redeemed.expiry_by_challenge.len() * 2).max(PRUNE_THRESHOLD);
+        }
+
+        let remaining = u64::try_from(expires_at - Utc::now().timestamp()).unwrap_or(0);
+        redeemed
+            .expiry_by_challenge
+            .insert(
+                challenge.to_string(),
+                Instant::now() + Duration::from_secs(remaining),
+            )
+            .is_none()
+    }
+}
+
+#[async_trait]
+impl HumanVerifier for ProofOfWorkVerifier {
+    /// Issues a signed challenge that expires after
+    /// [`PROOF_OF_WORK_CHALLENGE_TTL_SECONDS`].
+    fn challenge(&self) -> HumanChallenge {
+        let expires_at = Utc::now().timestamp() + PROOF_OF_WORK_CHALLENGE_TTL_SECONDS;
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let salt = hex::encode(salt);
+        let signature = hex::encode(self.signature(expires_at, &salt).finalize().into_bytes());
+
+        HumanChallenge::ProofOfWork {
+            challenge: format!("{expires_at}.{salt}.{signature}"),
+            difficulty: self.difficulty,
+        }
+    }
+
+    /// Accepts `"{challenge}:{nonce}"` when the challenge is genuine, unexpired
+    /// and unused, and the hash meets the difficulty.
+    async fn verify(
+        &self,
+        response: &str,
+        _client_address: Option<&str>,
+    ) -> Result<bool, ApiError> {
+        let Some((challenge, _)) = response.rsplit_once(':') else {
+            return Ok(false);
+        };
+        let Some(expires_at) = self.check_challenge(challenge) else {
+            return Ok(false);
+        };
+
+        Ok(expires_at > Utc::now().timestamp()
+            && leading_zero_bits(&Sha256::digest(response.as_bytes())) >= self.difficulty
+            && self.redeem(challenge, expires_at))
+    }
+}
+
+/// Finds a nonce for a proof-of-work challenge.
+///
+/// Returns the full answer, `"{challenge}:{nonce}"`, ready to send back.
+///
+/// # Arguments
+///
+/// - `challenge` - Challenge token from [`HumanChallenge::ProofOfWork`]
+/// - `difficulty` - Required leading zero bits
+pub fn solve_proof_of_work(challenge: &str, difficulty: u32) -> String {
+    (0u64..)
+        .map(|nonce| format!("{challenge}:{nonce}"))
+        .find(|answer| leading_zero_bits(&Sha256::digest(answer.as_bytes())) >= difficulty)
+        .expect("a nonce exists for any supported difficulty")
+}
+
+/// Counts the leading zero bits of a hash.
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+/// Response body returned by `siteverify` endpoints.
+#[derive(Debug, Deserialize)]
+struct SiteVerifyResponse {
+    /// Whether the token was valid.
+    success: bool,
+}
+
+/// Verifier that checks CAPTCHA tokens with an hCaptcha/Turnstile-style service.
+///
+/// Each token is sent as a form-encoded `POST {url}` with `secret`,
+/// `response`, and, when known, `remoteip` and `sitekey`; the token is
+/// accepted when the JSON response has `"success": true`. Pointing the URL at
+/// a local stub makes the flow testable without the real service.
+///
+/// Requests are bounded by [`HUMAN_VERIFIER_CONNECT_TIMEOUT`] and
+/// [`HUMAN_VERIFIER_REQUEST_TIMEOUT`]. A service that does not answer in time
+/// fails closed: the answer is not accepted and the request is rejected with
+/// [`ApiError::HumanVerificationServiceError`].
+pub struct HttpHumanVerifier {
+    client: reqwest::Client,
+    url: String,
+    secret: String,
+    site_key: Option<String>,
+    request_timeout: Duration,
+}
+
+impl HttpHumanVerifier {
+    /// Creates a new verification service client.
+    ///
+    /// # Arguments
+    ///
+    /// - `url` - `siteverify` endpoint of the service
+    /// - `secret` - Secret key shared with the service
+    /// - `site_key` - Optional public site key, sent to clients and the service
+    pub fn new(url: &str, secret: &str, site_key: Option<&str>) -> Self {
+        Self {
+            client: http_client(
+                HUMAN_VERIFIER_CONNECT_TIMEOUT,
+                HUMAN_VERIFIER_REQUEST_TIMEOUT,
+            ),
+            url: url.to_string(),
+            secret: secret.to_string(),
+            site_key: site_key.map(str::to_string),
+            request_timeout: HUMAN_VERIFIER_REQUEST_TIMEOUT,
+        }
+    }
+
+    /// Replaces the default connect and request timeouts.
+    ///
+    /// # Arguments
+    ///
+    /// - `connect_timeout` - How long to wait to connect to the service
+    /// - `request_timeout` - How long to wait for the whole verification request
+    pub fn with_timeouts(mut self, connect_timeout: Duration, request_timeout: Duration) -> Self {
+        self.client = http_client(connect_timeout, request_timeout);
+        self.request_timeout = request_timeout;
+        self
+    }
+}
+
+/// Builds the HTTP client used to reach the verification service.
+///
+/// # Arguments
+///
+/// - `connect_timeout` - How long to wait to connect to the service
+/// - `request_timeout` - How long to wait for the whole verification request
+fn http_client(connect_timeout: Duration, request_timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(connect_timeout)
+        .timeout(request_timeout)
+        .build()
+        .expect("HTTP client should build with the default TLS backend")
+}
+
+#[async_trait]
+impl HumanVerifier for HttpHumanVerifier {
+    /// Asks the client to complete the CAPTCHA widget for the configured site key.
+    fn challenge(&self) -> HumanChallenge {
+        HumanChallenge::Captcha {
+            site_key: self.site_key.clone(),
+        }
+    }
+
+    /// Posts the token to the verification service.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::HumanVerificationServiceError`] when the service is
+    /// unreachable, does not answer within the timeout, responds with a
+    /// non-success status, or returns an unexpected body.
+    async fn verify(&self, response: &str, client_address: Option<&str>) -> Result<bool, ApiError> {
+        let body = {
+            let mut form = url::form_urlencoded::Serializer::new(String::new());
+            form.append_pair("secret", &self.secret);
+            form.append_pair("response", response);
+            if let Some(client_address) = client_address {
+                form.append_pair("remoteip", client_address);
+            }
+            if let Some(site_key) = &self.site_key {
+                form.append_pair("sitekey", site_key);
+            }
+            form.finish()
+        };
+
+        let service_error = |e: reqwest::Error| {
+            if e.is_timeout() {
+                ApiError::HumanVerificationServiceError(format!(
+                    "verification service did not respond within {} ms",
+                    self.request_timeout.as_millis()
+                ))
+            } else {
+                ApiError::HumanVerificationServiceError(e.to_string())
+            }
+        };
+        let result = self
+            .client
+            .post(&self.url)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                "application/x-www-form-urlencoded",
+            )
+            .body(body)
+            .send()
+            .await
+            .map_err(service_error)?;
+
+        if !result.status().is_success() {
+            return Err(ApiError::HumanVerificationServiceError(format!(
+                "verification service responded with {}",
+                result.status()
+            )));
+        }
+
+        let body: SiteVerifyResponse = result.json().await.map_err(service_error)?;
+        Ok(body.success)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+
+    use std::time::Duration;
+
+    use super::{
+        HttpHumanVerifier, HumanChallenge, HumanVerifier, ProofOfWorkVerifier, leading_zero_bits,
+        solve_proof_of_work,
+    };
+    use crate::core::error::ApiError;
+
+    #[test]
+    // Verifies leading zero bits are counted across byte boundaries.
+    fn counts_leading_zero_bits() {
+        assert_eq!(leading_zero_bits(&[0x80]), 0);
+        assert_eq!(leading_zero_bits(&[0x00, 0x1f]), 11);
+        assert_eq!(leading_zero_bits(&[0x00, 0x00]), 16);
+    }
+
+    #[actix_web::test]
+    // Verifies solved challenges are accepted once and forged, unsolved or differently keyed ones are rejected.
+    async fn proof_of_work_accepts_each_solution_once() {
+        let verifier = ProofOfWorkVerifier::new(b"secret", 8);
+        let HumanChallenge::ProofOfWork {
+            challenge,
+            difficulty,
+        } = verifier.challenge()
+        else {
+            panic!("expected a proof-of-work challenge");
+        };
+        assert_eq!(difficulty, 8);
+
+        let answer = solve_proof_of_work(&challenge, difficulty);
+        assert!(verifier.verify(&answer, None).await.expect("should verify"));
+        assert!(!verifier.verify(&answer, None).await.expect("should verify"));
+
+        let HumanChallenge::ProofOfWork { challenge, .. } = verifier.challenge() else {
+            panic!("expected a proof-of-work challenge");
+        };
+        let answer = solve_proof_of_work(&challenge, 8);
+        for other in [
+            ProofOfWorkVerifier::new(b"other-secret", 8),
+            ProofOfWorkVerifier::with_derived_key(b"secret", 8),
+        ] {
+            assert!(!other.verify(&answer, None).await.expect("should verify"));
+        }
+        let unsolved = (0u64..)
+            .map(|nonce| format!("{challenge}:{nonce}"))
+            .find(|answer| leading_zero_bits(&Sha256::digest(answer.as_bytes())) < 8)
+            .expect("most nonces miss the difficulty");
+        assert!(
+            !verifier
+                .verify(&unsolved, None)
+                .await
+                .expect("should verify")
+        );
+        assert!(
+            !verifier
+                .verify("garbage", None)
+                .await
+                .expect("should verify")
+        );
+    }
+
+    #[actix_web::test]
+    // Verifies a verification service that never answers fails closed after the timeout.
+    async fn unresponsive_service_times_out() {
+        // The listener accepts connections into its backlog but never replies.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("listener should bind");
+        let url = format!(
+            "http://{}/siteverify",
+            listener
+                .local_addr()
+                .expect("listener should have an address")
+        );
+        let verifier = HttpHumanVerifier::new(&url, "secret", None)
+            .with_timeouts(Duration::from_millis(200), Duration::from_millis(200));
+
+        let result = verifier.verify("token", None).await;
+        assert!(matches!(
+            result,
+            Err(ApiError::HumanVerificationServiceError(message)) if message.contains("did not respond")
+        ));
+    }
+}
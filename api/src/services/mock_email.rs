@@ -0,0 +1,92 @@
+//! In-memory email sender for tests.
+//!
+//! This module is compiled for the crate's own unit tests and, behind the
+//! `test-utils` cargo feature, for downstream crates and integration tests that
+//! need to assert on sent emails without calling an email provider.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::core::error::ApiError;
+use crate::services::email::{EmailSender, OutgoingEmail};
+use crate::services::email_templates::EmailTemplate;
+
+/// Email sender that records every email instead of delivering it.
+#[derive(Debug, Default)]
+pub struct MockEmailSender {
+    calls: Mutex<Vec<OutgoingEmail>>,
+    delay: Duration,
+}
+
+impl MockEmailSender {
+    /// Creates a new mock sender.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a mock sender that takes `delay` to send each email, like a
+    /// slow provider.
+    ///
+    /// # Arguments
+    ///
+    /// - `delay` - Time each send waits before recording the email
+    pub fn with_delay(delay: Duration) -> Self {
+        Self {
+            delay,
+            ..Self::default()
+        }
+    }
+
+    /// Returns a snapshot of all captured emails, oldest first.
+    pub fn calls(&self) -> Vec<OutgoingEmail> {
+        self.calls
+            .lock()
+            .expect("mock email mutex poisoned")
+            .clone()
+    }
+
+    /// Returns the most recent email sent to `to` using `template`.
+    ///
+    /// # Arguments
+    ///
+    /// - `template` - Template the email was rendered from
+    /// - `to` - Recipient email address
+    pub fn last_sent(&self, template: EmailTemplate, to: &str) -> Option<OutgoingEmail> {
+        self.calls
+            .lock()
+            .expect("mock email mutex poisoned")
+            .iter()
+            .rev()
+            .find(|email| email.message.template() == template && email.to == to)
+            .cloned()
+    }
+
+    /// Returns the one-time code from the most recent email sent to `to` using `template`.
+    ///
+    /// # Arguments
+    ///
+    /// - `template` - Template the email was rendered from
+    /// - `to` - Recipient email address
+    pub fn last_code(&self, template: EmailTemplate, to: &str) -> Option<String> {
+        self.last_sent(template, to)
+            .and_then(|email| email.message.code().map(str::to_string))
+    }
+}
+
+#[async_trait]
+impl EmailSender for MockEmailSender {
+    async fn send(&self, email: OutgoingEmail) -> Result<(), ApiError> {
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+
+        self.calls
+            .lock()
+            .expect("mock email mutex poisoned")
+            .push(email);
+
+        Ok(())
+    }
+}
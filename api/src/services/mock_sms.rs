@@ -0,0 +1,57 @@
+//! In-memory SMS sender for tests.
+//!
+//! Like [`mock_email`](crate::services::mock_email), this module is compiled
+//! for the crate's own unit tests and, behind the `test-utils` cargo feature,
+//! for downstream crates and integration tests.
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::core::error::ApiError;
+use crate::services::sms::{OutgoingSms, SmsSender};
+
+/// SMS sender that records every message instead of delivering it.
+#[derive(Debug, Default)]
+pub struct MockSmsSender {
+    calls: Mutex<Vec<OutgoingSms>>,
+}
+
+impl MockSmsSender {
+    /// Creates a new mock sender.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a snapshot of all captured messages, oldest first.
+    pub fn calls(&self) -> Vec<OutgoingSms> {
+        self.calls.lock().expect("mock sms mutex poisoned").clone()
+    }
+
+    /// Returns the one-time code from the most recent message sent to `to`.
+    ///
+    /// # Arguments
+    ///
+    /// - `to` - Recipient phone number in E.164 format
+    pub fn last_code(&self, to: &str) -> Option<String> {
+        self.calls
+            .lock()
+            .expect("mock sms mutex poisoned")
+            .iter()
+            .rev()
+            .find(|sms| sms.to == to)
+            .map(|sms| sms.message.code().to_string())
+    }
+}
+
+#[async_trait]
+impl SmsSender for MockSmsSender {
+    async fn send(&self, sms: OutgoingSms) -> Result<(), ApiError> {
+        self.calls
+            .lock()
+            .expect("mock sms mutex poisoned")
+            .push(sms);
+
+        Ok(())
+    }
+}
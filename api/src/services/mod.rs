@@ -3,6 +3,22 @@
 //! This module contains wrappers around third-party services so business logic
 //! can depend on a small, testable interface.
 //!
+//! - [`dev_mailbox`] - In-memory email capture for local development
 //! - [`email`] - Transactional email delivery via Resend for auth flows
+//! - [`email_templates`] - Localized HTML and plain-text email templates
+//! - [`human_verification`] - Proof-of-work and CAPTCHA challenges for abuse-prone endpoints
+//! - [`mock_email`] - Recording email sender for tests (`test-utils` feature)
+//! - [`mock_sms`] - Recording SMS sender for tests (`test-utils` feature)
+//! - [`sms`] - Transactional SMS delivery via an HTTP gateway for phone flows
 
+pub mod dev_mailbox;
 pub mod email;
+pub mod email_templates;
+pub mod human_verification;
+pub mod sms;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod mock_email;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod mock_sms;
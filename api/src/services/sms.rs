@@ -0,0 +1,260 @@
+//! Transactional SMS delivery helpers.
+//!
+//! This module mirrors [`email`](crate::services::email) for text messages:
+//!
+//! - [`SmsMessage`] - Typed content of an SMS
+//! - [`OutgoingSms`] - Recipient phone number wrapped around a message
+//! - [`SmsSender`] - Single-method abstraction implemented by every backend
+//! - [`HttpSmsGateway`] - Delivers messages through a JSON HTTP gateway
+//! - [`LogSmsSender`] - Logs messages instead of sending them (development only)
+
+use async_trait::async_trait;
+use log::info;
+use serde::Serialize;
+
+use crate::core::error::ApiError;
+
+/// Typed content of a transactional SMS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmsMessage {
+    /// Code confirming ownership of a phone number.
+    PhoneConfirmation {
+        /// Confirmation code to include in the message.
+        code: String,
+    },
+    /// Code allowing the recipient to reset their password.
+    PasswordReset {
+        /// Password reset code to include in the message.
+        code: String,
+    },
+    /// Code allowing the recipient to log in without a password.
+    LogInCode {
+        /// Log-in code to include in the message.
+        code: String,
+    },
+}
+
+impl SmsMessage {
+    /// Returns the one-time code carried by this message.
+    pub fn code(&self) -> &str {
+        match self {
+            SmsMessage::PhoneConfirmation { code }
+            | SmsMessage::PasswordReset { code }
+            | SmsMessage::LogInCode { code } => code,
+        }
+    }
+
+    /// Renders the message text.
+    ///
+    /// # Arguments
+    ///
+    /// - `code_expiry_minutes` - Code lifetime shown to the recipient
+    pub fn body(&self, code_expiry_minutes: u64) -> String {
+        let purpose = match self {
+            SmsMessage::PhoneConfirmation { .. } => "phone confirmation code",
+            SmsMessage::PasswordReset { .. } => "password reset code",
+            SmsMessage::LogInCode { .. } => "log-in code",
+        };
+        let unit = if code_expiry_minutes == 1 {
+            "minute"
+        } else {
+            "minutes"
+        };
+
+        format!(
+            "Your {purpose} is {}. It expires in {code_expiry_minutes} {unit}. Never share this code.",
+            self.code()
+        )
+    }
+}
+
+/// A message addressed to a phone number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingSms {
+    /// Recipient phone number in E.164 format.
+    pub to: String,
+    /// Typed message content.
+    pub message: SmsMessage,
+}
+
+impl OutgoingSms {
+    /// Creates an SMS for a recipient.
+    ///
+    /// # Arguments
+    ///
+    /// - `to` - Recipient phone number in E.164 format
+    /// - `message` - Typed message content
+    pub fn new(to: &str, message: SmsMessage) -> Self {
+        Self {
+            to: to.to_string(),
+            message,
+        }
+    }
+}
+
+/// Abstraction for sending transactional SMS messages.
+#[async_trait]
+pub trait SmsSender {
+    /// Renders and delivers an SMS.
+    ///
+    /// # Arguments
+    ///
+    /// - `sms` - Recipient and message content
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::SmsServiceError`] when delivery fails.
+    async fn send(&self, sms: OutgoingSms) -> Result<(), ApiError>;
+}
+
+/// Request body posted to the SMS gateway.
+#[derive(Debug, Serialize)]
+struct GatewayRequest<'a> {
+    /// Sender ID or number, when configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<&'a str>,
+    /// Recipient phone number in E.164 format.
+    to: &'a str,
+    /// Message text.
+    body: &'a str,
+}
+
+/// SMS sender that posts messages to an HTTP gateway.
+///
+/// Each message is sent as `POST {url}` with a JSON body of `from`, `to`, and
+/// `body`, authenticated with a bearer token when an API key is configured.
+/// Any non-2xx response is treated as a delivery failure.
+pub struct HttpSmsGateway {
+    client: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+    from: Option<String>,
+    code_expiry_minutes: u64,
+}
+
+impl HttpSmsGateway {
+    /// Creates a new gateway client.
+    ///
+    /// # Arguments
+    ///
+    /// - `url` - Gateway endpoint that accepts message requests
+    /// - `api_key` - Optional bearer token sent with each request
+    /// - `from` - Optional sender ID or number
+    /// - `auth_code_expiry_seconds` - Auth code lifetime shown in messages
+    pub fn new(
+        url: &str,
+        api_key: Option<&str>,
+        from: Option<&str>,
+        auth_code_expiry_seconds: u64,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            api_key: api_key.map(str::to_string),
+            from: from.map(str::to_string),
+            code_expiry_minutes: code_expiry_minutes(auth_code_expiry_seconds),
+        }
+    }
+}
+
+#[async_trait]
+impl SmsSender for HttpSmsGateway {
+    /// Renders an SMS and posts it to the gateway.
+    ///
+    /// # Arguments
+    ///
+    /// - `sms` - Recipient and message content
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::SmsServiceError`] when the gateway is unreachable or
+    /// responds with a non-success status.
+    async fn send(&self, sms: OutgoingSms) -> Result<(), ApiError> {
+        let body = sms.message.body(self.code_expiry_minutes);
+
+        let mut request = self.client.post(&self.url).json(&GatewayRequest {
+            from: self.from.as_deref(),
+            to: &sms.to,
+            body: &body,
+        });
+
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ApiError::SmsServiceError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ApiError::SmsServiceError(format!(
+                "gateway responded with {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// SMS sender that logs messages instead of delivering them.
+///
+/// Used in development when no gateway is configured so phone codes can be
+/// read from the API logs.
+pub struct LogSmsSender {
+    code_expiry_minutes: u64,
+}
+
+impl LogSmsSender {
+    /// Creates a logging sender.
+    ///
+    /// # Arguments
+    ///
+    /// - `auth_code_expiry_seconds` - Auth code lifetime shown in messages
+    pub fn new(auth_code_expiry_seconds: u64) -> Self {
+        Self {
+            code_expiry_minutes: code_expiry_minutes(auth_code_expiry_seconds),
+        }
+    }
+}
+
+#[async_trait]
+impl SmsSender for LogSmsSender {
+    async fn send(&self, sms: OutgoingSms) -> Result<(), ApiError> {
+        info!(
+            "SMS to {}: {}",
+            sms.to,
+            sms.message.body(self.code_expiry_minutes)
+        );
+        Ok(())
+    }
+}
+
+/// Converts an auth code lifetime to whole minutes, rounding up.
+///
+/// # Arguments
+///
+/// - `auth_code_expiry_seconds` - Auth code lifetime in seconds
+fn code_expiry_minutes(auth_code_expiry_seconds: u64) -> u64 {
+    auth_code_expiry_seconds.div_ceil(60).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SmsMessage;
+
+    #[test]
+    // Verifies message text includes the code and the configured expiry.
+    fn body_includes_code_and_expiry() {
+        let message = SmsMessage::LogInCode {
+            code: "123456".to_string(),
+        };
+
+        assert_eq!(
+            message.body(10),
+            "Your log-in code is 123456. It expires in 10 minutes. Never share this code."
+        );
+        assert!(message.body(1).contains("1 minute."));
+    }
+}
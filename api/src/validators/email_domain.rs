@@ -0,0 +1,69 @@
+//! Email domain policy validation for sign-up and email-change payloads.
+//!
+//! The policy comes from runtime configuration, so these validators are
+//! called from handlers after [`ValidatedJson`](crate::extractors::ValidatedJson)
+//! has checked that the address is well-formed.
+
+use validator::{ValidationError, ValidationErrors};
+
+use crate::auth::email_domains::EmailDomainPolicy;
+use crate::routes::auth::{RequestEmailChangeRequest, SignUpRequest};
+
+/// Checks an email address against the domain policy.
+///
+/// # Arguments
+///
+/// - `policy` - Configured email domain policy
+/// - `field` - Name of the email field in the request payload
+/// - `email` - Submitted email address
+///
+/// # Errors
+///
+/// Returns `ValidationErrors` with an `email_domain_*` coded error on `field`
+/// when the domain is rejected.
+fn validate_email_domain(
+    policy: &EmailDomainPolicy,
+    field: &'static str,
+    email: &str,
+) -> Result<(), ValidationErrors> {
+    let Err(violation) = policy.check(email) else {
+        return Ok(());
+    };
+
+    let mut error = ValidationError::new(violation.code());
+    error.message = Some(violation.message().into());
+    let mut errors = ValidationErrors::new();
+    errors.add(field, error);
+
+    Err(errors)
+}
+
+/// Validates the email domain in a sign-up request.
+///
+/// See [`sign_up`](crate::routes::auth::handlers::sign_up) for the handler
+/// that uses this validation.
+///
+/// # Errors
+///
+/// Returns `ValidationErrors` on `email` when the domain is rejected.
+pub fn validate_signup_email_domain(
+    policy: &EmailDomainPolicy,
+    req: &SignUpRequest,
+) -> Result<(), ValidationErrors> {
+    validate_email_domain(policy, "email", &req.email)
+}
+
+/// Validates the new email domain in an email-change request.
+///
+/// See [`request_email_change`](crate::routes::auth::handlers::request_email_change)
+/// for the handler that uses this validation.
+///
+/// # Errors
+///
+/// Returns `ValidationErrors` on `new_email` when the domain is rejected.
+pub fn validate_email_change_domain(
+    policy: &EmailDomainPolicy,
+    req: &RequestEmailChangeRequest,
+) -> Result<(), ValidationErrors> {
+    validate_email_domain(policy, "new_email", &req.new_email)
+}
@@ -0,0 +1,74 @@
+//! Locale validation for request payloads.
+//!
+//! Locales are accepted as simple BCP 47 language tags such as `en`, `es`, or
+//! `pt-BR` and are used to pick localized email templates.
+
+/// Maximum accepted locale tag length.
+const MAX_LOCALE_LENGTH: usize = 35;
+
+/// Validates that a value is a simple BCP 47 language tag.
+///
+/// The primary subtag must be 2-3 ASCII letters. Each following subtag,
+/// separated by `-` or `_`, must be 1-8 ASCII alphanumeric characters.
+///
+/// # Arguments
+///
+/// * `locale` - The locale value to validate
+///
+/// # Errors
+///
+/// Returns a `ValidationError` with code `invalid_locale` if the value is not
+/// a valid language tag.
+pub fn validate_locale(locale: &str) -> Result<(), validator::ValidationError> {
+    if is_valid_locale(locale) {
+        return Ok(());
+    }
+
+    let mut error = validator::ValidationError::new("invalid_locale");
+    error.message = Some("Locale is invalid".into());
+    Err(error)
+}
+
+/// Returns `true` when a value is a simple BCP 47 language tag.
+///
+/// # Arguments
+///
+/// * `locale` - The locale value to check
+fn is_valid_locale(locale: &str) -> bool {
+    if locale.is_empty() || locale.len() > MAX_LOCALE_LENGTH {
+        return false;
+    }
+
+    let mut subtags = locale.split(['-', '_']);
+
+    let primary_is_valid = subtags.next().is_some_and(|primary| {
+        (2..=3).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_alphabetic())
+    });
+
+    primary_is_valid
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_locale;
+
+    #[test]
+    // Verifies common language tags are accepted.
+    fn locale_validator_accepts_language_tags() {
+        for locale in ["en", "es", "pt-BR", "zh_Hant_TW", "fil"] {
+            assert!(validate_locale(locale).is_ok(), "{locale} should be valid");
+        }
+    }
+
+    #[test]
+    // Verifies malformed or path-like values are rejected.
+    fn locale_validator_rejects_invalid_values() {
+        for locale in ["", "e", "english", "en-", "../en", "en US", "en-toolongtag"] {
+            let error = validate_locale(locale).expect_err("locale should be invalid");
+            assert_eq!(error.code, "invalid_locale");
+        }
+    }
+}
@@ -6,6 +6,22 @@
 //!
 //! # Modules
 //!
+//! - [`email_domain`] - Email domain policy checks for sign-up and email-change flows
+//! - [`locale`] - Language tag validation for user locale preferences
+//! - [`password_history`] - Password reuse checks for password-update flows
 //! - [`password_match`] - Password confirmation validation for sign-up and password-update flows
+//! - [`password_policy`] - Configured password policy checks for sign-up and password-update flows
+//! - [`phone_number`] - E.164 phone number normalization and validation for SMS flows
+//! - [`profile`] - Time zone, preferences, and non-empty checks for profile updates
+//! - [`reauthentication`] - Single-factor check for step-up re-authentication
+//! - [`scopes`] - Known-scope checks for credential requests
 
+pub mod email_domain;
+pub mod locale;
+pub mod password_history;
 pub mod password_match;
+pub mod password_policy;
+pub mod phone_number;
+pub mod profile;
+pub mod reauthentication;
+pub mod scopes;
@@ -0,0 +1,167 @@
+//! Password reuse validation for password-update request payloads.
+//!
+//! Stored hashes are read inside the handler's transaction, so these
+//! validators are called from handlers once the previous hashes are known.
+
+use validator::{ValidationError, ValidationErrors};
+
+use crate::auth::password::{PasswordHashParams, verify_password_with_params};
+use crate::routes::auth::{ChangePasswordRequest, SetPasswordRequest};
+
+/// Validates that a password matches none of the user's previous hashes.
+///
+/// This is a private helper function used by the public validation functions
+/// for specific request types. Hashes that cannot be parsed never match.
+///
+/// # Arguments
+///
+/// - `field` - Name of the password field in the request payload
+/// - `label` - How the field is referred to in error messages
+/// - `password` - Candidate password
+/// - `previous_hashes` - The user's current and recent password hashes
+/// - `history_size` - Number of recent passwords remembered, for the message
+/// - `hash_params` - Configured hashing parameters holding the pepper secrets
+///
+/// # Errors
+///
+/// Returns `ValidationErrors` with code `password_reused` on `field` if the
+/// password matches one of the hashes.
+fn validate_password_not_reused(
+    field: &'static str,
+    label: &str,
+    password: &str,
+    previous_hashes: &[String],
+    history_size: usize,
+    hash_params: &PasswordHashParams,
+) -> Result<(), ValidationErrors> {
+    let reused = previous_hashes
+        .iter()
+        .any(|hash| verify_password_with_params(password, hash, hash_params).unwrap_or(false));
+    if !reused {
+        return Ok(());
+    }
+
+    let mut error = ValidationError::new("password_reused");
+    error.message = Some(if history_size <= 1 {
+        format!("{} must differ from your current password", label).into()
+    } else {
+        format!(
+            "{} must differ from your last {} passwords",
+            label, history_size
+        )
+        .into()
+    });
+
+    let mut errors = ValidationErrors::new();
+    errors.add(field, error);
+    Err(errors)
+}
+
+/// Validates that the new password in a change-password request was not used recently.
+///
+/// See [`change_password`](crate::routes::auth::handlers::change_password) for
+/// the handler that uses this validation.
+///
+/// # Arguments
+///
+/// - `req` - Change-password request
+/// - `previous_hashes` - The user's current and recent password hashes
+/// - `history_size` - Number of recent passwords remembered
+/// - `hash_params` - Configured hashing parameters holding the pepper secrets
+///
+/// # Errors
+///
+/// Returns `ValidationErrors` on `new_password` if the password was reused.
+pub fn validate_change_password_not_reused(
+    req: &ChangePasswordRequest,
+    previous_hashes: &[String],
+    history_size: usize,
+    hash_params: &PasswordHashParams,
+) -> Result<(), ValidationErrors> {
+    validate_password_not_reused(
+        "new_password",
+        "New password",
+        &req.new_password,
+        previous_hashes,
+        history_size,
+        hash_params,
+    )
+}
+
+/// Validates that the password in a set-password request was not used recently.
+///
+/// See [`set_password`](crate::routes::auth::handlers::set_password) for the
+/// handler that uses this validation.
+///
+/// # Arguments
+///
+/// - `req` - Set-password request
+/// - `previous_hashes` - The user's current and recent password hashes
+/// - `history_size` - Number of recent passwords remembered
+/// - `hash_params` - Configured hashing parameters holding the pepper secrets
+///
+/// # Errors
+///
+/// Returns `ValidationErrors` on `password` if the password was reused.
+pub fn validate_set_password_not_reused(
+    req: &SetPasswordRequest,
+    previous_hashes: &[String],
+    history_size: usize,
+    hash_params: &PasswordHashParams,
+) -> Result<(), ValidationErrors> {
+    validate_password_not_reused(
+        "password",
+        "Password",
+        &req.password,
+        previous_hashes,
+        history_size,
+        hash_params,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_set_password_not_reused;
+    use crate::auth::password::{PasswordHashParams, hash_password};
+    use crate::routes::auth::SetPasswordRequest;
+
+    #[test]
+    // Verifies passwords matching a previous hash are rejected and others accepted.
+    fn rejects_previous_passwords_only() {
+        let previous_hashes = vec![
+            hash_password("Velvet-Harbor-42").expect("password should hash"),
+            "not-a-hash".to_string(),
+        ];
+        let params = PasswordHashParams::default();
+        let request = |password: &str| SetPasswordRequest {
+            password: password.to_string(),
+            confirm: password.to_string(),
+        };
+
+        assert!(
+            validate_set_password_not_reused(
+                &request("Quiet-Lantern-7"),
+                &previous_hashes,
+                5,
+                &params
+            )
+            .is_ok()
+        );
+
+        let errors = validate_set_password_not_reused(
+            &request("Velvet-Harbor-42"),
+            &previous_hashes,
+            5,
+            &params,
+        )
+        .expect_err("reused password should be rejected");
+        let field_errors = errors.field_errors();
+        let error = &field_errors["password"][0];
+
+        assert_eq!(error.code.as_ref(), "password_reused");
+        assert_eq!(
+            error.message.as_deref(),
+            Some("Password must differ from your last 5 passwords")
+        );
+    }
+}
@@ -85,6 +85,8 @@ mod tests {
             email: "jane@example.com".to_string(),
             password: "password123".to_string(),
             confirm: "password123".to_string(),
+            locale: None,
+            human_verification: None,
         };
 
         assert!(validate_signup_passwords_match(&request).is_ok());
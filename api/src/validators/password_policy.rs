@@ -0,0 +1,179 @@
+//! Password policy validation for password-setting request payloads.
+//!
+//! The policy comes from runtime configuration and, outside sign-up, the
+//! user's stored profile, so these validators are called from handlers after
+//! [`ValidatedJson`](crate::extractors::ValidatedJson) has checked the payload
+//! shape. Each failed rule becomes its own error on the password field.
+
+use validator::{ValidationError, ValidationErrors};
+
+use crate::auth::password_policy::PasswordPolicy;
+use crate::routes::auth::{ChangePasswordRequest, SetPasswordRequest, SignUpRequest};
+
+/// Validates a password against the policy, collecting one error per failed rule.
+///
+/// This is a private helper function used by the public validation functions
+/// for specific request types.
+///
+/// # Arguments
+///
+/// - `policy` - Configured password policy
+/// - `field` - Name of the password field in the request payload
+/// - `label` - How the field is referred to in error messages
+/// - `password` - Candidate password
+/// - `user_inputs` - The user's own name, email and similar values
+///
+/// # Errors
+///
+/// Returns `ValidationErrors` with a `password_*` coded error on `field` for
+/// every rule the password fails.
+fn validate_password_policy(
+    policy: &PasswordPolicy,
+    field: &'static str,
+    label: &str,
+    password: &str,
+    user_inputs: &[&str],
+) -> Result<(), ValidationErrors> {
+    let violations = policy.check(password, user_inputs);
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    let mut errors = ValidationErrors::new();
+    for violation in violations {
+        let mut error = ValidationError::new(violation.code());
+        error.message = Some(violation.message(label).into());
+        errors.add(field, error);
+    }
+
+    Err(errors)
+}
+
+/// Validates the password in a sign-up request against the policy.
+///
+/// The submitted name and email count as user inputs.
+///
+/// See [`sign_up`](crate::routes::auth::handlers::sign_up) for the handler
+/// that uses this validation.
+///
+/// # Errors
+///
+/// Returns `ValidationErrors` on `password` for every failed rule.
+pub fn validate_signup_password_policy(
+    policy: &PasswordPolicy,
+    req: &SignUpRequest,
+) -> Result<(), ValidationErrors> {
+    validate_password_policy(
+        policy,
+        "password",
+        "Password",
+        &req.password,
+        &[&req.first_name, &req.last_name, &req.email],
+    )
+}
+
+/// Validates the new password in a change-password request against the policy.
+///
+/// See [`change_password`](crate::routes::auth::handlers::change_password) for
+/// the handler that uses this validation.
+///
+/// # Arguments
+///
+/// - `policy` - Configured password policy
+/// - `req` - Change-password request
+/// - `user_inputs` - The user's stored name, display name and email
+///
+/// # Errors
+///
+/// Returns `ValidationErrors` on `new_password` for every failed rule.
+pub fn validate_change_password_policy(
+    policy: &PasswordPolicy,
+    req: &ChangePasswordRequest,
+    user_inputs: &[&str],
+) -> Result<(), ValidationErrors> {
+    validate_password_policy(
+        policy,
+        "new_password",
+        "New password",
+        &req.new_password,
+        user_inputs,
+    )
+}
+
+/// Validates the password in a set-password request against the policy.
+///
+/// See [`set_password`](crate::routes::auth::handlers::set_password) for the
+/// handler that uses this validation.
+///
+/// # Arguments
+///
+/// - `policy` - Configured password policy
+/// - `req` - Set-password request
+/// - `user_inputs` - The user's stored name, display name and email
+///
+/// # Errors
+///
+/// Returns `ValidationErrors` on `password` for every failed rule.
+pub fn validate_set_password_policy(
+    policy: &PasswordPolicy,
+    req: &SetPasswordRequest,
+    user_inputs: &[&str],
+) -> Result<(), ValidationErrors> {
+    validate_password_policy(policy, "password", "Password", &req.password, user_inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_change_password_policy, validate_signup_password_policy};
+    use crate::auth::password_policy::PasswordPolicy;
+    use crate::routes::auth::{ChangePasswordRequest, SignUpRequest};
+
+    #[test]
+    // Verifies sign-up passwords built from the user's own name are rejected as weak.
+    fn signup_policy_penalizes_own_name() {
+        let request = |password: &str| SignUpRequest {
+            first_name: "Jane".to_string(),
+            last_name: "Whitfield".to_string(),
+            email: "jane.whitfield@example.com".to_string(),
+            password: password.to_string(),
+            confirm: password.to_string(),
+            locale: None,
+            human_verification: None,
+        };
+        let policy = PasswordPolicy::default();
+
+        assert!(validate_signup_password_policy(&policy, &request("Velvet-Harbor-42")).is_ok());
+
+        let errors = validate_signup_password_policy(&policy, &request("Whitfield1990"))
+            .expect_err("password built from the user's name should be rejected");
+        let field_errors = errors.field_errors();
+        let codes: Vec<&str> = field_errors["password"]
+            .iter()
+            .map(|error| error.code.as_ref())
+            .collect();
+
+        assert_eq!(codes, vec!["password_too_weak"]);
+    }
+
+    #[test]
+    // Verifies change-password failures are reported per rule on `new_password`.
+    fn change_password_policy_reports_each_rule() {
+        let request = ChangePasswordRequest {
+            current_password: "old-password-123".to_string(),
+            new_password: "aaaa".to_string(),
+            confirm: "aaaa".to_string(),
+        };
+
+        let errors =
+            validate_change_password_policy(&PasswordPolicy::default(), &request, &["Jane"])
+                .expect_err("weak password should be rejected");
+        let field_errors = errors.field_errors();
+        let new_password_errors = &field_errors["new_password"];
+
+        assert_eq!(new_password_errors.len(), 4);
+        assert_eq!(
+            new_password_errors[0].message.as_deref(),
+            Some("New password must have at least 8 characters")
+        );
+    }
+}
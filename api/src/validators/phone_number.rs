@@ -0,0 +1,70 @@
+//! Phone number normalization and validation for SMS flows.
+//!
+//! Phone numbers are stored in E.164 format (`+` followed by up to 15 digits).
+//! Common formatting characters are stripped before validation so clients may
+//! submit values such as `+1 (555) 010-0000`.
+
+/// Normalizes a phone number by removing spaces, dashes, dots, and parentheses.
+///
+/// # Arguments
+///
+/// * `phone_number` - The phone number as submitted by the client
+pub fn normalize_phone_number(phone_number: &str) -> String {
+    phone_number
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+        .collect()
+}
+
+/// Validates that a phone number is in E.164 format after normalization.
+///
+/// # Arguments
+///
+/// * `phone_number` - The phone number value to validate
+///
+/// # Errors
+///
+/// Returns a `ValidationError` with code `invalid_phone_number` if the value is
+/// not a `+` followed by 8 to 15 digits, the first of which is non-zero.
+pub fn validate_phone_number(phone_number: &str) -> Result<(), validator::ValidationError> {
+    let normalized = normalize_phone_number(phone_number);
+
+    let is_valid = normalized.strip_prefix('+').is_some_and(|digits| {
+        (8..=15).contains(&digits.len())
+            && !digits.starts_with('0')
+            && digits.chars().all(|c| c.is_ascii_digit())
+    });
+
+    if is_valid {
+        return Ok(());
+    }
+
+    let mut error = validator::ValidationError::new("invalid_phone_number");
+    error.message = Some("Phone number must be in international format, e.g. +15550100000".into());
+    Err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_phone_number, validate_phone_number};
+
+    #[test]
+    // Verifies formatting characters are stripped during normalization.
+    fn normalize_phone_number_strips_formatting() {
+        assert_eq!(normalize_phone_number("+1 (555) 010-0000"), "+15550100000");
+        assert_eq!(normalize_phone_number("+44.20.7946.0000"), "+442079460000");
+    }
+
+    #[test]
+    // Verifies E.164 numbers are accepted and local or malformed numbers rejected.
+    fn validate_phone_number_requires_e164_format() {
+        assert!(validate_phone_number("+1 555 010 0000").is_ok());
+        assert!(validate_phone_number("+442079460000").is_ok());
+
+        for phone_number in ["5550100000", "+0555010000", "+1555", "+1555abc0000", ""] {
+            let error =
+                validate_phone_number(phone_number).expect_err("phone number should be invalid");
+            assert_eq!(error.code, "invalid_phone_number");
+        }
+    }
+}
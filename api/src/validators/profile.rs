@@ -0,0 +1,121 @@
+//! Profile field validation for user update requests.
+//!
+//! Covers values that cannot be expressed with simple field-level attributes:
+//! IANA time zone names, the free-form `preferences` object, and the rule that
+//! a profile update must change at least one field.
+
+use std::str::FromStr;
+
+use chrono_tz::Tz;
+
+use crate::routes::auth::UpdateCurrentUserRequest;
+
+/// Maximum serialized size of the `preferences` object in bytes.
+pub const MAX_PREFERENCES_BYTES: usize = 16_384;
+
+/// Validates that a value is an IANA time zone name such as `Europe/Madrid`.
+///
+/// # Arguments
+///
+/// * `timezone` - The time zone value to validate
+///
+/// # Errors
+///
+/// Returns a `ValidationError` with code `invalid_timezone` if the value is not
+/// a known time zone.
+pub fn validate_timezone(timezone: &str) -> Result<(), validator::ValidationError> {
+    if Tz::from_str(timezone).is_ok() {
+        return Ok(());
+    }
+
+    let mut error = validator::ValidationError::new("invalid_timezone");
+    error.message = Some("Timezone must be an IANA time zone name, e.g. Europe/Madrid".into());
+    Err(error)
+}
+
+/// Validates that user preferences are a JSON object within the size limit.
+///
+/// # Arguments
+///
+/// * `preferences` - The preferences value to validate
+///
+/// # Errors
+///
+/// Returns a `ValidationError` with code `invalid_preferences` if the value is
+/// not an object, or `preferences_too_large` if it serializes to more than
+/// [`MAX_PREFERENCES_BYTES`].
+pub fn validate_preferences(
+    preferences: &serde_json::Value,
+) -> Result<(), validator::ValidationError> {
+    if !preferences.is_object() {
+        let mut error = validator::ValidationError::new("invalid_preferences");
+        error.message = Some("Preferences must be a JSON object".into());
+        return Err(error);
+    }
+
+    if preferences.to_string().len() > MAX_PREFERENCES_BYTES {
+        let mut error = validator::ValidationError::new("preferences_too_large");
+        error.message =
+            Some(format!("Preferences must not exceed {MAX_PREFERENCES_BYTES} bytes").into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// Validates that a profile update request changes at least one field.
+///
+/// Used with the `#[validate(schema(...))]` attribute on [`UpdateCurrentUserRequest`].
+///
+/// See [`update_current_user`](crate::routes::auth::handlers::update_current_user)
+/// for the handler that uses this validation.
+pub fn validate_profile_update_not_empty(
+    req: &UpdateCurrentUserRequest,
+) -> Result<(), validator::ValidationError> {
+    let is_empty = req.first_name.is_none()
+        && req.last_name.is_none()
+        && req.display_name.is_none()
+        && req.locale.is_none()
+        && req.timezone.is_none()
+        && req.preferences.is_none();
+
+    if is_empty {
+        let mut error = validator::ValidationError::new("empty_update");
+        error.message = Some("At least one field must be provided".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{MAX_PREFERENCES_BYTES, validate_preferences, validate_timezone};
+
+    #[test]
+    // Verifies IANA zone names are accepted and offsets or unknown names rejected.
+    fn validate_timezone_requires_iana_name() {
+        assert!(validate_timezone("UTC").is_ok());
+        assert!(validate_timezone("Europe/Madrid").is_ok());
+        assert!(validate_timezone("America/Argentina/Buenos_Aires").is_ok());
+
+        assert!(validate_timezone("").is_err());
+        assert!(validate_timezone("+02:00").is_err());
+        assert!(validate_timezone("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    // Verifies preferences must be a JSON object that fits within the size limit.
+    fn validate_preferences_requires_bounded_object() {
+        assert!(validate_preferences(&json!({ "theme": "dark" })).is_ok());
+
+        let error = validate_preferences(&json!(["dark"])).expect_err("arrays are rejected");
+        assert_eq!(error.code, "invalid_preferences");
+
+        let oversized = json!({ "blob": "x".repeat(MAX_PREFERENCES_BYTES) });
+        let error = validate_preferences(&oversized).expect_err("oversized objects are rejected");
+        assert_eq!(error.code, "preferences_too_large");
+    }
+}
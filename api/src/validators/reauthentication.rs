@@ -0,0 +1,47 @@
+//! Re-authentication request validation.
+//!
+//! A re-authentication proves the user's identity with exactly one factor:
+//! their password or an SMS log-in code.
+
+use crate::routes::auth::ReauthenticateRequest;
+
+/// Validates that a re-authentication request provides exactly one factor.
+///
+/// Used with the `#[validate(schema(...))]` attribute on [`ReauthenticateRequest`].
+///
+/// See [`reauthenticate`](crate::routes::auth::handlers::reauthenticate)
+/// for the handler that uses this validation.
+pub fn validate_reauthentication_factor(
+    req: &ReauthenticateRequest,
+) -> Result<(), validator::ValidationError> {
+    if req.password.is_some() == req.auth_code.is_some() {
+        let mut error = validator::ValidationError::new("reauthentication_factor");
+        error.message = Some("Provide either a password or an auth code".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_reauthentication_factor;
+    use crate::routes::auth::ReauthenticateRequest;
+
+    #[test]
+    // Verifies exactly one of password or auth code must be provided.
+    fn requires_exactly_one_factor() {
+        let request = |password: Option<&str>, auth_code: Option<&str>| ReauthenticateRequest {
+            password: password.map(str::to_string),
+            auth_code: auth_code.map(str::to_string),
+        };
+
+        assert!(validate_reauthentication_factor(&request(Some("password123"), None)).is_ok());
+        assert!(validate_reauthentication_factor(&request(None, Some("123456"))).is_ok());
+        assert!(validate_reauthentication_factor(&request(None, None)).is_err());
+        assert!(
+            validate_reauthentication_factor(&request(Some("password123"), Some("123456")))
+                .is_err()
+        );
+    }
+}
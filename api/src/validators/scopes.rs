@@ -0,0 +1,52 @@
+//! Scope list validation for credential requests.
+
+use crate::auth::scopes::Scope;
+
+/// Validates that a scope list is non-empty and contains only known scopes.
+///
+/// # Arguments
+///
+/// * `scopes` - Scope names submitted by the client
+///
+/// # Errors
+///
+/// Returns a `ValidationError` with code `scopes_required` if the list is
+/// empty, or `invalid_scope` if any entry is not a known scope.
+pub fn validate_scopes(scopes: &[String]) -> Result<(), validator::ValidationError> {
+    if scopes.is_empty() {
+        let mut error = validator::ValidationError::new("scopes_required");
+        error.message = Some("At least one scope is required".into());
+        return Err(error);
+    }
+
+    if let Some(unknown) = scopes.iter().find(|scope| Scope::parse(scope).is_none()) {
+        let known = Scope::ALL
+            .iter()
+            .map(|scope| scope.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut error = validator::ValidationError::new("invalid_scope");
+        error.message = Some(format!("Unknown scope `{unknown}`; expected one of: {known}").into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_scopes;
+
+    #[test]
+    // Verifies scope lists must be non-empty and contain only known scopes.
+    fn validate_scopes_requires_known_scopes() {
+        assert!(validate_scopes(&["profile:read".to_string()]).is_ok());
+
+        let error = validate_scopes(&[]).expect_err("empty lists are rejected");
+        assert_eq!(error.code, "scopes_required");
+
+        let error = validate_scopes(&["profile:read".to_string(), "admin".to_string()])
+            .expect_err("unknown scopes are rejected");
+        assert_eq!(error.code, "invalid_scope");
+    }
+}
@@ -1,22 +1,60 @@
 //! Integration tests for authentication routes.
 //!
 //! These tests cover core auth success and failure paths, including signup,
-//! email confirmation, login, email change, password reset verification, and
-//! password update behavior (both reset and authenticated change flows) with
-//! real database persistence and auth-guard enforcement.
+//! email confirmation, login, email change, phone confirmation and SMS codes,
+//! password reset verification, and password update behavior (both reset and
+//! authenticated change flows), API keys, service accounts with the
+//! `client_credentials` grant, the device authorization grant, token
+//! introspection and revocation, forward-auth session verification,
+//! immediate access token revocation across instances, token version
+//! invalidation, CSRF protection for cookie sessions, step-up
+//! re-authentication for sensitive actions, password policy enforcement,
+//! breached password screening, password reuse prevention, Argon2 parameter
+//! upgrades at log-in, bulk import of users with legacy password hashes,
+//! password pepper rotation, keyed auth-code hashing with configurable
+//! code formats, account-enumeration resistance of log-in and code
+//! verification, adaptive human verification for sign-up and password
+//! reset, and email domain allow/deny policies, with real database persistence and auth-guard enforcement.
+
+// Tests hold `test_guard()` across awaits on purpose to serialize DB access.
+#![allow(clippy::await_holding_lock)]
 
 mod support;
 
-use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
 
-use actix_web::{App, http::StatusCode, test, web};
+use actix_web::http::header::HeaderMap;
+use actix_web::{App, HttpResponse, http::StatusCode, middleware::from_fn, test, web};
 use serde_json::json;
+use sha1::{Digest, Sha1};
 use sqlx::{Pool, Postgres};
-use support::{MockEmailKind, app_state_with_mock_email, test_pool, unique_email};
+use support::{
+    app_state_with_mock_email, app_state_with_mock_senders, test_env, test_pool, unique_email,
+    unique_phone_number,
+};
 use uuid::Uuid;
 
-use api::auth::jwt::create_access_token;
+use api::auth::breached_passwords::{BreachedPasswordIndex, build_index};
+use api::auth::codes::UNAMBIGUOUS_ALPHANUMERIC_ALPHABET;
+use api::auth::csrf::csrf_protection;
+use api::auth::email_domains::{DisposableEmailDomains, parse_domain_patterns};
+use api::auth::jwt::{AccessTokenClaims, create_access_token, decode_access_token};
+use api::auth::password::{
+    PasswordHashParams, hash_password, hash_password_with_params, verify_password_with_params,
+};
+use api::auth::revocation::AccessTokenRevocations;
+use api::auth::service_accounts::generate_client_credentials;
+use api::auth::user_import::{ImportFormat, import_users, read_users};
+use api::core::app_state::AppState;
 use api::core::config::configure_routes;
+use api::repository::service_accounts::ServiceAccountRepo;
+use api::services::email_templates::EmailTemplate;
+use api::services::human_verification::{
+    HttpHumanVerifier, ProofOfWorkVerifier, solve_proof_of_work,
+};
+use api::services::mock_email::MockEmailSender;
+use api::services::sms::SmsMessage;
 
 fn test_guard() -> MutexGuard<'static, ()> {
     static TEST_MUTEX: OnceLock<Mutex<()>> = OnceLock::new();
@@ -43,6 +81,14 @@ async fn email_confirmed_for_user(pool: &Pool<Postgres>, email: &str) -> bool {
         .expect("user should exist")
 }
 
+async fn hashed_password_for_email(pool: &Pool<Postgres>, email: &str) -> String {
+    sqlx::query_scalar("SELECT hashed_password FROM users WHERE email = $1")
+        .bind(email)
+        .fetch_one(pool)
+        .await
+        .expect("user should exist")
+}
+
 async fn email_for_user(pool: &Pool<Postgres>, user_id: Uuid) -> String {
     sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
         .bind(user_id)
@@ -120,9 +166,51 @@ async fn sign_up_creates_user_auth_code_and_sends_confirmation_email() {
 
     let calls = mock_email.calls();
     assert_eq!(calls.len(), 1);
-    assert_eq!(calls[0].kind, MockEmailKind::Confirmation);
-    assert_eq!(calls[0].to_email, email);
-    assert_eq!(calls[0].code.len(), 6);
+    assert_eq!(calls[0].message.template(), EmailTemplate::Confirmation);
+    assert_eq!(calls[0].to, email);
+    assert_eq!(calls[0].message.code().map(str::len), Some(6));
+}
+
+#[actix_web::test]
+// Verifies signup stores the requested locale and uses it for the confirmation email.
+async fn sign_up_persists_locale_and_sends_localized_confirmation_email() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("signup-locale");
+    let request = test::TestRequest::post()
+        .uri("/auth/sign-up")
+        .set_json(json!({
+            "first_name": "Taylor",
+            "last_name": "User",
+            "email": email,
+            "password": "password123",
+            "confirm": "password123",
+            "locale": "es_MX"
+        }))
+        .to_request();
+
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let locale: String = sqlx::query_scalar("SELECT locale FROM users WHERE email = $1")
+        .bind(&email)
+        .fetch_one(&pool)
+        .await
+        .expect("locale query should succeed");
+
+    assert_eq!(locale, "es-MX");
+
+    let calls = mock_email.calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].locale, "es-MX");
 }
 
 #[actix_web::test]
@@ -327,6 +415,7 @@ async fn set_password_with_only_access_token_returns_unauthorized() {
     let access_token = create_access_token(
         Uuid::new_v4(),
         "ghost-user@example.dev",
+        0,
         &jwt_secret,
         access_token_expiry,
     )
@@ -381,10 +470,7 @@ async fn confirm_email_marks_email_as_confirmed_and_auth_code_as_used() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &email)
         .expect("confirmation email should be captured");
 
     let confirm = test::TestRequest::post()
@@ -414,11 +500,11 @@ async fn confirm_email_marks_email_as_confirmed_and_auth_code_as_used() {
 }
 
 #[actix_web::test]
-// Verifies login is blocked for accounts that have not confirmed email yet.
-async fn log_in_requires_confirmed_email() {
+// Verifies unconfirmed accounts fail log-in like unknown emails and are reminded only by email.
+async fn log_in_unconfirmed_email_matches_unknown_email() {
     let _guard = test_guard();
     let pool = test_pool().await;
-    let (state, _) = app_state_with_mock_email(pool);
+    let (state, mock_email) = app_state_with_mock_email(pool.clone());
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(state))
@@ -439,24 +525,67 @@ async fn log_in_requires_confirmed_email() {
         .to_request();
     let sign_up_response = test::call_service(&app, sign_up).await;
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
+    let sign_up_code = mock_email
+        .last_code(EmailTemplate::Confirmation, &email)
+        .expect("confirmation email should be captured");
 
-    let login = test::TestRequest::post()
-        .uri("/auth/log-in")
-        .set_json(json!({
-            "email": email,
-            "password": "password123"
-        }))
-        .to_request();
-    let login_response = test::call_service(&app, login).await;
-    assert_eq!(login_response.status(), StatusCode::FORBIDDEN);
-
-    let body: serde_json::Value = test::read_body_json(login_response).await;
+    let mut responses = Vec::new();
+    for email in [email.clone(), unique_email("login-unknown")] {
+        let login = test::TestRequest::post()
+            .uri("/auth/log-in")
+            .set_json(json!({
+                "email": email,
+                "password": "password123"
+            }))
+            .to_request();
+        let login_response = test::call_service(&app, login).await;
+        let status = login_response.status();
+        let body: serde_json::Value = test::read_body_json(login_response).await;
+        responses.push((status, body));
+    }
+    assert_eq!(responses[0].0, StatusCode::UNAUTHORIZED);
+    assert_eq!(responses[0], responses[1]);
     assert_eq!(
-        body.get("error")
+        responses[0]
+            .1
+            .get("error")
             .and_then(|error| error.get("code"))
             .and_then(|code| code.as_str()),
-        Some("EMAIL_NOT_CONFIRMED")
+        Some("INVALID_CREDENTIALS")
+    );
+
+    // The reminder is sent in the background with a new code that replaces the old one.
+    let mut reminder_code = None;
+    for _ in 0..50 {
+        reminder_code = mock_email
+            .last_code(EmailTemplate::Confirmation, &email)
+            .filter(|code| *code != sign_up_code);
+        if reminder_code.is_some() {
+            break;
+        }
+        actix_web::rt::time::sleep(Duration::from_millis(20)).await;
+    }
+    let reminder_code = reminder_code.expect("confirmation reminder should be sent");
+
+    let confirm_with_old_code = test::TestRequest::post()
+        .uri("/auth/confirm-email")
+        .set_json(json!({ "email": email, "auth_code": sign_up_code }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, confirm_with_old_code)
+            .await
+            .status(),
+        StatusCode::BAD_REQUEST
+    );
+    let confirm = test::TestRequest::post()
+        .uri("/auth/confirm-email")
+        .set_json(json!({ "email": email, "auth_code": reminder_code }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, confirm).await.status(),
+        StatusCode::OK
     );
+    assert!(email_confirmed_for_user(&pool, &email).await);
 }
 
 #[actix_web::test]
@@ -487,10 +616,7 @@ async fn log_in_sets_auth_cookies_and_persists_refresh_token() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &email)
         .expect("confirmation email should be captured");
 
     let confirm = test::TestRequest::post()
@@ -561,10 +687,7 @@ async fn log_in_with_remember_me_sets_persistent_refresh_cookie() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &email)
         .expect("confirmation email should be captured");
 
     let confirm = test::TestRequest::post()
@@ -625,10 +748,7 @@ async fn refresh_session_rotates_refresh_token_state() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &email)
         .expect("confirmation email should be captured");
 
     let confirm = test::TestRequest::post()
@@ -723,10 +843,7 @@ async fn auth_flow_normalizes_email_case_across_auth_steps() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == normalized_email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &normalized_email)
         .expect("confirmation email should be captured for normalized email");
 
     let confirm = test::TestRequest::post()
@@ -818,10 +935,7 @@ async fn verify_forgot_password_issues_cookies_and_marks_reset_code_used() {
     assert_eq!(forgot_response.status(), StatusCode::OK);
 
     let reset_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::PasswordReset && call.to_email == email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::PasswordReset, &email)
         .expect("password reset email should be captured");
 
     let verify = test::TestRequest::post()
@@ -882,10 +996,7 @@ async fn set_password_revokes_old_refresh_tokens_and_stores_new_token() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &email)
         .expect("confirmation email should be captured");
 
     let confirm = test::TestRequest::post()
@@ -982,10 +1093,7 @@ async fn change_password_updates_password_and_rotates_refresh_session() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &email)
         .expect("confirmation email should be captured");
 
     let confirm = test::TestRequest::post()
@@ -1113,10 +1221,7 @@ async fn change_password_with_invalid_current_password_returns_unauthorized() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &email)
         .expect("confirmation email should be captured");
 
     let confirm = test::TestRequest::post()
@@ -1193,6 +1298,98 @@ async fn change_password_with_invalid_current_password_returns_unauthorized() {
     assert_eq!(revoked_count, 0);
 }
 
+#[actix_web::test]
+// Verifies change-password checks the password policy before the current password or session.
+async fn change_password_policy_failure_precedes_current_password_check() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("policy-order");
+    let sign_up = test::TestRequest::post()
+        .uri("/auth/sign-up")
+        .set_json(json!({
+            "first_name": "Taylor",
+            "last_name": "User",
+            "email": email,
+            "password": "password123",
+            "confirm": "password123"
+        }))
+        .to_request();
+    let sign_up_response = test::call_service(&app, sign_up).await;
+    assert_eq!(sign_up_response.status(), StatusCode::CREATED);
+
+    let confirmation_code = mock_email
+        .last_code(EmailTemplate::Confirmation, &email)
+        .expect("confirmation email should be captured");
+    let confirm = test::TestRequest::post()
+        .uri("/auth/confirm-email")
+        .set_json(json!({
+            "email": email,
+            "auth_code": confirmation_code
+        }))
+        .to_request();
+    let confirm_response = test::call_service(&app, confirm).await;
+    assert_eq!(confirm_response.status(), StatusCode::OK);
+
+    let login = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({
+            "email": email,
+            "password": "password123"
+        }))
+        .to_request();
+    let login_response = test::call_service(&app, login).await;
+    assert_eq!(login_response.status(), StatusCode::OK);
+
+    let access_cookie = login_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .map(|cookie| cookie.to_owned())
+        .expect("access cookie should be set on login");
+    let refresh_cookie = login_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "refresh_token")
+        .map(|cookie| cookie.to_owned())
+        .expect("refresh cookie should be set on login");
+
+    let change_password = test::TestRequest::post()
+        .uri("/auth/change-password")
+        .cookie(access_cookie)
+        .cookie(refresh_cookie)
+        .set_json(json!({
+            "current_password": "wrong-password",
+            "new_password": "short1",
+            "confirm": "short1"
+        }))
+        .to_request();
+    let change_password_response = test::call_service(&app, change_password).await;
+    assert_eq!(change_password_response.status(), StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = test::read_body_json(change_password_response).await;
+    let errors = body
+        .get("errors")
+        .and_then(|value| value.as_array())
+        .expect("validation errors should be returned as an array");
+    assert!(
+        errors
+            .iter()
+            .any(|error| error.get("field") == Some(&json!("new_password")))
+    );
+
+    let user_id = user_id_for_email(&pool, &email).await;
+    assert_eq!(active_refresh_token_count(&pool, user_id).await, 1);
+    assert_eq!(revoked_refresh_token_count(&pool, user_id).await, 0);
+}
+
 #[actix_web::test]
 // Verifies stale cookies from a logged-out session cannot be reused to change a password.
 async fn log_out_then_change_password_with_old_cookies_returns_unauthorized() {
@@ -1221,10 +1418,7 @@ async fn log_out_then_change_password_with_old_cookies_returns_unauthorized() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &email)
         .expect("confirmation email should be captured");
 
     let confirm = test::TestRequest::post()
@@ -1317,10 +1511,7 @@ async fn log_out_then_set_password_with_old_cookies_returns_unauthorized() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &email)
         .expect("confirmation email should be captured");
 
     let confirm = test::TestRequest::post()
@@ -1426,10 +1617,7 @@ async fn request_email_change_for_existing_email_returns_generic_success() {
     assert_eq!(owner_sign_up_response.status(), StatusCode::CREATED);
 
     let owner_confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == owner_email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &owner_email)
         .expect("owner confirmation email should be captured");
 
     let owner_confirm = test::TestRequest::post()
@@ -1458,6 +1646,19 @@ async fn request_email_change_for_existing_email_returns_generic_success() {
         .find(|cookie| cookie.name() == "access_token")
         .map(|cookie| cookie.to_owned())
         .expect("access cookie should be set on login");
+    let reauthenticate = test::TestRequest::post()
+        .uri("/auth/reauthenticate")
+        .cookie(owner_access_cookie.clone())
+        .set_json(json!({ "password": "password123" }))
+        .to_request();
+    let reauthenticate_response = test::call_service(&app, reauthenticate).await;
+    assert_eq!(reauthenticate_response.status(), StatusCode::OK);
+    let owner_access_cookie = reauthenticate_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .map(|cookie| cookie.into_owned())
+        .expect("access cookie should be set on re-authentication");
 
     let request_email_change = test::TestRequest::post()
         .uri("/auth/request-email-change")
@@ -1476,7 +1677,9 @@ async fn request_email_change_for_existing_email_returns_generic_success() {
     let email_change_call_count = mock_email
         .calls()
         .into_iter()
-        .filter(|call| call.kind == MockEmailKind::EmailChange && call.to_email == occupied_email)
+        .filter(|call| {
+            call.message.template() == EmailTemplate::EmailChange && call.to == occupied_email
+        })
         .count();
     assert_eq!(email_change_call_count, 0);
 
@@ -1520,10 +1723,7 @@ async fn email_change_confirmation_updates_email_and_marks_code_used() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == current_email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &current_email)
         .expect("confirmation email should be captured");
 
     let confirm = test::TestRequest::post()
@@ -1552,6 +1752,19 @@ async fn email_change_confirmation_updates_email_and_marks_code_used() {
         .find(|cookie| cookie.name() == "access_token")
         .map(|cookie| cookie.to_owned())
         .expect("access cookie should be set on login");
+    let reauthenticate = test::TestRequest::post()
+        .uri("/auth/reauthenticate")
+        .cookie(access_cookie.clone())
+        .set_json(json!({ "password": "password123" }))
+        .to_request();
+    let reauthenticate_response = test::call_service(&app, reauthenticate).await;
+    assert_eq!(reauthenticate_response.status(), StatusCode::OK);
+    let access_cookie = reauthenticate_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .map(|cookie| cookie.into_owned())
+        .expect("access cookie should be set on re-authentication");
 
     let normalized_new_email = unique_email("email-change-next");
 
@@ -1564,12 +1777,7 @@ async fn email_change_confirmation_updates_email_and_marks_code_used() {
     assert_eq!(request_email_change_response.status(), StatusCode::OK);
 
     let email_change_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| {
-            call.kind == MockEmailKind::EmailChange && call.to_email == normalized_new_email
-        })
-        .map(|call| call.code)
+        .last_code(EmailTemplate::EmailChange, &normalized_new_email)
         .expect("email-change confirmation email should be captured");
 
     let confirm_email_change = test::TestRequest::post()
@@ -1653,10 +1861,7 @@ async fn confirm_email_change_with_invalid_code_returns_bad_request() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == current_email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &current_email)
         .expect("confirmation email should be captured");
 
     let confirm = test::TestRequest::post()
@@ -1685,6 +1890,19 @@ async fn confirm_email_change_with_invalid_code_returns_bad_request() {
         .find(|cookie| cookie.name() == "access_token")
         .map(|cookie| cookie.to_owned())
         .expect("access cookie should be set on login");
+    let reauthenticate = test::TestRequest::post()
+        .uri("/auth/reauthenticate")
+        .cookie(access_cookie.clone())
+        .set_json(json!({ "password": "password123" }))
+        .to_request();
+    let reauthenticate_response = test::call_service(&app, reauthenticate).await;
+    assert_eq!(reauthenticate_response.status(), StatusCode::OK);
+    let access_cookie = reauthenticate_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .map(|cookie| cookie.into_owned())
+        .expect("access cookie should be set on re-authentication");
 
     let new_email = unique_email("email-invalid-next");
 
@@ -1760,10 +1978,7 @@ async fn confirm_email_change_with_expired_code_returns_bad_request() {
     assert_eq!(sign_up_response.status(), StatusCode::CREATED);
 
     let confirmation_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::Confirmation && call.to_email == current_email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::Confirmation, &current_email)
         .expect("confirmation email should be captured");
 
     let confirm = test::TestRequest::post()
@@ -1792,6 +2007,19 @@ async fn confirm_email_change_with_expired_code_returns_bad_request() {
         .find(|cookie| cookie.name() == "access_token")
         .map(|cookie| cookie.to_owned())
         .expect("access cookie should be set on login");
+    let reauthenticate = test::TestRequest::post()
+        .uri("/auth/reauthenticate")
+        .cookie(access_cookie.clone())
+        .set_json(json!({ "password": "password123" }))
+        .to_request();
+    let reauthenticate_response = test::call_service(&app, reauthenticate).await;
+    assert_eq!(reauthenticate_response.status(), StatusCode::OK);
+    let access_cookie = reauthenticate_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .map(|cookie| cookie.into_owned())
+        .expect("access cookie should be set on re-authentication");
 
     let new_email = unique_email("email-change-expired-next");
     let request_email_change = test::TestRequest::post()
@@ -1803,10 +2031,7 @@ async fn confirm_email_change_with_expired_code_returns_bad_request() {
     assert_eq!(request_email_change_response.status(), StatusCode::OK);
 
     let email_change_code = mock_email
-        .calls()
-        .into_iter()
-        .find(|call| call.kind == MockEmailKind::EmailChange && call.to_email == new_email)
-        .map(|call| call.code)
+        .last_code(EmailTemplate::EmailChange, &new_email)
         .expect("email-change confirmation email should be captured");
 
     let user_id = user_id_for_email(&pool, &current_email).await;
@@ -1843,3 +2068,3125 @@ async fn confirm_email_change_with_expired_code_returns_bad_request() {
 
     assert_eq!(email_for_user(&pool, user_id).await, current_email);
 }
+
+#[actix_web::test]
+// Verifies phone confirmation stores the normalized number and SMS log-in issues a session.
+async fn phone_confirmation_and_phone_log_in_code_issue_session() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, mock_email, mock_sms) = app_state_with_mock_senders(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("phone-log-in");
+    let sign_up = test::TestRequest::post()
+        .uri("/auth/sign-up")
+        .set_json(json!({
+            "first_name": "Taylor",
+            "last_name": "User",
+            "email": email,
+            "password": "password123",
+            "confirm": "password123"
+        }))
+        .to_request();
+    let sign_up_response = test::call_service(&app, sign_up).await;
+    assert_eq!(sign_up_response.status(), StatusCode::CREATED);
+
+    let confirmation_code = mock_email
+        .last_code(EmailTemplate::Confirmation, &email)
+        .expect("confirmation email should be captured");
+    let confirm = test::TestRequest::post()
+        .uri("/auth/confirm-email")
+        .set_json(json!({ "email": email, "auth_code": confirmation_code }))
+        .to_request();
+    let confirm_response = test::call_service(&app, confirm).await;
+    assert_eq!(confirm_response.status(), StatusCode::OK);
+
+    let login = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let login_response = test::call_service(&app, login).await;
+    assert_eq!(login_response.status(), StatusCode::OK);
+    let access_cookie = login_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .map(|cookie| cookie.to_owned())
+        .expect("access cookie should be set on login");
+
+    let phone_number = unique_phone_number();
+    let formatted_phone_number = format!(
+        "{} ({}) {}-{}",
+        &phone_number[..2],
+        &phone_number[2..5],
+        &phone_number[5..8],
+        &phone_number[8..]
+    );
+
+    // Log-in codes are only sent to confirmed numbers.
+    let early_log_in_code = test::TestRequest::post()
+        .uri("/auth/request-phone-log-in-code")
+        .set_json(json!({ "phone_number": phone_number }))
+        .to_request();
+    let early_log_in_code_response = test::call_service(&app, early_log_in_code).await;
+    assert_eq!(early_log_in_code_response.status(), StatusCode::OK);
+    assert!(mock_sms.calls().is_empty());
+
+    let request_confirmation = test::TestRequest::post()
+        .uri("/auth/request-phone-confirmation")
+        .cookie(access_cookie.clone())
+        .set_json(json!({ "phone_number": formatted_phone_number }))
+        .to_request();
+    let request_confirmation_response = test::call_service(&app, request_confirmation).await;
+    assert_eq!(request_confirmation_response.status(), StatusCode::OK);
+
+    let phone_code = mock_sms
+        .last_code(&phone_number)
+        .expect("phone confirmation sms should be captured");
+
+    let wrong_number = test::TestRequest::post()
+        .uri("/auth/confirm-phone")
+        .cookie(access_cookie.clone())
+        .set_json(json!({ "phone_number": unique_phone_number(), "auth_code": phone_code }))
+        .to_request();
+    let wrong_number_response = test::call_service(&app, wrong_number).await;
+    assert_eq!(wrong_number_response.status(), StatusCode::BAD_REQUEST);
+
+    let confirm_phone = test::TestRequest::post()
+        .uri("/auth/confirm-phone")
+        .cookie(access_cookie.clone())
+        .set_json(json!({ "phone_number": formatted_phone_number, "auth_code": phone_code }))
+        .to_request();
+    let confirm_phone_response = test::call_service(&app, confirm_phone).await;
+    assert_eq!(confirm_phone_response.status(), StatusCode::OK);
+
+    let me = test::TestRequest::get()
+        .uri("/auth/me")
+        .cookie(access_cookie)
+        .to_request();
+    let me_body: serde_json::Value = test::call_and_read_body_json(&app, me).await;
+    assert_eq!(me_body["user"]["phone_number"], phone_number);
+    assert_eq!(me_body["user"]["phone_confirmed"], true);
+
+    let request_log_in_code = test::TestRequest::post()
+        .uri("/auth/request-phone-log-in-code")
+        .set_json(json!({ "phone_number": phone_number }))
+        .to_request();
+    let request_log_in_code_response = test::call_service(&app, request_log_in_code).await;
+    assert_eq!(request_log_in_code_response.status(), StatusCode::OK);
+
+    let log_in_code = mock_sms
+        .last_code(&phone_number)
+        .expect("log-in code sms should be captured");
+    assert!(matches!(
+        mock_sms.calls().last().map(|sms| &sms.message),
+        Some(SmsMessage::LogInCode { .. })
+    ));
+
+    let phone_log_in = test::TestRequest::post()
+        .uri("/auth/log-in-with-phone-code")
+        .set_json(json!({
+            "phone_number": phone_number,
+            "auth_code": log_in_code,
+            "remember_me": true
+        }))
+        .to_request();
+    let phone_log_in_response = test::call_service(&app, phone_log_in).await;
+    assert_eq!(phone_log_in_response.status(), StatusCode::OK);
+
+    let cookie_names: Vec<String> = phone_log_in_response
+        .response()
+        .cookies()
+        .map(|cookie| cookie.name().to_string())
+        .collect();
+    assert!(cookie_names.iter().any(|name| name == "access_token"));
+    assert!(cookie_names.iter().any(|name| name == "refresh_token"));
+
+    let user_id = user_id_for_email(&pool, &email).await;
+    assert_eq!(active_refresh_token_count(&pool, user_id).await, 2);
+
+    let reused_log_in = test::TestRequest::post()
+        .uri("/auth/log-in-with-phone-code")
+        .set_json(json!({ "phone_number": phone_number, "auth_code": log_in_code }))
+        .to_request();
+    let reused_log_in_response = test::call_service(&app, reused_log_in).await;
+    assert_eq!(reused_log_in_response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+// Verifies SMS password reset issues cookies that allow setting a new password.
+async fn forgot_password_by_phone_verifies_code_and_allows_set_password() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email, mock_sms) = app_state_with_mock_senders(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("phone-reset");
+    let phone_number = unique_phone_number();
+    let hashed_password = hash_password("password123").expect("password should hash");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed, phone_number, phone_confirmed) VALUES ('Taylor', 'User', $1, $2, true, $3, true)",
+    )
+    .bind(&email)
+    .bind(&hashed_password)
+    .bind(&phone_number)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+
+    let unknown_number = test::TestRequest::post()
+        .uri("/auth/forgot-password-by-phone")
+        .set_json(json!({ "phone_number": unique_phone_number() }))
+        .to_request();
+    let unknown_number_response = test::call_service(&app, unknown_number).await;
+    assert_eq!(unknown_number_response.status(), StatusCode::OK);
+    assert!(mock_sms.calls().is_empty());
+
+    let forgot_password = test::TestRequest::post()
+        .uri("/auth/forgot-password-by-phone")
+        .set_json(json!({ "phone_number": phone_number }))
+        .to_request();
+    let forgot_password_response = test::call_service(&app, forgot_password).await;
+    assert_eq!(forgot_password_response.status(), StatusCode::OK);
+
+    let reset_code = mock_sms
+        .last_code(&phone_number)
+        .expect("password reset sms should be captured");
+
+    let verify = test::TestRequest::post()
+        .uri("/auth/verify-forgot-password-by-phone")
+        .set_json(json!({ "phone_number": phone_number, "auth_code": reset_code }))
+        .to_request();
+    let verify_response = test::call_service(&app, verify).await;
+    assert_eq!(verify_response.status(), StatusCode::OK);
+
+    let cookies: Vec<_> = verify_response
+        .response()
+        .cookies()
+        .map(|cookie| cookie.into_owned())
+        .collect();
+
+    let mut set_password = test::TestRequest::post()
+        .uri("/auth/set-password")
+        .set_json(json!({ "password": "new-password123", "confirm": "new-password123" }));
+    for cookie in cookies {
+        set_password = set_password.cookie(cookie);
+    }
+    let set_password_response = test::call_service(&app, set_password.to_request()).await;
+    assert_eq!(set_password_response.status(), StatusCode::OK);
+
+    let login = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({ "email": email, "password": "new-password123" }))
+        .to_request();
+    let login_response = test::call_service(&app, login).await;
+    assert_eq!(login_response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+// Verifies a phone number confirmed by one account cannot be claimed by another.
+async fn request_phone_confirmation_for_taken_number_returns_generic_success() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email, mock_sms) = app_state_with_mock_senders(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let phone_number = unique_phone_number();
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed, phone_number, phone_confirmed) VALUES ('Owner', 'User', $1, 'unused', true, $2, true)",
+    )
+    .bind(unique_email("phone-owner"))
+    .bind(&phone_number)
+    .execute(&pool)
+    .await
+    .expect("owner insert should succeed");
+
+    let email = unique_email("phone-claimant");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Claimant', 'User', $1, 'unused', true)",
+    )
+    .bind(&email)
+    .execute(&pool)
+    .await
+    .expect("claimant insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+
+    let access_token = create_access_token(user_id, &email, 0, "integration-test-jwt-secret", 900)
+        .expect("access token should be created");
+
+    let request_confirmation = test::TestRequest::post()
+        .uri("/auth/request-phone-confirmation")
+        .insert_header(("Cookie", format!("access_token={access_token}")))
+        .set_json(json!({ "phone_number": phone_number }))
+        .to_request();
+    let request_confirmation_response = test::call_service(&app, request_confirmation).await;
+    assert_eq!(request_confirmation_response.status(), StatusCode::OK);
+    assert!(mock_sms.calls().is_empty());
+}
+
+#[actix_web::test]
+// Verifies profile updates apply partial changes, bump updated_at, and enforce If-Match.
+async fn update_current_user_applies_changes_and_rejects_stale_etag() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("profile-update");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, 'unused', true)",
+    )
+    .bind(&email)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+    let access_token = create_access_token(user_id, &email, 0, "integration-test-jwt-secret", 900)
+        .expect("access token should be created");
+    let access_cookie = format!("access_token={access_token}");
+
+    let me = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("Cookie", access_cookie.clone()))
+        .to_request();
+    let me_response = test::call_service(&app, me).await;
+    assert_eq!(me_response.status(), StatusCode::OK);
+    let original_etag = me_response
+        .headers()
+        .get("etag")
+        .expect("etag header should be set")
+        .to_str()
+        .expect("etag should be ascii")
+        .to_string();
+
+    let update = test::TestRequest::patch()
+        .uri("/auth/me")
+        .insert_header(("Cookie", access_cookie.clone()))
+        .insert_header(("If-Match", original_etag.clone()))
+        .set_json(json!({
+            "first_name": "Jordan",
+            "display_name": "JT",
+            "locale": "pt_BR",
+            "timezone": "America/Sao_Paulo",
+            "preferences": { "theme": "dark" }
+        }))
+        .to_request();
+    let update_response = test::call_service(&app, update).await;
+    assert_eq!(update_response.status(), StatusCode::OK);
+    let updated_etag = update_response
+        .headers()
+        .get("etag")
+        .expect("etag header should be set")
+        .to_str()
+        .expect("etag should be ascii")
+        .to_string();
+    assert_ne!(updated_etag, original_etag);
+    let updated_last_modified = update_response
+        .headers()
+        .get("last-modified")
+        .expect("last-modified header should be set")
+        .to_str()
+        .expect("last-modified should be ascii")
+        .to_string();
+
+    let body: serde_json::Value = test::read_body_json(update_response).await;
+    assert_eq!(body["user"]["first_name"], "Jordan");
+    assert_eq!(body["user"]["last_name"], "User");
+    assert_eq!(body["user"]["display_name"], "JT");
+    assert_eq!(body["user"]["locale"], "pt-BR");
+    assert_eq!(body["user"]["timezone"], "America/Sao_Paulo");
+    assert_eq!(body["user"]["preferences"], json!({ "theme": "dark" }));
+    assert_ne!(body["user"]["updated_at"], body["user"]["created_at"]);
+
+    let stale_etag = test::TestRequest::patch()
+        .uri("/auth/me")
+        .insert_header(("Cookie", access_cookie.clone()))
+        .insert_header(("If-Match", original_etag))
+        .set_json(json!({ "last_name": "Overwritten" }))
+        .to_request();
+    let stale_etag_response = test::call_service(&app, stale_etag).await;
+    assert_eq!(
+        stale_etag_response.status(),
+        StatusCode::PRECONDITION_FAILED
+    );
+    let body: serde_json::Value = test::read_body_json(stale_etag_response).await;
+    assert_eq!(body["error"]["code"], "PRECONDITION_FAILED");
+
+    let stale_date = test::TestRequest::patch()
+        .uri("/auth/me")
+        .insert_header(("Cookie", access_cookie.clone()))
+        .insert_header(("If-Unmodified-Since", "Sat, 01 Jan 2000 00:00:00 GMT"))
+        .set_json(json!({ "last_name": "Overwritten" }))
+        .to_request();
+    let stale_date_response = test::call_service(&app, stale_date).await;
+    assert_eq!(
+        stale_date_response.status(),
+        StatusCode::PRECONDITION_FAILED
+    );
+
+    let clear_display_name = test::TestRequest::patch()
+        .uri("/auth/me")
+        .insert_header(("Cookie", access_cookie))
+        .insert_header(("If-Unmodified-Since", updated_last_modified))
+        .set_json(json!({ "display_name": null }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, clear_display_name).await;
+    assert_eq!(body["user"]["display_name"], serde_json::Value::Null);
+    assert_eq!(body["user"]["last_name"], "User");
+}
+
+#[actix_web::test]
+// Verifies profile updates reject empty bodies, unknown time zones, and non-object preferences.
+async fn update_current_user_validation_errors_return_bad_request() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("profile-validation");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, 'unused', true)",
+    )
+    .bind(&email)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+    let access_token = create_access_token(user_id, &email, 0, "integration-test-jwt-secret", 900)
+        .expect("access token should be created");
+
+    for (payload, field) in [
+        (json!({}), "empty_update"),
+        (json!({ "timezone": "Mars/Olympus_Mons" }), "timezone"),
+        (json!({ "preferences": ["dark"] }), "preferences"),
+        (json!({ "first_name": "" }), "first_name"),
+    ] {
+        let request = test::TestRequest::patch()
+            .uri("/auth/me")
+            .insert_header(("Cookie", format!("access_token={access_token}")))
+            .set_json(payload)
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body: serde_json::Value = test::read_body_json(response).await;
+        let fields: Vec<&str> = body["errors"]
+            .as_array()
+            .expect("errors should be an array")
+            .iter()
+            .filter_map(|error| error["field"].as_str())
+            .collect();
+        assert!(fields.contains(&field), "expected {field} in {fields:?}");
+    }
+}
+
+#[actix_web::test]
+// Verifies token-in-body log-in and refresh return bearer tokens usable without cookies.
+async fn token_log_in_and_refresh_support_bearer_authentication() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("bearer-token");
+    let hashed_password = hash_password("password123").expect("password should hash");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, $2, true)",
+    )
+    .bind(&email)
+    .bind(&hashed_password)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+
+    let log_in = test::TestRequest::post()
+        .uri("/auth/log-in/token")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let log_in_response = test::call_service(&app, log_in).await;
+    assert_eq!(log_in_response.status(), StatusCode::OK);
+    assert_eq!(log_in_response.response().cookies().count(), 0);
+    assert_eq!(
+        log_in_response
+            .headers()
+            .get("cache-control")
+            .and_then(|value| value.to_str().ok()),
+        Some("no-store")
+    );
+
+    let tokens: serde_json::Value = test::read_body_json(log_in_response).await;
+    assert_eq!(tokens["token_type"], "Bearer");
+    assert_eq!(tokens["expires_in"], 900);
+    assert_eq!(tokens["user_id"], user_id.to_string());
+    let access_token = tokens["access_token"].as_str().expect("access token");
+    let refresh_token = tokens["refresh_token"].as_str().expect("refresh token");
+    assert_eq!(active_refresh_token_count(&pool, user_id).await, 1);
+
+    let me = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("Authorization", format!("Bearer {access_token}")))
+        .to_request();
+    let me_body: serde_json::Value = test::call_and_read_body_json(&app, me).await;
+    assert_eq!(me_body["user"]["email"], email);
+
+    let invalid_bearer = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("Authorization", "Bearer not-a-token"))
+        .to_request();
+    let invalid_bearer_response = test::call_service(&app, invalid_bearer).await;
+    assert_eq!(invalid_bearer_response.status(), StatusCode::UNAUTHORIZED);
+
+    let refresh = test::TestRequest::post()
+        .uri("/auth/refresh/token")
+        .set_json(json!({ "refresh_token": refresh_token }))
+        .to_request();
+    let refresh_response = test::call_service(&app, refresh).await;
+    assert_eq!(refresh_response.status(), StatusCode::OK);
+    assert_eq!(refresh_response.response().cookies().count(), 0);
+
+    let refreshed: serde_json::Value = test::read_body_json(refresh_response).await;
+    let next_refresh_token = refreshed["refresh_token"].as_str().expect("refresh token");
+    assert_ne!(next_refresh_token, refresh_token);
+    assert_eq!(active_refresh_token_count(&pool, user_id).await, 1);
+    assert_eq!(revoked_refresh_token_count(&pool, user_id).await, 1);
+
+    let reused_refresh = test::TestRequest::post()
+        .uri("/auth/refresh/token")
+        .set_json(json!({ "refresh_token": refresh_token }))
+        .to_request();
+    let reused_refresh_response = test::call_service(&app, reused_refresh).await;
+    assert_eq!(reused_refresh_response.status(), StatusCode::UNAUTHORIZED);
+
+    let refreshed_me = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header((
+            "Authorization",
+            format!(
+                "Bearer {}",
+                refreshed["access_token"].as_str().expect("access token")
+            ),
+        ))
+        .to_request();
+    let refreshed_me_response = test::call_service(&app, refreshed_me).await;
+    assert_eq!(refreshed_me_response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+// Verifies API keys can be created, used with their scopes, listed, expired, and revoked.
+async fn api_key_lifecycle_enforces_scopes_expiry_and_revocation() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("api-key");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, 'unused', true)",
+    )
+    .bind(&email)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+    let access_token = create_access_token(user_id, &email, 0, "integration-test-jwt-secret", 900)
+        .expect("access token should be created");
+    let session_header = ("Authorization", format!("Bearer {access_token}"));
+
+    let invalid_scope = test::TestRequest::post()
+        .uri("/auth/api-keys")
+        .insert_header(session_header.clone())
+        .set_json(json!({ "name": "CI", "scopes": ["admin"] }))
+        .to_request();
+    let invalid_scope_response = test::call_service(&app, invalid_scope).await;
+    assert_eq!(invalid_scope_response.status(), StatusCode::BAD_REQUEST);
+
+    let create = test::TestRequest::post()
+        .uri("/auth/api-keys")
+        .insert_header(session_header.clone())
+        .set_json(json!({ "name": "CI", "scopes": ["profile:read"], "expires_in_days": 30 }))
+        .to_request();
+    let create_response = test::call_service(&app, create).await;
+    assert_eq!(create_response.status(), StatusCode::CREATED);
+    let created: serde_json::Value = test::read_body_json(create_response).await;
+    let key = created["key"].as_str().expect("key").to_string();
+    let api_key_id = created["api_key"]["id"].as_str().expect("id").to_string();
+    assert!(key.starts_with(&format!(
+        "ak_{}_",
+        created["api_key"]["prefix"].as_str().expect("prefix")
+    )));
+    assert_eq!(created["api_key"]["scopes"], json!(["profile:read"]));
+    assert!(created["api_key"].get("secret_hash").is_none());
+
+    let me_with_header = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("X-API-Key", key.clone()))
+        .to_request();
+    let me_body: serde_json::Value = test::call_and_read_body_json(&app, me_with_header).await;
+    assert_eq!(me_body["user"]["email"], email);
+
+    let me_with_bearer = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("Authorization", format!("Bearer {key}")))
+        .to_request();
+    let me_with_bearer_response = test::call_service(&app, me_with_bearer).await;
+    assert_eq!(me_with_bearer_response.status(), StatusCode::OK);
+
+    let update_without_scope = test::TestRequest::patch()
+        .uri("/auth/me")
+        .insert_header(("X-API-Key", key.clone()))
+        .set_json(json!({ "first_name": "Script" }))
+        .to_request();
+    let update_without_scope_response = test::call_service(&app, update_without_scope).await;
+    assert_eq!(
+        update_without_scope_response.status(),
+        StatusCode::FORBIDDEN
+    );
+    let body: serde_json::Value = test::read_body_json(update_without_scope_response).await;
+    assert_eq!(body["error"]["code"], "INSUFFICIENT_SCOPE");
+
+    // API keys cannot manage other API keys.
+    let create_with_key = test::TestRequest::post()
+        .uri("/auth/api-keys")
+        .insert_header(("Authorization", format!("Bearer {key}")))
+        .set_json(json!({ "name": "Escalated", "scopes": ["profile:write"] }))
+        .to_request();
+    let create_with_key_response = test::call_service(&app, create_with_key).await;
+    assert_eq!(create_with_key_response.status(), StatusCode::UNAUTHORIZED);
+
+    let list = test::TestRequest::get()
+        .uri("/auth/api-keys")
+        .insert_header(session_header.clone())
+        .to_request();
+    let list_body: serde_json::Value = test::call_and_read_body_json(&app, list).await;
+    let listed = list_body["api_keys"].as_array().expect("api keys array");
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["id"], api_key_id.as_str());
+    assert!(!listed[0]["last_used_at"].is_null());
+
+    sqlx::query("UPDATE api_keys SET expires_at = NOW() - INTERVAL '1 minute' WHERE id = $1::uuid")
+        .bind(&api_key_id)
+        .execute(&pool)
+        .await
+        .expect("expire update should succeed");
+    let expired = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("X-API-Key", key.clone()))
+        .to_request();
+    let expired_response = test::call_service(&app, expired).await;
+    assert_eq!(expired_response.status(), StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = test::read_body_json(expired_response).await;
+    assert_eq!(body["error"]["code"], "TOKEN_EXPIRED");
+
+    let revoke = test::TestRequest::delete()
+        .uri(&format!("/auth/api-keys/{api_key_id}"))
+        .insert_header(session_header.clone())
+        .to_request();
+    let revoke_response = test::call_service(&app, revoke).await;
+    assert_eq!(revoke_response.status(), StatusCode::OK);
+
+    let revoke_again = test::TestRequest::delete()
+        .uri(&format!("/auth/api-keys/{api_key_id}"))
+        .insert_header(session_header)
+        .to_request();
+    let revoke_again_response = test::call_service(&app, revoke_again).await;
+    assert_eq!(revoke_again_response.status(), StatusCode::NOT_FOUND);
+
+    let revoked = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("X-API-Key", key))
+        .to_request();
+    let revoked_response = test::call_service(&app, revoked).await;
+    assert_eq!(revoked_response.status(), StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = test::read_body_json(revoked_response).await;
+    assert_eq!(body["error"]["code"], "TOKEN_INVALID");
+}
+
+#[actix_web::test]
+// Verifies service accounts obtain scoped service tokens that are never accepted as user sessions.
+async fn client_credentials_grant_issues_service_tokens() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("service-account");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, 'unused', true)",
+    )
+    .bind(&email)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+    let access_token = create_access_token(user_id, &email, 0, "integration-test-jwt-secret", 900)
+        .expect("access token should be created");
+    let session_header = ("Authorization", format!("Bearer {access_token}"));
+
+    let create = test::TestRequest::post()
+        .uri("/auth/service-accounts")
+        .insert_header(session_header.clone())
+        .set_json(json!({ "name": "Billing worker", "scopes": ["profile:read", "profile:write"] }))
+        .to_request();
+    let create_response = test::call_service(&app, create).await;
+    assert_eq!(create_response.status(), StatusCode::CREATED);
+    let created: serde_json::Value = test::read_body_json(create_response).await;
+    let client_id = created["service_account"]["client_id"]
+        .as_str()
+        .expect("client id")
+        .to_string();
+    let client_secret = created["client_secret"]
+        .as_str()
+        .expect("secret")
+        .to_string();
+    let service_account_id = created["service_account"]["id"]
+        .as_str()
+        .expect("id")
+        .to_string();
+    assert!(client_id.starts_with("sa_"));
+
+    let wrong_secret = test::TestRequest::post()
+        .uri("/oauth/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id.as_str()),
+            ("client_secret", "wrong"),
+        ])
+        .to_request();
+    let wrong_secret_response = test::call_service(&app, wrong_secret).await;
+    assert_eq!(wrong_secret_response.status(), StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = test::read_body_json(wrong_secret_response).await;
+    assert_eq!(body["error"], "invalid_client");
+
+    let unsupported = test::TestRequest::post()
+        .uri("/oauth/token")
+        .set_form([
+            ("grant_type", "password"),
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+        ])
+        .to_request();
+    let unsupported_response = test::call_service(&app, unsupported).await;
+    assert_eq!(unsupported_response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(unsupported_response).await;
+    assert_eq!(body["error"], "unsupported_grant_type");
+
+    let basic = base64::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        format!("{client_id}:{client_secret}"),
+    );
+    let token = test::TestRequest::post()
+        .uri("/oauth/token")
+        .insert_header(("Authorization", format!("Basic {basic}")))
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("scope", "profile:read"),
+        ])
+        .to_request();
+    let token_response = test::call_service(&app, token).await;
+    assert_eq!(token_response.status(), StatusCode::OK);
+    let token_body: serde_json::Value = test::read_body_json(token_response).await;
+    assert_eq!(token_body["token_type"], "Bearer");
+    assert_eq!(token_body["scope"], "profile:read");
+    let service_token = token_body["access_token"]
+        .as_str()
+        .expect("access token")
+        .to_string();
+
+    let current = test::TestRequest::get()
+        .uri("/auth/service-accounts/me")
+        .insert_header(("Authorization", format!("Bearer {service_token}")))
+        .to_request();
+    let current_body: serde_json::Value = test::call_and_read_body_json(&app, current).await;
+    assert_eq!(current_body["client_id"], client_id.as_str());
+    assert_eq!(current_body["scopes"], json!(["profile:read"]));
+    assert_eq!(
+        current_body["service_account_id"],
+        service_account_id.as_str()
+    );
+
+    // Service tokens never act as a user session, even with profile scopes.
+    let me = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("Authorization", format!("Bearer {service_token}")))
+        .to_request();
+    let me_response = test::call_service(&app, me).await;
+    assert_eq!(me_response.status(), StatusCode::UNAUTHORIZED);
+
+    let session_on_current = test::TestRequest::get()
+        .uri("/auth/service-accounts/me")
+        .insert_header(session_header.clone())
+        .to_request();
+    let session_on_current_response = test::call_service(&app, session_on_current).await;
+    assert_eq!(
+        session_on_current_response.status(),
+        StatusCode::UNAUTHORIZED
+    );
+
+    let excess_scope = test::TestRequest::post()
+        .uri("/oauth/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+            ("scope", "profile:read admin"),
+        ])
+        .to_request();
+    let excess_scope_response = test::call_service(&app, excess_scope).await;
+    assert_eq!(excess_scope_response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(excess_scope_response).await;
+    assert_eq!(body["error"], "invalid_scope");
+
+    let revoke = test::TestRequest::delete()
+        .uri(&format!("/auth/service-accounts/{service_account_id}"))
+        .insert_header(session_header)
+        .to_request();
+    let revoke_response = test::call_service(&app, revoke).await;
+    assert_eq!(revoke_response.status(), StatusCode::OK);
+
+    let revoked_current = test::TestRequest::get()
+        .uri("/auth/service-accounts/me")
+        .insert_header(("Authorization", format!("Bearer {service_token}")))
+        .to_request();
+    let revoked_current_response = test::call_service(&app, revoked_current).await;
+    assert_eq!(revoked_current_response.status(), StatusCode::UNAUTHORIZED);
+
+    let revoked_token = test::TestRequest::post()
+        .uri("/oauth/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+        ])
+        .to_request();
+    let revoked_token_response = test::call_service(&app, revoked_token).await;
+    assert_eq!(revoked_token_response.status(), StatusCode::UNAUTHORIZED);
+}
+
+fn device_token_request(device_code: &str, client_id: &str) -> test::TestRequest {
+    test::TestRequest::post().uri("/oauth/token").set_form([
+        ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+        ("device_code", device_code),
+        ("client_id", client_id),
+    ])
+}
+
+#[actix_web::test]
+// Verifies the device grant polls with pending/slow_down semantics and issues a refreshable session once approved.
+async fn device_authorization_grant_issues_session_after_approval() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("device-grant");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, 'unused', true)",
+    )
+    .bind(&email)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+    let access_token = create_access_token(user_id, &email, 0, "integration-test-jwt-secret", 900)
+        .expect("access token should be created");
+    let session_header = ("Authorization", format!("Bearer {access_token}"));
+
+    let missing_client = test::TestRequest::post()
+        .uri("/oauth/device/code")
+        .set_form([("scope", "")])
+        .to_request();
+    let missing_client_response = test::call_service(&app, missing_client).await;
+    assert_eq!(missing_client_response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(missing_client_response).await;
+    assert_eq!(body["error"], "invalid_request");
+
+    let start = test::TestRequest::post()
+        .uri("/oauth/device/code")
+        .set_form([("client_id", "cli")])
+        .to_request();
+    let started: serde_json::Value = test::call_and_read_body_json(&app, start).await;
+    let device_code = started["device_code"].as_str().expect("device code");
+    let user_code = started["user_code"].as_str().expect("user code");
+    assert_eq!(user_code.len(), 9);
+    assert_eq!(started["verification_uri"], "http://localhost:3000/device");
+    assert_eq!(
+        started["verification_uri_complete"],
+        format!("http://localhost:3000/device?user_code={user_code}")
+    );
+    assert_eq!(started["interval"], 5);
+
+    let response =
+        test::call_service(&app, device_token_request(device_code, "cli").to_request()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["error"], "authorization_pending");
+
+    let response =
+        test::call_service(&app, device_token_request(device_code, "cli").to_request()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["error"], "slow_down");
+
+    let response = test::call_service(
+        &app,
+        device_token_request(device_code, "other-client").to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["error"], "invalid_grant");
+
+    let approve = test::TestRequest::post()
+        .uri("/auth/device/approve")
+        .insert_header(session_header.clone())
+        .set_json(json!({ "user_code": user_code.to_lowercase() }))
+        .to_request();
+    let approved: serde_json::Value = test::call_and_read_body_json(&app, approve).await;
+    assert_eq!(approved["client_id"], "cli");
+
+    let approve_again = test::TestRequest::post()
+        .uri("/auth/device/approve")
+        .insert_header(session_header.clone())
+        .set_json(json!({ "user_code": user_code }))
+        .to_request();
+    let approve_again_response = test::call_service(&app, approve_again).await;
+    assert_eq!(approve_again_response.status(), StatusCode::BAD_REQUEST);
+
+    let interval: i32 = sqlx::query_scalar(
+        "SELECT interval_seconds FROM device_authorizations WHERE user_code = $1",
+    )
+    .bind(user_code.replace('-', ""))
+    .fetch_one(&pool)
+    .await
+    .expect("device authorization should exist");
+    assert_eq!(interval, 10);
+
+    let response =
+        test::call_service(&app, device_token_request(device_code, "cli").to_request()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let tokens: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(tokens["token_type"], "Bearer");
+    assert!(tokens.get("scope").is_none());
+    let device_access_token = tokens["access_token"].as_str().expect("access token");
+    let device_refresh_token = tokens["refresh_token"].as_str().expect("refresh token");
+
+    let me = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("Authorization", format!("Bearer {device_access_token}")))
+        .to_request();
+    let me_body: serde_json::Value = test::call_and_read_body_json(&app, me).await;
+    assert_eq!(me_body["user"]["email"], email);
+
+    let refresh = test::TestRequest::post()
+        .uri("/auth/refresh/token")
+        .set_json(json!({ "refresh_token": device_refresh_token }))
+        .to_request();
+    let refresh_response = test::call_service(&app, refresh).await;
+    assert_eq!(refresh_response.status(), StatusCode::OK);
+
+    let response =
+        test::call_service(&app, device_token_request(device_code, "cli").to_request()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["error"], "invalid_grant");
+
+    // A denied device is told so, and an unused device code expires.
+    let start_denied = test::TestRequest::post()
+        .uri("/oauth/device/code")
+        .set_form([("client_id", "cli")])
+        .to_request();
+    let denied: serde_json::Value = test::call_and_read_body_json(&app, start_denied).await;
+    let deny = test::TestRequest::post()
+        .uri("/auth/device/deny")
+        .insert_header(session_header)
+        .set_json(json!({ "user_code": denied["user_code"] }))
+        .to_request();
+    let deny_response = test::call_service(&app, deny).await;
+    assert_eq!(deny_response.status(), StatusCode::OK);
+    let response = test::call_service(
+        &app,
+        device_token_request(denied["device_code"].as_str().expect("code"), "cli").to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["error"], "access_denied");
+
+    let start_expired = test::TestRequest::post()
+        .uri("/oauth/device/code")
+        .set_form([("client_id", "cli")])
+        .to_request();
+    let expired: serde_json::Value = test::call_and_read_body_json(&app, start_expired).await;
+    sqlx::query(
+        "UPDATE device_authorizations SET expires_at = NOW() - INTERVAL '1 second' WHERE user_code = $1",
+    )
+    .bind(expired["user_code"].as_str().expect("code").replace('-', ""))
+    .execute(&pool)
+    .await
+    .expect("expire update should succeed");
+    let response = test::call_service(
+        &app,
+        device_token_request(expired["device_code"].as_str().expect("code"), "cli").to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["error"], "expired_token");
+}
+
+async fn create_owned_service_account(pool: &Pool<Postgres>, name: &str) -> (String, String) {
+    let owner_email = unique_email("service-account-owner");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Operator', 'User', $1, 'unused', true)",
+    )
+    .bind(&owner_email)
+    .execute(pool)
+    .await
+    .expect("owner insert should succeed");
+    let owner_user_id = user_id_for_email(pool, &owner_email).await;
+
+    let generated = generate_client_credentials();
+    ServiceAccountRepo::create_service_account(
+        pool,
+        owner_user_id,
+        name,
+        &generated.client_id,
+        &generated.client_secret_hash,
+        &["profile:read".to_string()],
+    )
+    .await
+    .expect("service account insert should succeed");
+
+    (generated.client_id, generated.client_secret)
+}
+
+#[actix_web::test]
+// Verifies trusted resource servers can introspect every token type and revoke access and refresh tokens.
+async fn introspection_and_revocation_report_token_state() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (client_id, client_secret) = create_owned_service_account(&pool, "Gateway").await;
+    let (mut state, _mock_email) = app_state_with_mock_email(pool.clone());
+    state.env.oauth_trusted_client_ids = vec![client_id.clone()];
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("introspection");
+    let hashed_password = hash_password("password123").expect("password should hash");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, $2, true)",
+    )
+    .bind(&email)
+    .bind(&hashed_password)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+
+    let log_in = test::TestRequest::post()
+        .uri("/auth/log-in/token")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let tokens: serde_json::Value = test::call_and_read_body_json(&app, log_in).await;
+    let access_token = tokens["access_token"].as_str().expect("access token");
+    let refresh_token = tokens["refresh_token"].as_str().expect("refresh token");
+
+    let introspect = |token: &str, client_id: &str, client_secret: &str| {
+        test::TestRequest::post()
+            .uri("/oauth/introspect")
+            .set_form([
+                ("token", token),
+                ("client_id", client_id),
+                ("client_secret", client_secret),
+            ])
+            .to_request()
+    };
+
+    let unauthenticated =
+        test::call_service(&app, introspect(access_token, &client_id, "wrong")).await;
+    assert_eq!(unauthenticated.status(), StatusCode::UNAUTHORIZED);
+
+    // The user's own service account is not a trusted resource server.
+    let create = test::TestRequest::post()
+        .uri("/auth/service-accounts")
+        .insert_header(("Authorization", format!("Bearer {access_token}")))
+        .set_json(json!({ "name": "Own worker", "scopes": ["profile:read"] }))
+        .to_request();
+    let created: serde_json::Value = test::call_and_read_body_json(&app, create).await;
+    let own_client_id = created["service_account"]["client_id"]
+        .as_str()
+        .expect("client id");
+    let own_client_secret = created["client_secret"].as_str().expect("secret");
+    let untrusted = test::call_service(
+        &app,
+        introspect(access_token, own_client_id, own_client_secret),
+    )
+    .await;
+    assert_eq!(untrusted.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = test::read_body_json(untrusted).await;
+    assert_eq!(body["error"], "unauthorized_client");
+
+    let access: serde_json::Value =
+        test::call_and_read_body_json(&app, introspect(access_token, &client_id, &client_secret))
+            .await;
+    assert_eq!(access["active"], true);
+    assert_eq!(access["sub"], user_id.to_string());
+    assert_eq!(access["username"], email);
+    assert_eq!(access["token_type"], "access_token");
+    assert_eq!(access["scope"], "profile:read profile:write");
+    assert!(access["exp"].as_u64().is_some());
+
+    let refresh: serde_json::Value =
+        test::call_and_read_body_json(&app, introspect(refresh_token, &client_id, &client_secret))
+            .await;
+    assert_eq!(refresh["active"], true);
+    assert_eq!(refresh["sub"], user_id.to_string());
+    assert_eq!(refresh["token_type"], "refresh_token");
+
+    let service_token_request = test::TestRequest::post()
+        .uri("/oauth/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+        ])
+        .to_request();
+    let service_token: serde_json::Value =
+        test::call_and_read_body_json(&app, service_token_request).await;
+    let service: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        introspect(
+            service_token["access_token"].as_str().expect("token"),
+            &client_id,
+            &client_secret,
+        ),
+    )
+    .await;
+    assert_eq!(service["active"], true);
+    assert_eq!(service["client_id"], client_id.as_str());
+    assert_eq!(service["scope"], "profile:read");
+    assert!(service.get("username").is_none());
+
+    let garbage: serde_json::Value =
+        test::call_and_read_body_json(&app, introspect("not-a-token", &client_id, &client_secret))
+            .await;
+    assert_eq!(garbage, json!({ "active": false }));
+
+    let revoke_service = test::TestRequest::post()
+        .uri("/oauth/revoke")
+        .set_form([
+            (
+                "token",
+                service_token["access_token"].as_str().expect("token"),
+            ),
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+        ])
+        .to_request();
+    let revoke_service_response = test::call_service(&app, revoke_service).await;
+    assert_eq!(revoke_service_response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(revoke_service_response).await;
+    assert_eq!(body["error"], "unsupported_token_type");
+
+    let revoke_access = test::TestRequest::post()
+        .uri("/oauth/revoke")
+        .set_form([
+            ("token", access_token),
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+        ])
+        .to_request();
+    let revoke_access_response = test::call_service(&app, revoke_access).await;
+    assert_eq!(revoke_access_response.status(), StatusCode::OK);
+
+    let revoked_access: serde_json::Value =
+        test::call_and_read_body_json(&app, introspect(access_token, &client_id, &client_secret))
+            .await;
+    assert_eq!(revoked_access, json!({ "active": false }));
+
+    let me_after_revoke = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("Authorization", format!("Bearer {access_token}")))
+        .to_request();
+    let me_after_revoke_response = test::call_service(&app, me_after_revoke).await;
+    assert_eq!(me_after_revoke_response.status(), StatusCode::UNAUTHORIZED);
+
+    for _ in 0..2 {
+        let revoke_refresh = test::TestRequest::post()
+            .uri("/oauth/revoke")
+            .set_form([
+                ("token", refresh_token),
+                ("token_type_hint", "refresh_token"),
+                ("client_id", client_id.as_str()),
+                ("client_secret", client_secret.as_str()),
+            ])
+            .to_request();
+        let revoke_refresh_response = test::call_service(&app, revoke_refresh).await;
+        assert_eq!(revoke_refresh_response.status(), StatusCode::OK);
+    }
+    assert_eq!(active_refresh_token_count(&pool, user_id).await, 0);
+
+    let revoked: serde_json::Value =
+        test::call_and_read_body_json(&app, introspect(refresh_token, &client_id, &client_secret))
+            .await;
+    assert_eq!(revoked, json!({ "active": false }));
+
+    let refresh_after_revoke = test::TestRequest::post()
+        .uri("/auth/refresh/token")
+        .set_json(json!({ "refresh_token": refresh_token }))
+        .to_request();
+    let refresh_after_revoke_response = test::call_service(&app, refresh_after_revoke).await;
+    assert_eq!(
+        refresh_after_revoke_response.status(),
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[actix_web::test]
+// Verifies a service account cannot revoke another user's session but can revoke its owner's.
+async fn revocation_is_limited_to_the_client_owner() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let mut sessions = Vec::new();
+    for prefix in ["revocation-victim", "revocation-attacker"] {
+        let email = unique_email(prefix);
+        let hashed_password = hash_password("password123").expect("password should hash");
+        sqlx::query(
+            "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, $2, true)",
+        )
+        .bind(&email)
+        .bind(&hashed_password)
+        .execute(&pool)
+        .await
+        .expect("user insert should succeed");
+
+        let log_in = test::TestRequest::post()
+            .uri("/auth/log-in/token")
+            .set_json(json!({ "email": email, "password": "password123" }))
+            .to_request();
+        let tokens: serde_json::Value = test::call_and_read_body_json(&app, log_in).await;
+
+        let create = test::TestRequest::post()
+            .uri("/auth/service-accounts")
+            .insert_header((
+                "Authorization",
+                format!("Bearer {}", tokens["access_token"].as_str().expect("token")),
+            ))
+            .set_json(json!({ "name": "Worker", "scopes": ["profile:read"] }))
+            .to_request();
+        let created: serde_json::Value = test::call_and_read_body_json(&app, create).await;
+
+        sessions.push((user_id_for_email(&pool, &email).await, tokens, created));
+    }
+    let (victim_id, victim_tokens, victim_client) = &sessions[0];
+    let (_, _, attacker_client) = &sessions[1];
+
+    let revoke = |token: &str, client: &serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/oauth/revoke")
+            .set_form([
+                ("token", token),
+                (
+                    "client_id",
+                    client["service_account"]["client_id"]
+                        .as_str()
+                        .expect("client id"),
+                ),
+                (
+                    "client_secret",
+                    client["client_secret"].as_str().expect("secret"),
+                ),
+            ])
+            .to_request()
+    };
+    let victim_access_token = victim_tokens["access_token"].as_str().expect("token");
+    let victim_refresh_token = victim_tokens["refresh_token"].as_str().expect("token");
+
+    for token in [victim_access_token, victim_refresh_token] {
+        let response = test::call_service(&app, revoke(token, attacker_client)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body: serde_json::Value = test::read_body_json(response).await;
+        assert_eq!(body["error"], "unauthorized_client");
+    }
+    assert_eq!(active_refresh_token_count(&pool, *victim_id).await, 1);
+
+    let me = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("Authorization", format!("Bearer {victim_access_token}")))
+        .to_request();
+    let me_response = test::call_service(&app, me).await;
+    assert_eq!(me_response.status(), StatusCode::OK);
+
+    for token in [victim_access_token, victim_refresh_token] {
+        let response = test::call_service(&app, revoke(token, victim_client)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    assert_eq!(active_refresh_token_count(&pool, *victim_id).await, 0);
+
+    let me_after_revoke = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("Authorization", format!("Bearer {victim_access_token}")))
+        .to_request();
+    let me_after_revoke_response = test::call_service(&app, me_after_revoke).await;
+    assert_eq!(me_after_revoke_response.status(), StatusCode::UNAUTHORIZED);
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+#[actix_web::test]
+// Verifies forward auth accepts cookie and bearer sessions, refreshes cookie sessions, and redirects when asked.
+async fn forward_auth_verify_accepts_and_refreshes_sessions() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("forward-auth");
+    let hashed_password = hash_password("password123").expect("password should hash");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, $2, true)",
+    )
+    .bind(&email)
+    .bind(&hashed_password)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+
+    let log_in = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let log_in_response = test::call_service(&app, log_in).await;
+    assert_eq!(log_in_response.status(), StatusCode::OK);
+    let cookie = |name: &str| {
+        log_in_response
+            .response()
+            .cookies()
+            .find(|cookie| cookie.name() == name)
+            .map(|cookie| cookie.into_owned())
+            .expect("session cookie should be set on login")
+    };
+    let access_cookie = cookie("access_token");
+    let refresh_cookie = cookie("refresh_token");
+
+    let with_cookie = test::TestRequest::get()
+        .uri("/auth/verify")
+        .cookie(access_cookie)
+        .to_request();
+    let with_cookie_response = test::call_service(&app, with_cookie).await;
+    assert_eq!(with_cookie_response.status(), StatusCode::OK);
+    let headers = with_cookie_response.headers();
+    assert_eq!(
+        header_value(headers, "X-User-Id"),
+        Some(user_id.to_string().as_str())
+    );
+    assert_eq!(header_value(headers, "X-User-Email"), Some(email.as_str()));
+    assert_eq!(with_cookie_response.response().cookies().count(), 0);
+
+    let access_token = create_access_token(user_id, &email, 0, "integration-test-jwt-secret", 900)
+        .expect("access token should be created");
+    let with_bearer = test::TestRequest::default()
+        .method(actix_web::http::Method::HEAD)
+        .uri("/auth/verify")
+        .insert_header(("Authorization", format!("Bearer {access_token}")))
+        .to_request();
+    let with_bearer_response = test::call_service(&app, with_bearer).await;
+    assert_eq!(with_bearer_response.status(), StatusCode::OK);
+    assert_eq!(
+        header_value(with_bearer_response.headers(), "X-User-Id"),
+        Some(user_id.to_string().as_str())
+    );
+
+    let refreshed = test::TestRequest::get()
+        .uri("/auth/verify")
+        .cookie(refresh_cookie.clone())
+        .to_request();
+    let refreshed_response = test::call_service(&app, refreshed).await;
+    assert_eq!(refreshed_response.status(), StatusCode::OK);
+    assert_eq!(
+        header_value(refreshed_response.headers(), "X-User-Email"),
+        Some(email.as_str())
+    );
+    let rotated_names: Vec<String> = refreshed_response
+        .response()
+        .cookies()
+        .map(|cookie| cookie.name().to_string())
+        .collect();
+    assert!(rotated_names.contains(&"access_token".to_string()));
+    assert!(rotated_names.contains(&"refresh_token".to_string()));
+    assert_eq!(active_refresh_token_count(&pool, user_id).await, 1);
+    assert_eq!(revoked_refresh_token_count(&pool, user_id).await, 1);
+
+    let replayed = test::TestRequest::get()
+        .uri("/auth/verify")
+        .cookie(refresh_cookie)
+        .to_request();
+    let replayed_response = test::call_service(&app, replayed).await;
+    assert_eq!(replayed_response.status(), StatusCode::UNAUTHORIZED);
+
+    let anonymous = test::TestRequest::get().uri("/auth/verify").to_request();
+    let anonymous_response = test::call_service(&app, anonymous).await;
+    assert_eq!(anonymous_response.status(), StatusCode::UNAUTHORIZED);
+
+    let redirected = test::TestRequest::get()
+        .uri("/auth/verify?redirect=true")
+        .insert_header(("X-Forwarded-Proto", "https"))
+        .insert_header(("X-Forwarded-Host", "app.example.com"))
+        .insert_header(("X-Forwarded-Uri", "/reports?page=2"))
+        .to_request();
+    let redirected_response = test::call_service(&app, redirected).await;
+    assert_eq!(redirected_response.status(), StatusCode::FOUND);
+    assert_eq!(
+        header_value(redirected_response.headers(), "Location"),
+        Some(
+            "http://localhost:3000/auth/log-in?redirect_to=https%3A%2F%2Fapp.example.com%2Freports%3Fpage%3D2"
+        )
+    );
+}
+
+/// Waits for a peer instance's denylist to pick up a revocation notification.
+async fn wait_until_revoked(revocations: &AccessTokenRevocations, claims: &AccessTokenClaims) {
+    for _ in 0..50 {
+        if revocations.is_revoked(claims) {
+            return;
+        }
+        actix_web::rt::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("revocation was not broadcast to the peer instance");
+}
+
+#[actix_web::test]
+// Verifies log-out and password changes revoke access tokens immediately, here and on other instances.
+async fn access_tokens_are_revoked_on_log_out_and_password_change() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let peer = Arc::new(AccessTokenRevocations::new());
+    let peer_listener = peer.clone().listen(pool.clone());
+
+    let email = unique_email("access-revocation");
+    let hashed_password = hash_password("password123").expect("password should hash");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, $2, true)",
+    )
+    .bind(&email)
+    .bind(&hashed_password)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+
+    let log_in = test::TestRequest::post()
+        .uri("/auth/log-in/token")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let tokens: serde_json::Value = test::call_and_read_body_json(&app, log_in).await;
+    let bearer_token = tokens["access_token"].as_str().expect("access token");
+    let bearer_claims = decode_access_token(bearer_token, "integration-test-jwt-secret")
+        .expect("access token should decode");
+    let me_with_bearer = || {
+        test::TestRequest::get()
+            .uri("/auth/me")
+            .insert_header(("Authorization", format!("Bearer {bearer_token}")))
+            .to_request()
+    };
+    assert_eq!(
+        test::call_service(&app, me_with_bearer()).await.status(),
+        StatusCode::OK
+    );
+
+    let log_out = test::TestRequest::post()
+        .uri("/auth/log-out")
+        .insert_header(("Authorization", format!("Bearer {bearer_token}")))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, log_out).await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        test::call_service(&app, me_with_bearer()).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+    wait_until_revoked(&peer, &bearer_claims).await;
+
+    let cookie_log_in = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let cookie_log_in_response = test::call_service(&app, cookie_log_in).await;
+    let cookie = |response: &actix_web::dev::ServiceResponse, name: &str| {
+        response
+            .response()
+            .cookies()
+            .find(|cookie| cookie.name() == name)
+            .map(|cookie| cookie.into_owned())
+            .expect("session cookie should be set")
+    };
+    let old_access_cookie = cookie(&cookie_log_in_response, "access_token");
+    let old_refresh_cookie = cookie(&cookie_log_in_response, "refresh_token");
+    let old_claims = decode_access_token(old_access_cookie.value(), "integration-test-jwt-secret")
+        .expect("access token should decode");
+
+    let change_password = test::TestRequest::post()
+        .uri("/auth/change-password")
+        .cookie(old_access_cookie.clone())
+        .cookie(old_refresh_cookie)
+        .set_json(json!({
+            "current_password": "password123",
+            "new_password": "new-password-123",
+            "confirm": "new-password-123"
+        }))
+        .to_request();
+    let change_password_response = test::call_service(&app, change_password).await;
+    assert_eq!(change_password_response.status(), StatusCode::OK);
+    let new_access_cookie = cookie(&change_password_response, "access_token");
+
+    let me_with_old_cookie = test::TestRequest::get()
+        .uri("/auth/me")
+        .cookie(old_access_cookie)
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, me_with_old_cookie).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+    let me_with_new_cookie = test::TestRequest::get()
+        .uri("/auth/me")
+        .cookie(new_access_cookie)
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, me_with_new_cookie).await.status(),
+        StatusCode::OK
+    );
+    wait_until_revoked(&peer, &old_claims).await;
+
+    peer_listener.abort();
+    let _ = peer_listener.await;
+}
+
+#[actix_web::test]
+// Verifies bumping a user's token version rejects older access tokens, on this and other instances.
+async fn token_version_bump_rejects_older_access_tokens() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("token-version");
+    let hashed_password = hash_password("password123").expect("password should hash");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, $2, true)",
+    )
+    .bind(&email)
+    .bind(&hashed_password)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+
+    let log_in = test::TestRequest::post()
+        .uri("/auth/log-in/token")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let tokens: serde_json::Value = test::call_and_read_body_json(&app, log_in).await;
+    let old_token = tokens["access_token"].as_str().expect("access token");
+    let old_header = ("Authorization", format!("Bearer {old_token}"));
+
+    let reauthenticate = test::TestRequest::post()
+        .uri("/auth/reauthenticate")
+        .insert_header(old_header.clone())
+        .set_json(json!({ "password": "password123" }))
+        .to_request();
+    let reauthenticated: serde_json::Value =
+        test::call_and_read_body_json(&app, reauthenticate).await;
+    let reauthenticated_token = reauthenticated["access_token"]
+        .as_str()
+        .expect("re-authenticated access token");
+
+    let new_email = unique_email("token-version-next");
+    let request_email_change = test::TestRequest::post()
+        .uri("/auth/request-email-change")
+        .insert_header(("Authorization", format!("Bearer {reauthenticated_token}")))
+        .set_json(json!({ "new_email": new_email }))
+        .to_request();
+    let request_email_change_response = test::call_service(&app, request_email_change).await;
+    assert_eq!(request_email_change_response.status(), StatusCode::OK);
+    let email_change_code = mock_email
+        .last_code(EmailTemplate::EmailChange, &new_email)
+        .expect("email-change confirmation email should be captured");
+
+    let confirm_email_change = test::TestRequest::post()
+        .uri("/auth/confirm-email-change")
+        .insert_header(old_header.clone())
+        .set_json(json!({ "new_email": new_email, "auth_code": email_change_code }))
+        .to_request();
+    let confirm_email_change_response = test::call_service(&app, confirm_email_change).await;
+    assert_eq!(confirm_email_change_response.status(), StatusCode::OK);
+    let new_access_cookie = confirm_email_change_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .map(|cookie| cookie.into_owned())
+        .expect("access cookie should be reissued");
+
+    let me_with_old_token = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(old_header)
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, me_with_old_token).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+    let me_with_new_cookie = test::TestRequest::get()
+        .uri("/auth/me")
+        .cookie(new_access_cookie.clone())
+        .to_request();
+    let me: serde_json::Value = test::call_and_read_body_json(&app, me_with_new_cookie).await;
+    assert_eq!(me["user"]["email"], new_email.as_str());
+
+    // An administrator bumps the version directly; a fresh instance has nothing cached.
+    sqlx::query("UPDATE users SET token_version = token_version + 1 WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .expect("token version bump should succeed");
+    let (other_state, _other_mock_email) = app_state_with_mock_email(pool.clone());
+    let other_app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(other_state))
+            .configure(configure_routes),
+    )
+    .await;
+    let me_after_bump = test::TestRequest::get()
+        .uri("/auth/me")
+        .cookie(new_access_cookie)
+        .to_request();
+    assert_eq!(
+        test::call_service(&other_app, me_after_bump).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[actix_web::test]
+// Verifies cookie sessions need a trusted origin and matching CSRF header, bearer requests are exempt, and log-out clears the CSRF cookie.
+async fn csrf_protection_guards_cookie_authenticated_requests() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .wrap(from_fn(csrf_protection))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("csrf");
+    let hashed_password = hash_password("password123").expect("password should hash");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, $2, true)",
+    )
+    .bind(&email)
+    .bind(&hashed_password)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+
+    let log_in = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let log_in_response = test::call_service(&app, log_in).await;
+    assert_eq!(log_in_response.status(), StatusCode::OK);
+    let cookie = |name: &str| {
+        log_in_response
+            .response()
+            .cookies()
+            .find(|cookie| cookie.name() == name)
+            .map(|cookie| cookie.into_owned())
+            .expect("cookie should be set on login")
+    };
+    let refresh_cookie = cookie("refresh_token");
+    let csrf_cookie = cookie("csrf_token");
+    assert_ne!(csrf_cookie.http_only(), Some(true));
+    assert_eq!(csrf_cookie.max_age(), refresh_cookie.max_age());
+    let csrf_token = csrf_cookie.value().to_string();
+    assert_eq!(csrf_token.len(), 32);
+
+    let missing_header = test::TestRequest::post()
+        .uri("/auth/refresh")
+        .cookie(refresh_cookie.clone())
+        .cookie(csrf_cookie.clone())
+        .insert_header(("Origin", "http://localhost:3000"))
+        .to_request();
+    let missing_header_response = test::call_service(&app, missing_header).await;
+    assert_eq!(missing_header_response.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = test::read_body_json(missing_header_response).await;
+    assert_eq!(body["error"]["code"], "CSRF_VALIDATION_FAILED");
+
+    let wrong_origin = test::TestRequest::post()
+        .uri("/auth/refresh")
+        .cookie(refresh_cookie.clone())
+        .cookie(csrf_cookie.clone())
+        .insert_header(("Origin", "https://attacker.example"))
+        .insert_header(("X-CSRF-Token", csrf_token.as_str()))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, wrong_origin).await.status(),
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(active_refresh_token_count(&pool, user_id).await, 1);
+
+    // Only the forward-auth endpoint is exempt, not every `/auth/verify*` route.
+    let verify_forgot_password = test::TestRequest::post()
+        .uri("/auth/verify-forgot-password")
+        .cookie(refresh_cookie.clone())
+        .cookie(csrf_cookie.clone())
+        .insert_header(("Origin", "http://localhost:3000"))
+        .set_json(json!({ "email": email, "code": "123456" }))
+        .to_request();
+    let verify_forgot_password_response = test::call_service(&app, verify_forgot_password).await;
+    assert_eq!(
+        verify_forgot_password_response.status(),
+        StatusCode::FORBIDDEN
+    );
+    let body: serde_json::Value = test::read_body_json(verify_forgot_password_response).await;
+    assert_eq!(body["error"]["code"], "CSRF_VALIDATION_FAILED");
+
+    let valid = test::TestRequest::post()
+        .uri("/auth/refresh")
+        .cookie(refresh_cookie.clone())
+        .cookie(csrf_cookie.clone())
+        .insert_header(("Referer", "http://localhost:3000/settings"))
+        .insert_header(("X-CSRF-Token", csrf_token.as_str()))
+        .to_request();
+    let valid_response = test::call_service(&app, valid).await;
+    assert_eq!(valid_response.status(), StatusCode::OK);
+    let rotated_refresh_cookie = valid_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "refresh_token")
+        .map(|cookie| cookie.into_owned())
+        .expect("refresh cookie should be rotated");
+    let reissued_csrf_token = valid_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "csrf_token")
+        .map(|cookie| cookie.value().to_string());
+    assert_eq!(reissued_csrf_token.as_deref(), Some(csrf_token.as_str()));
+
+    let access_token = create_access_token(user_id, &email, 0, "integration-test-jwt-secret", 900)
+        .expect("access token should be created");
+    let bearer_log_out = test::TestRequest::post()
+        .uri("/auth/log-out")
+        .cookie(rotated_refresh_cookie)
+        .insert_header(("Origin", "https://attacker.example"))
+        .insert_header(("Authorization", format!("Bearer {access_token}")))
+        .to_request();
+    let bearer_log_out_response = test::call_service(&app, bearer_log_out).await;
+    assert_eq!(bearer_log_out_response.status(), StatusCode::OK);
+    let cleared_csrf_cookie = bearer_log_out_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "csrf_token")
+        .map(|cookie| cookie.into_owned())
+        .expect("CSRF cookie should be cleared on log-out");
+    assert_eq!(cleared_csrf_cookie.value(), "");
+    assert_eq!(
+        cleared_csrf_cookie.max_age(),
+        Some(actix_web::cookie::time::Duration::ZERO)
+    );
+}
+
+#[actix_web::test]
+// Verifies re-authentication by password or SMS code unlocks logging out everywhere and deleting the account.
+async fn step_up_reauthentication_guards_sensitive_actions() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _mock_email, mock_sms) = app_state_with_mock_senders(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("step-up");
+    let phone_number = unique_phone_number();
+    let hashed_password = hash_password("password123").expect("password should hash");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed, phone_number, phone_confirmed) VALUES ('Taylor', 'User', $1, $2, true, $3, true)",
+    )
+    .bind(&email)
+    .bind(&hashed_password)
+    .bind(&phone_number)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+
+    let log_in = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let log_in_response = test::call_service(&app, log_in).await;
+    assert_eq!(log_in_response.status(), StatusCode::OK);
+    let access_cookie = log_in_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .map(|cookie| cookie.into_owned())
+        .expect("access cookie should be set on login");
+
+    let log_out_everywhere = test::TestRequest::post()
+        .uri("/auth/log-out-everywhere")
+        .cookie(access_cookie.clone())
+        .to_request();
+    let log_out_everywhere_response = test::call_service(&app, log_out_everywhere).await;
+    assert_eq!(log_out_everywhere_response.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = test::read_body_json(log_out_everywhere_response).await;
+    assert_eq!(body["error"]["code"], "REAUTHENTICATION_REQUIRED");
+
+    let delete_account = test::TestRequest::delete()
+        .uri("/auth/me")
+        .cookie(access_cookie.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, delete_account).await.status(),
+        StatusCode::FORBIDDEN
+    );
+
+    let wrong_password = test::TestRequest::post()
+        .uri("/auth/reauthenticate")
+        .cookie(access_cookie.clone())
+        .set_json(json!({ "password": "wrong-password" }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, wrong_password).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+
+    let both_factors = test::TestRequest::post()
+        .uri("/auth/reauthenticate")
+        .cookie(access_cookie.clone())
+        .set_json(json!({ "password": "password123", "auth_code": "123456" }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, both_factors).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+
+    let reauthenticate = test::TestRequest::post()
+        .uri("/auth/reauthenticate")
+        .cookie(access_cookie.clone())
+        .set_json(json!({ "password": "password123" }))
+        .to_request();
+    let reauthenticate_response = test::call_service(&app, reauthenticate).await;
+    assert_eq!(reauthenticate_response.status(), StatusCode::OK);
+    let reauthenticated_cookie = reauthenticate_response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .map(|cookie| cookie.into_owned())
+        .expect("access cookie should be set on re-authentication");
+    let claims = decode_access_token(
+        reauthenticated_cookie.value(),
+        "integration-test-jwt-secret",
+    )
+    .expect("re-authenticated token should decode");
+    assert_eq!(claims.amr, vec!["pwd".to_string()]);
+    assert_eq!(claims.auth_time, Some(claims.iat));
+
+    // Per-user revocations cut off at whole seconds.
+    actix_web::rt::time::sleep(Duration::from_millis(1100)).await;
+
+    let log_out_everywhere = test::TestRequest::post()
+        .uri("/auth/log-out-everywhere")
+        .cookie(reauthenticated_cookie.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, log_out_everywhere).await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(active_refresh_token_count(&pool, user_id).await, 0);
+
+    let me = test::TestRequest::get()
+        .uri("/auth/me")
+        .cookie(access_cookie)
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, me).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+
+    let log_in = test::TestRequest::post()
+        .uri("/auth/log-in/token")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let tokens: serde_json::Value = test::call_and_read_body_json(&app, log_in).await;
+    let access_token = tokens["access_token"].as_str().expect("access token");
+
+    let request_code = test::TestRequest::post()
+        .uri("/auth/request-phone-log-in-code")
+        .set_json(json!({ "phone_number": phone_number }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, request_code).await.status(),
+        StatusCode::OK
+    );
+    let log_in_code = mock_sms
+        .last_code(&phone_number)
+        .expect("log-in code SMS should be captured");
+
+    let reauthenticate = test::TestRequest::post()
+        .uri("/auth/reauthenticate")
+        .insert_header(("Authorization", format!("Bearer {access_token}")))
+        .set_json(json!({ "auth_code": log_in_code }))
+        .to_request();
+    let reauthenticate_response = test::call_service(&app, reauthenticate).await;
+    assert_eq!(reauthenticate_response.status(), StatusCode::OK);
+    assert_eq!(reauthenticate_response.response().cookies().count(), 0);
+    let reauthenticated: serde_json::Value = test::read_body_json(reauthenticate_response).await;
+    let reauthenticated_token = reauthenticated["access_token"]
+        .as_str()
+        .expect("re-authenticated access token");
+    let claims = decode_access_token(reauthenticated_token, "integration-test-jwt-secret")
+        .expect("re-authenticated token should decode");
+    assert_eq!(claims.amr, vec!["sms".to_string()]);
+
+    let delete_account = test::TestRequest::delete()
+        .uri("/auth/me")
+        .insert_header(("Authorization", format!("Bearer {reauthenticated_token}")))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, delete_account).await.status(),
+        StatusCode::OK
+    );
+    let remaining_users: i64 =
+        sqlx::query_scalar("SELECT COUNT(*)::bigint FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .expect("count query should succeed");
+    assert_eq!(remaining_users, 0);
+
+    let me = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("Authorization", format!("Bearer {access_token}")))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, me).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[actix_web::test]
+// Verifies the password policy rejects weak passwords with one field error per failed rule.
+async fn password_policy_failures_are_returned_per_rule() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (mut state, _) = app_state_with_mock_email(pool.clone());
+    state.env.password_min_strength_score = 2;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let field_errors = |body: serde_json::Value, field: &str| -> Vec<String> {
+        body.get("errors")
+            .and_then(|value| value.as_array())
+            .expect("validation errors should be returned as an array")
+            .iter()
+            .inspect(|error| assert_eq!(error.get("field"), Some(&json!(field))))
+            .filter_map(|error| error.get("message").and_then(|m| m.as_str()))
+            .map(str::to_string)
+            .collect()
+    };
+
+    let email = unique_email("password-policy");
+    let sign_up = |password: &str| {
+        test::TestRequest::post()
+            .uri("/auth/sign-up")
+            .set_json(json!({
+                "first_name": "Jane",
+                "last_name": "Whitfield",
+                "email": email,
+                "password": password,
+                "confirm": password
+            }))
+            .to_request()
+    };
+
+    let weak_response = test::call_service(&app, sign_up("whitfield")).await;
+    assert_eq!(weak_response.status(), StatusCode::BAD_REQUEST);
+    let messages = field_errors(test::read_body_json(weak_response).await, "password");
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].starts_with("Password must mix at least 2"));
+    assert!(messages[1].starts_with("Password is too easy to guess"));
+
+    let strong_response = test::call_service(&app, sign_up("Velvet-Harbor-42")).await;
+    assert_eq!(strong_response.status(), StatusCode::CREATED);
+
+    sqlx::query("UPDATE users SET email_confirmed = true WHERE email = $1")
+        .bind(&email)
+        .execute(&pool)
+        .await
+        .expect("email confirmation should succeed");
+
+    let login = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({
+            "email": email,
+            "password": "Velvet-Harbor-42"
+        }))
+        .to_request();
+    let login_response = test::call_service(&app, login).await;
+    assert_eq!(login_response.status(), StatusCode::OK);
+    let cookies: Vec<_> = login_response
+        .response()
+        .cookies()
+        .map(|cookie| cookie.into_owned())
+        .collect();
+
+    let mut change_password = test::TestRequest::post()
+        .uri("/auth/change-password")
+        .set_json(json!({
+            "current_password": "Velvet-Harbor-42",
+            "new_password": "Jane.Whitfield1",
+            "confirm": "Jane.Whitfield1"
+        }));
+    for cookie in &cookies {
+        change_password = change_password.cookie(cookie.clone());
+    }
+    let change_password_response = test::call_service(&app, change_password.to_request()).await;
+    assert_eq!(change_password_response.status(), StatusCode::BAD_REQUEST);
+    let messages = field_errors(
+        test::read_body_json(change_password_response).await,
+        "new_password",
+    );
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].starts_with("New password is too easy to guess"));
+
+    // The rejected change must leave the session usable.
+    assert_eq!(
+        active_refresh_token_count(&pool, user_id_for_email(&pool, &email).await).await,
+        1
+    );
+}
+
+#[actix_web::test]
+// Verifies breached passwords are rejected when chosen and flag a forced reset at log-in.
+async fn breached_passwords_are_rejected_and_flagged_at_log_in() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+
+    let breached_password = "Velvet-Harbor-42";
+    let corpus = format!(
+        "{}:12\n",
+        hex::encode_upper(Sha1::digest(breached_password.as_bytes()))
+    );
+    let index_path = std::env::temp_dir().join(format!("breached-{}.idx", Uuid::new_v4()));
+    let index_file = std::fs::File::create(&index_path).expect("index file should be created");
+    build_index(std::io::Cursor::new(corpus), index_file, 1).expect("index should build");
+    let index = BreachedPasswordIndex::open(&index_path).expect("index should open");
+
+    let (mut state, _) = app_state_with_mock_email(pool.clone());
+    state.env.breached_passwords_check_on_log_in = true;
+    let state = state.with_breached_passwords(Some(Arc::new(index)));
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let sign_up = test::TestRequest::post()
+        .uri("/auth/sign-up")
+        .set_json(json!({
+            "first_name": "Taylor",
+            "last_name": "User",
+            "email": unique_email("breached-sign-up"),
+            "password": breached_password,
+            "confirm": breached_password
+        }))
+        .to_request();
+    let sign_up_response = test::call_service(&app, sign_up).await;
+    assert_eq!(sign_up_response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(sign_up_response).await;
+    assert_eq!(
+        body["errors"],
+        json!([{
+            "field": "password",
+            "message": "Password has appeared in a data breach; choose a different password"
+        }])
+    );
+
+    // An account whose password was chosen before it showed up in a breach.
+    let email = unique_email("breached-log-in");
+    let hashed_password = hash_password(breached_password).expect("password should hash");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, $2, true)",
+    )
+    .bind(&email)
+    .bind(&hashed_password)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+
+    let login = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({ "email": email, "password": breached_password }))
+        .to_request();
+    let login_response = test::call_service(&app, login).await;
+    assert_eq!(login_response.status(), StatusCode::OK);
+    let cookies: Vec<_> = login_response
+        .response()
+        .cookies()
+        .map(|cookie| cookie.into_owned())
+        .collect();
+    let body: serde_json::Value = test::read_body_json(login_response).await;
+    assert_eq!(body["password_reset_required"], json!(true));
+
+    let access_cookie = cookies
+        .iter()
+        .find(|cookie| cookie.name() == "access_token")
+        .expect("access cookie should be set on login")
+        .clone();
+    let me = test::TestRequest::get()
+        .uri("/auth/me")
+        .cookie(access_cookie)
+        .to_request();
+    let me_body: serde_json::Value = test::call_and_read_body_json(&app, me).await;
+    assert_eq!(me_body["user"]["password_reset_required"], json!(true));
+
+    let mut set_password = test::TestRequest::post()
+        .uri("/auth/set-password")
+        .set_json(json!({
+            "password": "Quiet-Lantern-7",
+            "confirm": "Quiet-Lantern-7"
+        }));
+    for cookie in &cookies {
+        set_password = set_password.cookie(cookie.clone());
+    }
+    let set_password_response = test::call_service(&app, set_password.to_request()).await;
+    assert_eq!(set_password_response.status(), StatusCode::OK);
+
+    let password_reset_required: bool =
+        sqlx::query_scalar("SELECT password_reset_required FROM users WHERE email = $1")
+            .bind(&email)
+            .fetch_one(&pool)
+            .await
+            .expect("reset flag should be queryable");
+    assert!(!password_reset_required);
+
+    std::fs::remove_file(index_path).expect("index file should be removed");
+}
+
+#[actix_web::test]
+// Verifies change-password rejects the current and recent passwords and keeps only the last N hashes.
+async fn password_history_prevents_reuse() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (mut state, _) = app_state_with_mock_email(pool.clone());
+    state.env.password_history_size = 2;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("password-history");
+    let hashed_password = hash_password("password123").expect("password should hash");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, $2, true)",
+    )
+    .bind(&email)
+    .bind(&hashed_password)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+    let user_id = user_id_for_email(&pool, &email).await;
+
+    let login = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let login_response = test::call_service(&app, login).await;
+    assert_eq!(login_response.status(), StatusCode::OK);
+    let mut cookies: Vec<_> = login_response
+        .response()
+        .cookies()
+        .map(|cookie| cookie.into_owned())
+        .collect();
+
+    let mut current_password = "password123".to_string();
+    for (new_password, expected_status) in [
+        ("password123", StatusCode::BAD_REQUEST),
+        ("first-new-password-1", StatusCode::OK),
+        ("password123", StatusCode::BAD_REQUEST),
+        ("second-new-password-2", StatusCode::OK),
+        ("third-new-password-3", StatusCode::OK),
+        ("first-new-password-1", StatusCode::OK),
+    ] {
+        let mut change_password = test::TestRequest::post()
+            .uri("/auth/change-password")
+            .set_json(json!({
+                "current_password": current_password,
+                "new_password": new_password,
+                "confirm": new_password
+            }));
+        for cookie in &cookies {
+            change_password = change_password.cookie(cookie.clone());
+        }
+        let response = test::call_service(&app, change_password.to_request()).await;
+        assert_eq!(
+            response.status(),
+            expected_status,
+            "changing to {new_password}"
+        );
+
+        if expected_status == StatusCode::OK {
+            current_password = new_password.to_string();
+            cookies = response
+                .response()
+                .cookies()
+                .map(|cookie| cookie.into_owned())
+                .collect();
+        } else {
+            let body: serde_json::Value = test::read_body_json(response).await;
+            assert_eq!(
+                body["errors"],
+                json!([{
+                    "field": "new_password",
+                    "message": "New password must differ from your last 2 passwords"
+                }])
+            );
+        }
+    }
+
+    let history_count: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM password_history WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .expect("password history should be queryable");
+    assert_eq!(history_count, 2);
+}
+
+#[actix_web::test]
+// Verifies log-in upgrades hashes made with outdated Argon2 parameters once the password is verified.
+async fn log_in_rehashes_outdated_password_hashes() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("argon2-rehash");
+    let legacy_params = PasswordHashParams {
+        memory_kib: 8192,
+        iterations: 1,
+        parallelism: 1,
+        ..PasswordHashParams::default()
+    };
+    let legacy_hash =
+        hash_password_with_params("password123", &legacy_params).expect("password should hash");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, $2, true)",
+    )
+    .bind(&email)
+    .bind(&legacy_hash)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+
+    let wrong_password = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({ "email": email, "password": "wrong-password" }))
+        .to_request();
+    let response = test::call_service(&app, wrong_password).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(hashed_password_for_email(&pool, &email).await, legacy_hash);
+
+    for _ in 0..2 {
+        let login = test::TestRequest::post()
+            .uri("/auth/log-in")
+            .set_json(json!({ "email": email, "password": "password123" }))
+            .to_request();
+        let response = test::call_service(&app, login).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let upgraded_hash = hashed_password_for_email(&pool, &email).await;
+        assert!(upgraded_hash.starts_with("$argon2id$v=19$m=19456,t=2,p=1$"));
+    }
+}
+
+#[actix_web::test]
+// Verifies imported users with bcrypt hashes are confirmed, can log in, and get upgraded to Argon2id.
+async fn imported_users_log_in_with_legacy_hashes() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, _) = app_state_with_mock_email(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("imported-bcrypt");
+    let existing_email = unique_email("imported-existing");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, $2, true)",
+    )
+    .bind(&existing_email)
+    .bind(hash_password("password123").expect("password should hash"))
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+
+    let bcrypt_hash = bcrypt::hash("password123", 4).expect("bcrypt should hash");
+    let csv = format!(
+        "email,first_name,last_name,hashed_password,locale\n\
+         {},Ada,Lovelace,{},es\n\
+         not-an-email,Grace,Hopper,{},\n\
+         {},Taylor,User,{},\n\
+         unsupported@example.com,Alan,Turing,$1$saltsalt$qjXMvbEw8oaL.CzflDugX/,\n",
+        email.to_uppercase(),
+        bcrypt_hash,
+        bcrypt_hash,
+        existing_email,
+        bcrypt_hash
+    );
+    let rows = read_users(csv.as_bytes(), ImportFormat::Csv).expect("CSV should read");
+
+    let dry_run = import_users(&pool, rows, true)
+        .await
+        .expect("dry run should succeed");
+    assert_eq!(dry_run.imported, 1);
+    let user_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE email = $1")
+        .bind(&email)
+        .fetch_one(&pool)
+        .await
+        .expect("users should be queryable");
+    assert_eq!(user_count, 0);
+
+    let rows = read_users(csv.as_bytes(), ImportFormat::Csv).expect("CSV should read");
+    let summary = import_users(&pool, rows, false)
+        .await
+        .expect("import should succeed");
+    assert_eq!(summary.imported, 1);
+    let skipped: Vec<(u64, &str)> = summary
+        .skipped
+        .iter()
+        .map(|row| (row.line, row.reason.as_str()))
+        .collect();
+    assert_eq!(
+        skipped,
+        vec![
+            (3, "Email is invalid"),
+            (
+                4,
+                format!("A user with email {} already exists", existing_email).as_str()
+            ),
+            (5, "Password hash format is not supported"),
+        ]
+    );
+    assert!(email_confirmed_for_user(&pool, &email).await);
+    assert_eq!(hashed_password_for_email(&pool, &email).await, bcrypt_hash);
+
+    let login = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let response = test::call_service(&app, login).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        hashed_password_for_email(&pool, &email)
+            .await
+            .starts_with("$argon2id$")
+    );
+}
+
+#[actix_web::test]
+// Verifies log-in re-peppers hashes made with an older pepper version after rotation.
+async fn log_in_repeppers_hashes_after_rotation() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (mut state, _) = app_state_with_mock_email(pool.clone());
+    state.env.password_peppers = "1:old-pepper,2:new-pepper"
+        .parse()
+        .expect("peppers should parse");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("pepper-rotation");
+    let old_params = PasswordHashParams {
+        peppers: "1:old-pepper".parse().expect("peppers should parse"),
+        ..PasswordHashParams::default()
+    };
+    let old_hash =
+        hash_password_with_params("password123", &old_params).expect("password should hash");
+    sqlx::query(
+        "INSERT INTO users (first_name, last_name, email, hashed_password, email_confirmed) VALUES ('Taylor', 'User', $1, $2, true)",
+    )
+    .bind(&email)
+    .bind(&old_hash)
+    .execute(&pool)
+    .await
+    .expect("user insert should succeed");
+
+    for _ in 0..2 {
+        let login = test::TestRequest::post()
+            .uri("/auth/log-in")
+            .set_json(json!({ "email": email, "password": "password123" }))
+            .to_request();
+        let response = test::call_service(&app, login).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let repeppered_hash = hashed_password_for_email(&pool, &email).await;
+        assert!(repeppered_hash.contains(",keyid=Mg$"));
+    }
+}
+
+#[actix_web::test]
+// Verifies configured alphanumeric codes are stored keyed and unkeyed hashes only verify once marked legacy.
+async fn auth_codes_use_configured_format_and_keyed_hashes() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (mut state, mock_email) = app_state_with_mock_email(pool.clone());
+    state.env.auth_code_length = 8;
+    state.env.auth_code_alphabet = UNAMBIGUOUS_ALPHANUMERIC_ALPHABET.to_string();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("keyed-code");
+    let legacy_email = unique_email("legacy-code");
+    for email in [&email, &legacy_email] {
+        let sign_up = test::TestRequest::post()
+            .uri("/auth/sign-up")
+            .set_json(json!({
+                "first_name": "Taylor",
+                "last_name": "User",
+                "email": email,
+                "password": "password123",
+                "confirm": "password123"
+            }))
+            .to_request();
+        let response = test::call_service(&app, sign_up).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    let code = mock_email
+        .last_code(EmailTemplate::Confirmation, &email)
+        .expect("confirmation email should be captured");
+    assert_eq!(code.len(), 8);
+    assert!(
+        code.chars()
+            .all(|c| UNAMBIGUOUS_ALPHANUMERIC_ALPHABET.contains(c))
+    );
+
+    let user_id = user_id_for_email(&pool, &email).await;
+    let code_hash: String = sqlx::query_scalar(
+        "SELECT code_hash FROM auth_codes WHERE user_id = $1 AND code_type = 'email_confirmation'",
+    )
+    .bind(user_id)
+    .fetch_one(&pool)
+    .await
+    .expect("auth code should exist");
+    assert!(code_hash.starts_with("v2$"));
+    assert!(!code_hash.contains(&hex::encode(sha2::Sha256::digest(code.as_bytes()))));
+
+    let typed_code = format!("{}-{}", &code[..4], &code[4..]).to_lowercase();
+    let confirm = test::TestRequest::post()
+        .uri("/auth/confirm-email")
+        .set_json(json!({ "email": email, "auth_code": typed_code }))
+        .to_request();
+    let response = test::call_service(&app, confirm).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Codes issued before the upgrade were stored as plain SHA-256, which only
+    // verifies once the keyed-hash migration has marked it `v1$`.
+    let legacy_user_id = user_id_for_email(&pool, &legacy_email).await;
+    sqlx::query("UPDATE auth_codes SET code_hash = $2 WHERE user_id = $1")
+        .bind(legacy_user_id)
+        .bind(hex::encode(sha2::Sha256::digest(b"482913")))
+        .execute(&pool)
+        .await
+        .expect("auth code update should succeed");
+
+    let confirm_legacy = || {
+        test::TestRequest::post()
+            .uri("/auth/confirm-email")
+            .set_json(json!({ "email": legacy_email, "auth_code": "482913" }))
+            .to_request()
+    };
+    let response = test::call_service(&app, confirm_legacy()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    sqlx::raw_sql(include_str!(
+        "../migrations/20261018200000_mark_legacy_auth_code_hashes.sql"
+    ))
+    .execute(&pool)
+    .await
+    .expect("legacy hash migration should run");
+
+    let response = test::call_service(&app, confirm_legacy()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(email_confirmed_for_user(&pool, &legacy_email).await);
+}
+
+#[actix_web::test]
+// Verifies log-in answers unknown emails like wrong passwords, after comparable hashing work.
+async fn log_in_does_not_reveal_unknown_emails() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (state, mock_email) = app_state_with_mock_email(pool);
+    let hash_params = state.password_hash_params();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("login-enumeration");
+    let sign_up = test::TestRequest::post()
+        .uri("/auth/sign-up")
+        .set_json(json!({
+            "first_name": "Taylor",
+            "last_name": "User",
+            "email": email,
+            "password": "password123",
+            "confirm": "password123"
+        }))
+        .to_request();
+    let response = test::call_service(&app, sign_up).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let code = mock_email
+        .last_code(EmailTemplate::Confirmation, &email)
+        .expect("confirmation email should be captured");
+    let confirm = test::TestRequest::post()
+        .uri("/auth/confirm-email")
+        .set_json(json!({ "email": email, "auth_code": code }))
+        .to_request();
+    let response = test::call_service(&app, confirm).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let hash = hash_password_with_params("password123", &hash_params).expect("should hash");
+    let started = Instant::now();
+    verify_password_with_params("wrong-password", &hash, &hash_params).expect("should verify");
+    let verification_time = started.elapsed();
+
+    let mut responses = Vec::new();
+    for email in [email, unique_email("login-unknown")] {
+        let log_in = test::TestRequest::post()
+            .uri("/auth/log-in")
+            .set_json(json!({ "email": email, "password": "wrong-password" }))
+            .to_request();
+        let started = Instant::now();
+        let response = test::call_service(&app, log_in).await;
+        let elapsed = started.elapsed();
+        let status = response.status();
+        let body: serde_json::Value = test::read_body_json(response).await;
+        responses.push((status, body, elapsed));
+    }
+
+    let (known_status, known_body, _) = &responses[0];
+    let (unknown_status, unknown_body, unknown_elapsed) = &responses[1];
+    assert_eq!(*known_status, StatusCode::UNAUTHORIZED);
+    assert_eq!(unknown_status, known_status);
+    assert_eq!(unknown_body, known_body);
+    assert!(*unknown_elapsed >= verification_time / 2);
+}
+
+#[actix_web::test]
+// Verifies public code requests and code verification answer unknown accounts, missing codes and wrong codes alike.
+async fn code_verification_does_not_reveal_accounts() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (mut state, mock_email, _mock_sms) = app_state_with_mock_senders(pool);
+    state.env.auth_code_min_response_ms = 150;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let pending_email = unique_email("enumeration-pending");
+    let confirmed_email = unique_email("enumeration-confirmed");
+    for email in [&pending_email, &confirmed_email] {
+        let sign_up = test::TestRequest::post()
+            .uri("/auth/sign-up")
+            .set_json(json!({
+                "first_name": "Taylor",
+                "last_name": "User",
+                "email": email,
+                "password": "password123",
+                "confirm": "password123"
+            }))
+            .to_request();
+        let response = test::call_service(&app, sign_up).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+    let code = mock_email
+        .last_code(EmailTemplate::Confirmation, &confirmed_email)
+        .expect("confirmation email should be captured");
+    let confirm = test::TestRequest::post()
+        .uri("/auth/confirm-email")
+        .set_json(json!({ "email": confirmed_email, "auth_code": code }))
+        .to_request();
+    let response = test::call_service(&app, confirm).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Code requests take the minimum time whether or not a code is sent.
+    let unknown_email = unique_email("enumeration-unknown");
+    let mut request_bodies = Vec::new();
+    for email in [confirmed_email.as_str(), unknown_email.as_str()] {
+        let forgot = test::TestRequest::post()
+            .uri("/auth/forgot-password")
+            .set_json(json!({ "email": email }))
+            .to_request();
+        let started = Instant::now();
+        let response = test::call_service(&app, forgot).await;
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(response).await;
+        request_bodies.push(body);
+    }
+    assert_eq!(request_bodies[0], request_bodies[1]);
+    assert!(
+        mock_email
+            .last_code(EmailTemplate::PasswordReset, &unknown_email)
+            .is_none()
+    );
+
+    let unknown_phone_number = unique_phone_number();
+    for uri in [
+        "/auth/forgot-password-by-phone",
+        "/auth/request-phone-log-in-code",
+    ] {
+        let request = test::TestRequest::post()
+            .uri(uri)
+            .set_json(json!({ "phone_number": unknown_phone_number }))
+            .to_request();
+        let started = Instant::now();
+        let response = test::call_service(&app, request).await;
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let attempts = [
+        // Unknown account, wrong code, already-confirmed account.
+        ("/auth/confirm-email", unknown_email.as_str()),
+        ("/auth/confirm-email", pending_email.as_str()),
+        ("/auth/confirm-email", confirmed_email.as_str()),
+        // Unknown account, no reset code, wrong reset code.
+        ("/auth/verify-forgot-password", unknown_email.as_str()),
+        ("/auth/verify-forgot-password", pending_email.as_str()),
+        ("/auth/verify-forgot-password", confirmed_email.as_str()),
+    ];
+
+    let mut bodies = Vec::new();
+    for (uri, email) in attempts {
+        let request = test::TestRequest::post()
+            .uri(uri)
+            .set_json(json!({ "email": email, "auth_code": "000000" }))
+            .to_request();
+        let started = Instant::now();
+        let response = test::call_service(&app, request).await;
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(response).await;
+        bodies.push(body);
+    }
+
+    assert_eq!(
+        bodies[0]
+            .get("error")
+            .and_then(|error| error.get("code"))
+            .and_then(|code| code.as_str()),
+        Some("INVALID_AUTH_CODE")
+    );
+    assert!(bodies.iter().all(|body| body == &bodies[0]));
+}
+
+#[actix_web::test]
+// Verifies a slow email provider does not delay code requests for existing accounts.
+async fn slow_code_delivery_does_not_reveal_accounts() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let mut env = test_env();
+    env.auth_code_min_response_ms = 150;
+    let mock_email = Arc::new(MockEmailSender::with_delay(Duration::from_millis(800)));
+    let state = AppState::with_email_sender(pool, env, mock_email.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("slow-delivery");
+    let sign_up = test::TestRequest::post()
+        .uri("/auth/sign-up")
+        .set_json(json!({
+            "first_name": "Taylor",
+            "last_name": "User",
+            "email": email,
+            "password": "password123",
+            "confirm": "password123"
+        }))
+        .to_request();
+    let response = test::call_service(&app, sign_up).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    for email in [
+        email.as_str(),
+        unique_email("slow-delivery-unknown").as_str(),
+    ] {
+        let forgot = test::TestRequest::post()
+            .uri("/auth/forgot-password")
+            .set_json(json!({ "email": email }))
+            .to_request();
+        let started = Instant::now();
+        let response = test::call_service(&app, forgot).await;
+        let elapsed = started.elapsed();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(elapsed >= Duration::from_millis(150));
+        assert!(elapsed < Duration::from_millis(600));
+    }
+    assert!(
+        mock_email
+            .last_code(EmailTemplate::PasswordReset, &email)
+            .is_none()
+    );
+
+    // The reset email still arrives once the provider finishes.
+    actix_web::rt::time::sleep(Duration::from_millis(1000)).await;
+    assert!(
+        mock_email
+            .last_code(EmailTemplate::PasswordReset, &email)
+            .is_some()
+    );
+}
+
+#[actix_web::test]
+// Verifies sign-up asks for proof of work past the threshold and accepts each solution once.
+async fn sign_up_requires_proof_of_work_past_threshold() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (mut state, _) = app_state_with_mock_email(pool);
+    state.env.human_verification_threshold = 2;
+    let state = state.with_human_verifier(Arc::new(ProofOfWorkVerifier::new(b"secret", 8)));
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    // Each request claims a new forwarded address, which must not reset the
+    // per-client count since the peer is not a trusted proxy.
+    let forwarded_for = std::cell::Cell::new(0);
+    let sign_up = |email: String, answer: Option<String>| {
+        forwarded_for.set(forwarded_for.get() + 1);
+        test::TestRequest::post()
+            .uri("/auth/sign-up")
+            .peer_addr("203.0.113.7:40000".parse().expect("peer address parses"))
+            .insert_header((
+                "X-Forwarded-For",
+                format!("198.51.100.{}", forwarded_for.get()),
+            ))
+            .set_json(json!({
+                "first_name": "Taylor",
+                "last_name": "User",
+                "email": email,
+                "password": "password123",
+                "confirm": "password123",
+                "human_verification": answer
+            }))
+            .to_request()
+    };
+
+    for _ in 0..2 {
+        let response = test::call_service(&app, sign_up(unique_email("pow"), None)).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    let response = test::call_service(&app, sign_up(unique_email("pow"), None)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["error"]["code"], "HUMAN_VERIFICATION_REQUIRED");
+    let challenge = &body["error"]["challenge"];
+    assert_eq!(challenge["kind"], "proof_of_work");
+    assert_eq!(challenge["difficulty"], 8);
+
+    let answer = solve_proof_of_work(
+        challenge["challenge"]
+            .as_str()
+            .expect("challenge should be a string"),
+        8,
+    );
+    let email = unique_email("pow");
+    let response = test::call_service(&app, sign_up(email.clone(), Some(answer.clone()))).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = test::call_service(&app, sign_up(unique_email("pow"), Some(answer))).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["error"]["code"], "HUMAN_VERIFICATION_REQUIRED");
+}
+
+/// Form posted to the stub verification service.
+#[derive(serde::Deserialize)]
+struct SiteVerifyForm {
+    secret: String,
+    response: String,
+}
+
+/// Stub `siteverify` endpoint accepting only the token `stub-pass`.
+async fn stub_site_verify(form: web::Form<SiteVerifyForm>) -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "success": form.secret == "stub-secret" && form.response == "stub-pass"
+    }))
+}
+
+#[actix_web::test]
+// Verifies forgot-password checks CAPTCHA tokens with the HTTP verifier past the per-email threshold.
+async fn forgot_password_uses_http_human_verifier_past_threshold() {
+    let _guard = test_guard();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("stub should bind");
+    let stub_url = format!(
+        "http://{}/siteverify",
+        listener.local_addr().expect("stub should have an address")
+    );
+    let stub = actix_web::HttpServer::new(|| {
+        App::new().route("/siteverify", web::post().to(stub_site_verify))
+    })
+    .listen(listener)
+    .expect("stub should listen")
+    .workers(1)
+    .run();
+    actix_web::rt::spawn(stub);
+
+    let pool = test_pool().await;
+    let (mut state, mock_email) = app_state_with_mock_email(pool);
+    state.env.human_verification_threshold = 1;
+    let state = state.with_human_verifier(Arc::new(HttpHumanVerifier::new(
+        &stub_url,
+        "stub-secret",
+        Some("stub-site-key"),
+    )));
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("captcha");
+    let sign_up = test::TestRequest::post()
+        .uri("/auth/sign-up")
+        .set_json(json!({
+            "first_name": "Taylor",
+            "last_name": "User",
+            "email": email,
+            "password": "password123",
+            "confirm": "password123"
+        }))
+        .to_request();
+    let response = test::call_service(&app, sign_up).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let forgot = |answer: Option<&str>| {
+        test::TestRequest::post()
+            .uri("/auth/forgot-password")
+            .set_json(json!({ "email": email, "human_verification": answer }))
+            .to_request()
+    };
+
+    let response = test::call_service(&app, forgot(None)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    for answer in [None, Some("stub-fail")] {
+        let response = test::call_service(&app, forgot(answer)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body: serde_json::Value = test::read_body_json(response).await;
+        assert_eq!(body["error"]["code"], "HUMAN_VERIFICATION_REQUIRED");
+        assert_eq!(
+            body["error"]["challenge"],
+            json!({ "kind": "captcha", "site_key": "stub-site-key" })
+        );
+    }
+    let sent_emails = mock_email.calls().len();
+
+    let response = test::call_service(&app, forgot(Some("stub-pass"))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(mock_email.calls().len(), sent_emails + 1);
+}
+
+#[actix_web::test]
+// Verifies sign-up rejects disposable, denylisted and (in allowlist-only mode) unlisted domains on `email`.
+async fn sign_up_enforces_email_domain_policy() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (mut state, mock_email) = app_state_with_mock_email(pool);
+    state.env.block_disposable_email_domains = true;
+    state.env.email_domain_denylist =
+        parse_domain_patterns("*.blocked.test").expect("should parse");
+    let state = state.with_disposable_email_domains(Some(Arc::new(DisposableEmailDomains::parse(
+        "throwaway.test\n",
+    ))));
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let sign_up = |email: &str| {
+        test::TestRequest::post()
+            .uri("/auth/sign-up")
+            .set_json(json!({
+                "first_name": "Taylor",
+                "last_name": "User",
+                "email": email,
+                "password": "password123",
+                "confirm": "password123"
+            }))
+            .to_request()
+    };
+
+    for (email, message) in [
+        (
+            "taylor@inbox.throwaway.test",
+            "Disposable email addresses are not accepted",
+        ),
+        (
+            "taylor@mail.blocked.test",
+            "Email addresses from this domain are not accepted",
+        ),
+    ] {
+        let response = test::call_service(&app, sign_up(email)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(response).await;
+        assert_eq!(
+            body["errors"],
+            json!([{ "field": "email", "message": message }])
+        );
+    }
+    assert!(mock_email.calls().is_empty());
+
+    let response = test::call_service(&app, sign_up(&unique_email("domain-policy"))).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let mut state = state;
+    state.env.email_domain_allowlist =
+        parse_domain_patterns("*.example.com").expect("should parse");
+    state.env.email_domain_allowlist_only = true;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let response = test::call_service(&app, sign_up(&unique_email("domain-policy"))).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(
+        body["errors"],
+        json!([{
+            "field": "email",
+            "message": "Email addresses from this domain cannot be used here"
+        }])
+    );
+
+    let email = format!("taylor-{}@eng.example.com", Uuid::new_v4());
+    let response = test::call_service(&app, sign_up(&email)).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+}
+
+#[actix_web::test]
+// Verifies email-change requests reject disposable domains on `new_email` before creating a code.
+async fn request_email_change_enforces_email_domain_policy() {
+    let _guard = test_guard();
+    let pool = test_pool().await;
+    let (mut state, mock_email) = app_state_with_mock_email(pool.clone());
+    state.env.block_disposable_email_domains = true;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(configure_routes),
+    )
+    .await;
+
+    let email = unique_email("domain-change");
+    let sign_up = test::TestRequest::post()
+        .uri("/auth/sign-up")
+        .set_json(json!({
+            "first_name": "Taylor",
+            "last_name": "User",
+            "email": email,
+            "password": "password123",
+            "confirm": "password123"
+        }))
+        .to_request();
+    let response = test::call_service(&app, sign_up).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let code = mock_email
+        .last_code(EmailTemplate::Confirmation, &email)
+        .expect("confirmation email should be captured");
+    let confirm = test::TestRequest::post()
+        .uri("/auth/confirm-email")
+        .set_json(json!({ "email": email, "auth_code": code }))
+        .to_request();
+    let response = test::call_service(&app, confirm).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let log_in = test::TestRequest::post()
+        .uri("/auth/log-in")
+        .set_json(json!({ "email": email, "password": "password123" }))
+        .to_request();
+    let response = test::call_service(&app, log_in).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let access_cookie = response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .map(|cookie| cookie.into_owned())
+        .expect("access cookie should be set on login");
+    let reauthenticate = test::TestRequest::post()
+        .uri("/auth/reauthenticate")
+        .cookie(access_cookie)
+        .set_json(json!({ "password": "password123" }))
+        .to_request();
+    let response = test::call_service(&app, reauthenticate).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let access_cookie = response
+        .response()
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .map(|cookie| cookie.into_owned())
+        .expect("access cookie should be set on re-authentication");
+
+    let request_email_change = test::TestRequest::post()
+        .uri("/auth/request-email-change")
+        .cookie(access_cookie)
+        .set_json(json!({ "new_email": "taylor@mailinator.com" }))
+        .to_request();
+    let response = test::call_service(&app, request_email_change).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(
+        body["errors"],
+        json!([{
+            "field": "new_email",
+            "message": "Disposable email addresses are not accepted"
+        }])
+    );
+
+    let user_id = user_id_for_email(&pool, &email).await;
+    let email_change_codes: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*)::bigint FROM auth_codes WHERE user_id = $1 AND code_type = 'email_change'",
+    )
+    .bind(user_id)
+    .fetch_one(&pool)
+    .await
+    .expect("auth code count query should succeed");
+    assert_eq!(email_change_codes, 0);
+}
@@ -6,6 +6,9 @@ const apiBaseUrl = import.meta.env.VITE_API_BASE_URL ?? "/api";
 const api = axios.create({
     baseURL: apiBaseUrl,
     withCredentials: true,
+    xsrfCookieName: "csrf_token",
+    xsrfHeaderName: "X-CSRF-Token",
+    withXSRFToken: true,
 });
 
 type RetryableRequestConfig = InternalAxiosRequestConfig & {
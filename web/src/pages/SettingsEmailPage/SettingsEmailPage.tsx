@@ -14,6 +14,7 @@ import { settingsSections } from "@/pages/SettingsPage/settingsSections";
 
 type RequestEmailChangeFormData = {
     new_email: string;
+    password: string;
 };
 
 type ConfirmEmailChangeFormData = {
@@ -46,7 +47,7 @@ type RequestEmailChangeMutationResult = {
  * ## Related Components
  *
  * - `Form` - Handles email change request/confirm submissions.
- * - `TextInput` - Captures new email and confirmation code fields.
+ * - `TextInput` - Captures new email, current password, and confirmation code fields.
  * - `Button` - Triggers email change actions.
  */
 function SettingsEmailPage() {
@@ -61,6 +62,7 @@ function SettingsEmailPage() {
         setErrors: setRequestEmailErrors,
     } = useForm<RequestEmailChangeFormData>({
         new_email: "",
+        password: "",
     });
     const {
         data: confirmEmailData,
@@ -78,6 +80,11 @@ function SettingsEmailPage() {
         mutationFn: async () => {
             const normalizedEmail = requestEmailData.new_email.trim().toLowerCase();
 
+            // Email changes require a recent re-authentication.
+            await api.post("/auth/reauthenticate", {
+                password: requestEmailData.password,
+            });
+
             const response = await api.post<RequestEmailChangeResponse>(
                 "/auth/request-email-change",
                 {
@@ -93,6 +100,7 @@ function SettingsEmailPage() {
         onSuccess: ({ message, normalizedEmail }) => {
             setPendingEmailChange(normalizedEmail);
             setRequestEmailData("new_email", normalizedEmail);
+            setRequestEmailData("password", "");
             setConfirmEmailData("auth_code", "");
 
             addNotification({
@@ -214,6 +222,14 @@ function SettingsEmailPage() {
                                 setData={setRequestEmailData}
                                 errors={requestEmailErrors}
                             />
+                            <TextInput
+                                name="password"
+                                placeholder="Current Password"
+                                password
+                                data={requestEmailData}
+                                setData={setRequestEmailData}
+                                errors={requestEmailErrors}
+                            />
                             <Button type="submit">
                                 {pendingEmailChange
                                     ? "Resend Confirmation Code"
@@ -84,14 +84,21 @@ export const RequestsEmailChangeCodeAndShowsConfirmStep: Story = {
         try {
             const canvas = within(canvasElement);
             await userEvent.type(canvas.getByPlaceholderText("New Email"), "NEW@EMAIL.COM");
+            await userEvent.type(canvas.getByPlaceholderText("Current Password"), "password123");
             await userEvent.click(
                 canvas.getByRole("button", { name: "Send Confirmation Code" }),
             );
 
             await waitFor(() => {
-                expect(postCalls).toHaveLength(1);
+                expect(postCalls).toHaveLength(2);
             });
             await expect(postCalls[0]).toEqual({
+                url: "/auth/reauthenticate",
+                data: {
+                    password: "password123",
+                },
+            });
+            await expect(postCalls[1]).toEqual({
                 url: "/auth/request-email-change",
                 data: {
                     new_email: "new@email.com",
@@ -116,20 +123,25 @@ export const RequestsEmailChangeCodeAndShowsConfirmStep: Story = {
 
 export const ShowsEmailRequestValidationErrors: Story = {
     play: async ({ canvasElement }) => {
-        const restorePost = mockApiPostHandler(() =>
-            Promise.reject(
+        const restorePost = mockApiPostHandler((url) => {
+            if (url === "/auth/reauthenticate") {
+                return Promise.resolve(createMockApiResponse({ message: "Identity confirmed." }));
+            }
+
+            return Promise.reject(
                 createValidationAxiosError([
                     {
                         field: "new_email",
                         message: "Email is invalid",
                     },
                 ]),
-            ),
-        );
+            );
+        });
 
         try {
             const canvas = within(canvasElement);
             await userEvent.type(canvas.getByPlaceholderText("New Email"), "not-an-email");
+            await userEvent.type(canvas.getByPlaceholderText("Current Password"), "password123");
             await userEvent.click(
                 canvas.getByRole("button", { name: "Send Confirmation Code" }),
             );
@@ -181,6 +193,7 @@ export const ConfirmsEmailChangeAndRefreshesUser: Story = {
         try {
             const canvas = within(canvasElement);
             await userEvent.type(canvas.getByPlaceholderText("New Email"), "next@example.com");
+            await userEvent.type(canvas.getByPlaceholderText("Current Password"), "password123");
             await userEvent.click(
                 canvas.getByRole("button", { name: "Send Confirmation Code" }),
             );
@@ -191,15 +204,21 @@ export const ConfirmsEmailChangeAndRefreshesUser: Story = {
             );
 
             await waitFor(() => {
-                expect(postCalls).toHaveLength(2);
+                expect(postCalls).toHaveLength(3);
             });
             await expect(postCalls[0]).toEqual({
+                url: "/auth/reauthenticate",
+                data: {
+                    password: "password123",
+                },
+            });
+            await expect(postCalls[1]).toEqual({
                 url: "/auth/request-email-change",
                 data: {
                     new_email: "next@example.com",
                 },
             });
-            await expect(postCalls[1]).toEqual({
+            await expect(postCalls[2]).toEqual({
                 url: "/auth/confirm-email-change",
                 data: {
                     new_email: "next@example.com",
@@ -224,6 +243,10 @@ export const ShowsEmailConfirmValidationErrors: Story = {
         const restorePost = mockApiPostHandler((url, data) => {
             postCalls.push({ url, data });
 
+            if (url === "/auth/reauthenticate") {
+                return Promise.resolve(createMockApiResponse({ message: "Identity confirmed." }));
+            }
+
             if (url === "/auth/request-email-change") {
                 return Promise.resolve(
                     createMockApiResponse({
@@ -245,6 +268,7 @@ export const ShowsEmailConfirmValidationErrors: Story = {
         try {
             const canvas = within(canvasElement);
             await userEvent.type(canvas.getByPlaceholderText("New Email"), "next@example.com");
+            await userEvent.type(canvas.getByPlaceholderText("Current Password"), "password123");
             await userEvent.click(
                 canvas.getByRole("button", { name: "Send Confirmation Code" }),
             );
@@ -254,7 +278,7 @@ export const ShowsEmailConfirmValidationErrors: Story = {
             );
 
             await waitFor(() => {
-                expect(postCalls).toHaveLength(2);
+                expect(postCalls).toHaveLength(3);
             });
             await expect(canvas.getByText("Auth code is required")).toBeVisible();
         } finally {